@@ -1,11 +1,16 @@
-use proc_macro2::Ident;
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, ToTokens};
 use syn::parse::{Parse, ParseStream};
-use syn::token::{For, Impl};
+use syn::token::{Comma, Crate, Eq, For, Impl};
+use syn::Path;
 
 pub struct EvaluatorAttributes {
     pub implement_struct: bool,
     pub ident: Ident,
     pub operator_name: String,
+    /// Path of the tidal_core crate in the generated code, `::tidal_core` unless overridden
+    /// with `crate = path`
+    pub krate: TokenStream,
 }
 
 impl Parse for EvaluatorAttributes {
@@ -15,10 +20,25 @@ impl Parse for EvaluatorAttributes {
         let _: For = input.parse()?;
         let operator_ident: Ident = input.parse()?;
 
+        let krate = if input.parse::<Option<Comma>>()?.is_some() {
+            let _: Crate = input.parse()?;
+            let _: Eq = input.parse()?;
+
+            if input.peek(Crate) {
+                let krate: Crate = input.parse()?;
+                krate.into_token_stream()
+            } else {
+                input.parse::<Path>()?.into_token_stream()
+            }
+        } else {
+            quote! { ::tidal_core }
+        };
+
         Ok(Self {
             implement_struct: not_implement_struct.is_none(),
             ident,
             operator_name: operator_ident.to_string(),
+            krate,
         })
     }
 }
@@ -12,10 +12,34 @@ impl TryFrom<&TypePath> for DataType {
             return Ok(DataType::Scalar);
         }
 
+        if helper::path_ends_with(&value.path, "i32") {
+            return Ok(DataType::Int);
+        }
+
+        if helper::path_ends_with(&value.path, "bool") {
+            return Ok(DataType::Bool);
+        }
+
+        if helper::path_ends_with(&value.path, "String") {
+            return Ok(DataType::String);
+        }
+
+        if helper::path_ends_with(&value.path, "Vector2") {
+            return Ok(DataType::Vector2);
+        }
+
         if helper::path_ends_with(&value.path, "Vector3") {
             return Ok(DataType::Vector);
         }
 
+        if helper::path_ends_with(&value.path, "Vector4") {
+            return Ok(DataType::Vector4);
+        }
+
+        if helper::path_ends_with(&value.path, "Color") {
+            return Ok(DataType::Color);
+        }
+
         if helper::path_ends_with(&value.path, "CommandList") {
             return Ok(DataType::Command);
         }
@@ -25,6 +49,34 @@ impl TryFrom<&TypePath> for DataType {
                 if helper::path_ends_with(&ty.path, "Mesh") {
                     return Ok(DataType::Mesh);
                 }
+
+                if helper::path_ends_with(&ty.path, "Texture") {
+                    return Ok(DataType::Texture);
+                }
+
+                if helper::path_ends_with(&ty.path, "ScalarCurve") {
+                    return Ok(DataType::ScalarCurve);
+                }
+
+                if helper::path_ends_with(&ty.path, "VectorCurve") {
+                    return Ok(DataType::VectorCurve);
+                }
+
+                if helper::path_ends_with(&ty.path, "Timeline") {
+                    return Ok(DataType::Timeline);
+                }
+
+                if helper::path_ends_with(&ty.path, "Expression") {
+                    return Ok(DataType::Expression);
+                }
+            }
+        }
+
+        // Any other plain type is taken as an enum, the generated code requiring it implements
+        // `OperatorEnum`.
+        if let Some(segment) = value.path.segments.last() {
+            if segment.arguments.is_empty() {
+                return Ok(DataType::Enum);
             }
         }
 
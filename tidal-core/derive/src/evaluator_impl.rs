@@ -70,10 +70,31 @@ pub fn derive_evaluator_impl(
         .collect::<Vec<_>>();
     let input_data_type: Vec<DataType> = inputs.iter().map(|i| i.data_type).collect();
     let input_default_value: Vec<&DefaultValue> = inputs.iter().map(|i| &i.default_value).collect();
+    let input_description: Vec<TokenStream> = inputs
+        .iter()
+        .map(|i| optional_str(i.description.as_deref()))
+        .collect();
+    let input_hint = inputs.iter().map(|i| &i.hint);
+    let input_required = inputs.iter().map(|i| i.required);
+    let input_multiple = inputs.iter().map(|i| i.multiple);
+    let input_optional = inputs.iter().map(|i| i.optional);
+    let input_variants = inputs.iter().map(|i| match i.data_type {
+        DataType::Enum => {
+            let ty = &i.ty;
+            quote! { <#ty as OperatorEnum>::VARIANTS }
+        }
+        _ => quote! { &[] },
+    });
 
     let input_call = inputs.iter().enumerate().map(|(port, i)| {
-        if i.multiple {
+        if i.lazy && i.multiple {
+            quote! { ctx.lazy_input_multiple(#port) }
+        } else if i.lazy {
+            quote! { ctx.lazy_input(#port) }
+        } else if i.multiple {
             quote! { ctx.evaluate_input_multiple(#port)?.try_into()? }
+        } else if i.optional {
+            quote! { ctx.evaluate_optional_input(#port)?.map(TryInto::try_into).transpose()? }
         } else {
             quote! { ctx.evaluate_input(#port)?.try_into()? }
         }
@@ -117,6 +138,32 @@ pub fn derive_evaluator_impl(
     };
 
     let operator_name = args.operator_name;
+    let description = optional_str(helper::doc_comment(&item.attrs).as_deref());
+    let category = item
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("category"))
+        .map(|attr| attr.parse_args::<LitStr>())
+        .transpose()?
+        .map(|category| category.value());
+    let category = optional_str(category.as_deref());
+    let krate = args.krate;
+
+    // Ports depending on the node's inputs
+    let describe = item
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("describe"))
+        .map(|attr| attr.parse_args::<syn::Path>())
+        .transpose()?
+        .map(|describe| {
+            quote! {
+                #krate::inventory::submit!(NodeMetadataRegistryNode {
+                    operator: #operator_name,
+                    describe: #describe
+                });
+            }
+        });
 
     // Build
     let mut tokens = TokenStream::new();
@@ -126,7 +173,7 @@ pub fn derive_evaluator_impl(
     item.attrs.retain(helper::retain_attributes);
     item.sig.inputs.iter_mut().for_each(|input| match input {
         FnArg::Receiver(_) => unreachable!(),
-        FnArg::Typed(ty) => ty.attrs.retain(helper::retain_attributes),
+        FnArg::Typed(ty) => ty.attrs.retain(helper::retain_parameter_attributes),
     });
 
     // Build fn
@@ -144,9 +191,9 @@ pub fn derive_evaluator_impl(
     // Build struct impl
     tokens.extend(quote! {
         const _: () = {
-            use crate::interpreter::*;
-            use crate::operator::*;
-            use crate::graph::*;
+            use #krate::interpreter::*;
+            use #krate::operator::*;
+            use #krate::graph::*;
 
             #item
 
@@ -160,7 +207,7 @@ pub fn derive_evaluator_impl(
                     #lhs = #rhs;
 
                     #(
-                        ctx.write_output(#output_port, #output_ident.into());
+                        ctx.write_output(#output_port, #output_ident.into())?;
                     )*
 
                     Ok(())
@@ -175,22 +222,27 @@ pub fn derive_evaluator_impl(
                 fn create_metadata() -> Metadata {
                     Metadata {
                         name: #operator_name,
-                        description: None,
+                        description: #description,
+                        category: #category,
                         inputs: vec![
                             #(
                                InputMetadata {
-                                    name: #input_name,
+                                    name: #input_name.into(),
+                                    description: #input_description,
                                     data_type: #input_data_type,
-                                    required: false,
-                                    multiple: false,
+                                    required: #input_required,
+                                    multiple: #input_multiple,
+                                    optional: #input_optional,
                                     default: #input_default_value,
+                                    hint: #input_hint,
+                                    variants: #input_variants,
                                 }
                             ),*
                         ],
                         outputs: vec![
                             #(
                                OutputMetadata {
-                                    name: #output_name,
+                                    name: #output_name.into(),
                                     data_type: #output_data_type,
                                 }
                             ),*
@@ -198,18 +250,27 @@ pub fn derive_evaluator_impl(
                     }
                 }
 
-                inventory::submit!(EvaluatorRegistryNode{
+                #krate::inventory::submit!(EvaluatorRegistryNode{
                     operator: #operator_name,
                     create_operator
                 });
 
-                inventory::submit!(OperatorMetadataRegistryNode{
+                #krate::inventory::submit!(OperatorMetadataRegistryNode{
                     operator: #operator_name,
                     create_metadata
                 });
+
+                #describe
             };
         };
     });
 
     Ok(tokens)
 }
+
+fn optional_str(value: Option<&str>) -> TokenStream {
+    match value {
+        Some(value) => quote! { Some(#value) },
+        None => quote! { None },
+    }
+}
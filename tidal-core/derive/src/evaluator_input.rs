@@ -4,7 +4,11 @@ use syn::parse::{Parse, Parser};
 use syn::spanned::Spanned;
 use syn::{Error, FnArg, Pat, PatIdent, PatType, Type, TypePath};
 
-use crate::evaluator_input_default_value::{DefaultValue, ScalarDefaultValue, VectorDefaultValue};
+use crate::evaluator_input_default_value::{
+    BoolDefaultValue, ComponentsDefaultValue, DefaultValue, IntDefaultValue, ScalarDefaultValue,
+    StringDefaultValue,
+};
+use crate::evaluator_input_hint::InputHint;
 use crate::{evaluator_impl, helper};
 
 pub enum Argument {
@@ -34,21 +38,53 @@ impl TryFrom<&FnArg> for Argument {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum DataType {
     Scalar,
+    Int,
+    Bool,
+    String,
+    Vector2,
     Vector,
+    Vector4,
+    Color,
     Mesh,
+    Texture,
     Command,
+    ScalarCurve,
+    VectorCurve,
+    Timeline,
+    Expression,
+    /// Type deriving `OperatorEnum`
+    Enum,
+}
+
+impl DataType {
+    /// Whether inputs of this type can hold a constant when unconnected.
+    fn has_constant(&self) -> bool {
+        !matches!(self, DataType::Mesh | DataType::Texture | DataType::Command)
+    }
 }
 
 impl ToTokens for DataType {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         match self {
             DataType::Scalar => tokens.extend(quote! { DataType::Scalar }),
+            DataType::Int => tokens.extend(quote! { DataType::Int }),
+            DataType::Bool => tokens.extend(quote! { DataType::Bool }),
+            DataType::String => tokens.extend(quote! { DataType::String }),
+            DataType::Vector2 => tokens.extend(quote! { DataType::Vector2 }),
             DataType::Vector => tokens.extend(quote! { DataType::Vector }),
+            DataType::Vector4 => tokens.extend(quote! { DataType::Vector4 }),
+            DataType::Color => tokens.extend(quote! { DataType::Color }),
             DataType::Mesh => tokens.extend(quote! { DataType::Mesh }),
+            DataType::Texture => tokens.extend(quote! { DataType::Texture }),
             DataType::Command => tokens.extend(quote! { DataType::Command }),
+            DataType::ScalarCurve => tokens.extend(quote! { DataType::ScalarCurve }),
+            DataType::VectorCurve => tokens.extend(quote! { DataType::VectorCurve }),
+            DataType::Timeline => tokens.extend(quote! { DataType::Timeline }),
+            DataType::Expression => tokens.extend(quote! { DataType::Expression }),
+            DataType::Enum => tokens.extend(quote! { DataType::Enum }),
         }
     }
 }
@@ -57,8 +93,19 @@ impl ToTokens for DataType {
 pub struct Input {
     pub ident: Ident,
     pub multiple: bool,
+    /// Evaluated by the operator on demand rather than before it runs
+    pub lazy: bool,
     pub data_type: DataType,
+    /// Type of the values, inside `Option`, `Multiple` or `Lazy`
+    pub ty: TypePath,
     pub default_value: DefaultValue,
+    /// Receives `None` rather than a constant when unconnected
+    pub optional: bool,
+    /// Has to be connected for the operator to run
+    pub required: bool,
+    /// Doc comment of the parameter
+    pub description: Option<String>,
+    pub hint: InputHint,
 }
 
 impl TryFrom<&PatType> for Input {
@@ -69,21 +116,27 @@ impl TryFrom<&PatType> for Input {
             return Err(Error::new(input.span(), "not supported"));
         };
 
-        let Type::Path(ty) = &*input.ty else {
+        let Type::Path(ty) = helper::ungroup(&input.ty) else {
             return Err(Error::new(input.span(), "not supported"));
         };
 
-        let (ty, multiple) = if helper::path_ends_with(&ty.path, "Multiple") {
+        let wrapper = ["Multiple", "LazyMultiple", "Lazy", "Option"]
+            .into_iter()
+            .find(|wrapper| helper::path_ends_with(&ty.path, wrapper));
+
+        let optional = wrapper == Some("Option");
+
+        let (ty, multiple, lazy) = if let Some(wrapper) = wrapper {
             let Some(ty) = helper::get_first_generic_argument(&ty.path) else {
                 return Err(Error::new(
                     input.span(),
-                    "not supported Multiple call without arguments",
+                    format!("not supported {wrapper} call without arguments"),
                 ));
             };
 
-            (ty, true)
+            (ty, wrapper.contains("Multiple"), wrapper.contains("Lazy"))
         } else {
-            (ty, false)
+            (ty, false, false)
         };
 
         let data_type = ty.try_into()?;
@@ -98,42 +151,143 @@ impl TryFrom<&PatType> for Input {
                 ));
             }
 
+            if optional {
+                return Err(Error::new(
+                    input.span(),
+                    "default value for optional input not supported, it receives None instead",
+                ));
+            }
+
             let meta = attr.meta.require_list()?;
             let tokens = meta.tokens.clone();
 
             match data_type {
                 DataType::Scalar => DefaultValue::Scalar(ScalarDefaultValue::parse.parse2(tokens)?),
-                DataType::Vector => DefaultValue::Vector(VectorDefaultValue::parse.parse2(tokens)?),
+                DataType::Int => DefaultValue::Int(IntDefaultValue::parse.parse2(tokens)?),
+                DataType::Bool => DefaultValue::Bool(BoolDefaultValue::parse.parse2(tokens)?),
+                DataType::String => DefaultValue::String(StringDefaultValue::parse.parse2(tokens)?),
+                DataType::Vector2 => {
+                    DefaultValue::Vector2(parse_components(tokens, &[2], input.span())?)
+                }
+                DataType::Vector => {
+                    DefaultValue::Vector(parse_components(tokens, &[3], input.span())?)
+                }
+                DataType::Vector4 => {
+                    DefaultValue::Vector4(parse_components(tokens, &[4], input.span())?)
+                }
+                DataType::Color => {
+                    DefaultValue::Color(parse_components(tokens, &[3, 4], input.span())?)
+                }
                 DataType::Mesh => {
                     return Err(Error::new(
                         input.span(),
                         "default value for mesh not supported",
                     ));
                 }
+                DataType::Texture => {
+                    return Err(Error::new(
+                        input.span(),
+                        "default value for texture not supported",
+                    ));
+                }
                 DataType::Command => {
                     return Err(Error::new(
                         input.span(),
                         "default value for command not supported",
                     ));
                 }
+                DataType::ScalarCurve | DataType::VectorCurve => {
+                    return Err(Error::new(
+                        input.span(),
+                        "default value for curve not supported",
+                    ));
+                }
+                DataType::Timeline => {
+                    return Err(Error::new(
+                        input.span(),
+                        "default value for timeline not supported",
+                    ));
+                }
+                DataType::Expression => {
+                    return Err(Error::new(
+                        input.span(),
+                        "default value for expression not supported",
+                    ));
+                }
+                DataType::Enum => DefaultValue::Enum(ty.clone(), Some(syn::parse2(tokens)?)),
             }
+        } else if data_type == DataType::Enum && !multiple && !optional {
+            // Enums always hold a variant, the first one unless stated otherwise.
+            DefaultValue::Enum(ty.clone(), None)
         } else {
             DefaultValue::None
         };
 
         match data_type {
             DataType::Scalar => {}
+            DataType::Int => {}
+            DataType::Bool => {}
+            DataType::String => {}
+            DataType::Vector2 => {}
             DataType::Vector => {}
+            DataType::Vector4 => {}
+            DataType::Color => {}
             DataType::Mesh => {}
+            DataType::Texture => {}
             DataType::Command => {}
+            DataType::ScalarCurve => {}
+            DataType::VectorCurve => {}
+            DataType::Timeline => {}
+            DataType::Expression => {}
+            DataType::Enum => {}
+        }
+
+        let hint = match input.attrs.iter().find(|p| p.path().is_ident("input")) {
+            Some(attr) => attr.parse_args()?,
+            None => InputHint::default(),
+        };
+
+        // Inputs without a constant fail to evaluate when unconnected, unless the operator
+        // checks the connection itself or receives an empty list.
+        let required = helper::has_attribute(&input.attrs, "required")
+            || (!lazy && !multiple && !optional && !data_type.has_constant());
+
+        if required && optional {
+            return Err(Error::new(input.span(), "optional input can't be required"));
         }
 
         let ident = ident.clone();
         Ok(Self {
             ident,
             multiple,
+            lazy,
             data_type,
+            ty: ty.clone(),
             default_value,
+            optional,
+            required,
+            description: helper::doc_comment(&input.attrs),
+            hint,
         })
     }
 }
+
+/// Parses the components of a default value, checking their count is one of `counts`.
+fn parse_components(
+    tokens: TokenStream,
+    counts: &[usize],
+    span: proc_macro2::Span,
+) -> Result<ComponentsDefaultValue, Error> {
+    let components = ComponentsDefaultValue::parse.parse2(tokens)?;
+
+    if !counts.contains(&components.0.len()) {
+        let counts: Vec<String> = counts.iter().map(ToString::to_string).collect();
+
+        return Err(Error::new(
+            span,
+            format!("expected {} components", counts.join(" or ")),
+        ));
+    }
+
+    Ok(components)
+}
@@ -1,8 +1,9 @@
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
 use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
 use syn::token::Comma;
-use syn::LitFloat;
+use syn::{Ident, LitBool, LitFloat, LitInt, LitStr, TypePath};
 
 #[derive(Debug, Clone)]
 pub struct ScalarDefaultValue(LitFloat);
@@ -22,36 +23,57 @@ impl ToTokens for ScalarDefaultValue {
 }
 
 #[derive(Debug, Clone)]
-pub struct VectorDefaultValue(LitFloat, LitFloat, LitFloat);
+pub struct IntDefaultValue(LitInt);
 
-impl Parse for VectorDefaultValue {
+impl Parse for IntDefaultValue {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let x: LitFloat = input.parse()?;
-        let _: Comma = input.parse()?;
-        let y: LitFloat = input.parse()?;
-        let _: Comma = input.parse()?;
-        let z: LitFloat = input.parse()?;
+        Ok(Self(input.parse()?))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BoolDefaultValue(LitBool);
 
-        Ok(Self(x, y, z))
+impl Parse for BoolDefaultValue {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self(input.parse()?))
     }
 }
 
-impl ToTokens for VectorDefaultValue {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
-        let x = &self.0;
-        let y = &self.1;
-        let z = &self.2;
+#[derive(Debug, Clone)]
+pub struct StringDefaultValue(LitStr);
 
-        tokens.extend(quote! {
-            cgmath::Vector3::new(#x, #y, #z)
-        })
+impl Parse for StringDefaultValue {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self(input.parse()?))
+    }
+}
+
+/// Comma separated components of a vector or color.
+#[derive(Debug, Clone)]
+pub struct ComponentsDefaultValue(pub Vec<LitFloat>);
+
+impl Parse for ComponentsDefaultValue {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let components = Punctuated::<LitFloat, Comma>::parse_terminated(input)?;
+
+        Ok(Self(components.into_iter().collect()))
     }
 }
 
 #[derive(Debug, Clone, Default)]
 pub enum DefaultValue {
     Scalar(ScalarDefaultValue),
-    Vector(VectorDefaultValue),
+    Int(IntDefaultValue),
+    Bool(BoolDefaultValue),
+    String(StringDefaultValue),
+    Vector2(ComponentsDefaultValue),
+    Vector(ComponentsDefaultValue),
+    Vector4(ComponentsDefaultValue),
+    /// Three components for an opaque color, or four with the alpha
+    Color(ComponentsDefaultValue),
+    /// Variant of an enum, or its first variant
+    Enum(TypePath, Option<Ident>),
 
     #[default]
     None,
@@ -61,7 +83,37 @@ impl ToTokens for DefaultValue {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         match self {
             DefaultValue::Scalar(s) => tokens.extend(quote! { Some(Constant::Scalar(#s)) }),
-            DefaultValue::Vector(v) => tokens.extend(quote! { Some(Constant::Vector(#v)) }),
+            DefaultValue::Int(IntDefaultValue(v)) => {
+                tokens.extend(quote! { Some(Constant::Int(#v)) })
+            }
+            DefaultValue::Bool(BoolDefaultValue(v)) => {
+                tokens.extend(quote! { Some(Constant::Bool(#v)) })
+            }
+            DefaultValue::String(StringDefaultValue(v)) => {
+                tokens.extend(quote! { Some(Constant::String(String::from(#v))) })
+            }
+            DefaultValue::Vector2(ComponentsDefaultValue(c)) => tokens.extend(quote! {
+                Some(Constant::Vector2(cgmath::Vector2::new(#(#c),*)))
+            }),
+            DefaultValue::Vector(ComponentsDefaultValue(c)) => tokens.extend(quote! {
+                Some(Constant::Vector(cgmath::Vector3::new(#(#c),*)))
+            }),
+            DefaultValue::Vector4(ComponentsDefaultValue(c)) => tokens.extend(quote! {
+                Some(Constant::Vector4(cgmath::Vector4::new(#(#c),*)))
+            }),
+            DefaultValue::Color(ComponentsDefaultValue(c)) => {
+                let alpha = (c.len() == 3).then(|| quote! { , 1.0 });
+
+                tokens.extend(quote! {
+                    Some(Constant::Color(crate::color::Color::new(#(#c),* #alpha)))
+                })
+            }
+            DefaultValue::Enum(ty, Some(variant)) => tokens.extend(quote! {
+                Some(Constant::Enum(OperatorEnum::name(&#ty::#variant).into()))
+            }),
+            DefaultValue::Enum(ty, None) => tokens.extend(quote! {
+                Some(Constant::Enum(<#ty as OperatorEnum>::VARIANTS[0].into()))
+            }),
             DefaultValue::None => tokens.extend(quote! { None }),
         }
     }
@@ -0,0 +1,88 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::token::Comma;
+use syn::{Error, Expr, ExprLit, ExprUnary, Lit, MetaNameValue, UnOp};
+
+/// Hints of `#[input(min = 0.0, max = 1.0, step = 0.01)]` for the widget editing an input.
+#[derive(Debug, Default)]
+pub struct InputHint {
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+    pub step: Option<f32>,
+}
+
+impl Parse for InputHint {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let kv = Punctuated::<MetaNameValue, Comma>::parse_terminated(input)?;
+
+        let mut hint = InputHint::default();
+
+        for meta in kv.into_iter() {
+            let value = Some(parse_number(&meta.value)?);
+
+            if meta.path.is_ident("min") {
+                hint.min = value;
+            } else if meta.path.is_ident("max") {
+                hint.max = value;
+            } else if meta.path.is_ident("step") {
+                hint.step = value;
+            } else {
+                return Err(Error::new(meta.path.span(), "expected min, max or step"));
+            }
+        }
+
+        if let (Some(min), Some(max)) = (hint.min, hint.max) {
+            if min > max {
+                return Err(Error::new(input.span(), "min is greater than max"));
+            }
+        }
+
+        Ok(hint)
+    }
+}
+
+/// Parses a literal number, optionally negated.
+fn parse_number(expr: &Expr) -> syn::Result<f32> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Float(lit),
+            ..
+        }) => lit.base10_parse(),
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(lit), ..
+        }) => lit.base10_parse::<i64>().map(|v| v as f32),
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => parse_number(expr).map(|v| -v),
+        _ => Err(Error::new(expr.span(), "expected a number")),
+    }
+}
+
+impl ToTokens for InputHint {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let value = |v: Option<f32>| match v {
+            Some(v) => {
+                let v = Literal::f32_suffixed(v);
+                quote! { Some(#v) }
+            }
+            None => quote! { None },
+        };
+
+        let min = value(self.min);
+        let max = value(self.max);
+        let step = value(self.step);
+
+        tokens.extend(quote! {
+            InputHint {
+                min: #min,
+                max: #max,
+                step: #step,
+            }
+        });
+    }
+}
@@ -89,9 +89,33 @@ impl TryFrom<(&[Attribute], &ReturnType)> for Outputs {
             }
         }
 
-        let (data_types, return_style) = match &return_type {
-            ReturnType::Default => (vec![], ReturnStyle::Tuple),
-            ReturnType::Type(_, ty) => match ty.as_ref() {
+        let return_type = match return_type {
+            ReturnType::Default => None,
+            ReturnType::Type(_, ty) => Some(ty.as_ref()),
+        };
+
+        // Fallible evaluators wrap their outputs in a `Result`.
+        let return_type = if helper::has_attribute(attributes, "fallible") {
+            match return_type {
+                Some(Type::Path(ty)) if helper::path_ends_with(&ty.path, "Result") => {
+                    helper::get_first_generic_type(&ty.path)
+                }
+                _ => None,
+            }
+            .ok_or_else(|| {
+                Error::new(
+                    return_type.map_or_else(Span::call_site, |ty| ty.span()),
+                    "fallible evaluator must return a Result",
+                )
+            })
+            .map(Some)?
+        } else {
+            return_type
+        };
+
+        let (data_types, return_style) = match return_type {
+            None => (vec![], ReturnStyle::Tuple),
+            Some(ty) => match ty {
                 Type::Paren(ty) => match &*ty.elem {
                     Type::Path(ty) => (vec![DataType::try_from(ty)?], ReturnStyle::Tuple),
                     _ => unsupported_return_type!(ty.span()),
@@ -1,4 +1,7 @@
-use syn::{Attribute, Error, GenericArgument, Path, PathArguments, Type, TypePath};
+use syn::{
+    Attribute, Error, Expr, ExprLit, GenericArgument, Lit, Meta, MetaNameValue, Path,
+    PathArguments, Type, TypePath,
+};
 
 #[derive(Default)]
 pub struct ErrorAccumulator {
@@ -44,6 +47,26 @@ pub fn path_ends_with(path: &Path, s: &str) -> bool {
     }
 }
 
+/// Looks through the invisible groups `macro_rules` wraps around `$ty:ty` fragments.
+pub fn ungroup(ty: &Type) -> &Type {
+    match ty {
+        Type::Group(group) => ungroup(&group.elem),
+        ty => ty,
+    }
+}
+
+pub fn get_first_generic_type(path: &Path) -> Option<&Type> {
+    let path = path.segments.last()?;
+
+    if let PathArguments::AngleBracketed(a) = &path.arguments {
+        if let Some(GenericArgument::Type(ty)) = a.args.first() {
+            return Some(ty);
+        };
+    };
+
+    None
+}
+
 pub fn get_first_generic_argument(path: &Path) -> Option<&TypePath> {
     let path = path.segments.last()?;
 
@@ -64,8 +87,40 @@ const ATTRIBUTES: &[&str] = &[
     "context",
     "fallible",
     "default",
+    "describe",
+    "category",
+    "required",
 ];
 
+/// Text of the `///` doc comments among `attrs`, without the space following the slashes.
+pub fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(MetaNameValue {
+                value:
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(lit), ..
+                    }),
+                ..
+            }) => Some(lit.value()),
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(str::to_owned).unwrap_or(line))
+        .collect();
+
+    let doc = lines.join("\n").trim().to_owned();
+
+    (!doc.is_empty()).then_some(doc)
+}
+
+/// Attributes kept on the parameters of the function. Parameters can't have doc comments, so
+/// the ones describing inputs are removed along with the evaluator attributes.
+pub fn retain_parameter_attributes(attr: &Attribute) -> bool {
+    retain_attributes(attr) && !attr.path().is_ident("doc")
+}
+
 pub fn has_attribute(attrs: &[Attribute], name: &str) -> bool {
     attrs.iter().any(|attr| attr.path().is_ident(name))
 }
@@ -13,8 +13,10 @@ mod evaluator_data_type;
 mod evaluator_impl;
 mod evaluator_input;
 mod evaluator_input_default_value;
+mod evaluator_input_hint;
 mod evaluator_output;
 mod helper;
+mod operator_enum_impl;
 
 #[proc_macro_attribute]
 pub fn evaluator(
@@ -28,3 +30,12 @@ pub fn evaluator(
         .unwrap_or_else(Error::into_compile_error)
         .into()
 }
+
+#[proc_macro_derive(OperatorEnum)]
+pub fn operator_enum(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    operator_enum_impl::derive_operator_enum_impl(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,85 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Error, Fields};
+
+use crate::helper::ErrorAccumulator;
+
+/// Implements `OperatorEnum` for an enum without fields, and the conversions to and from the
+/// interpreter's values, so operators can take it as input or output.
+pub fn derive_operator_enum_impl(input: DeriveInput) -> Result<TokenStream, Error> {
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new(
+            input.span(),
+            "OperatorEnum can only be derived for enums",
+        ));
+    };
+
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "OperatorEnum can't be derived for generic enums",
+        ));
+    }
+
+    if data.variants.is_empty() {
+        return Err(Error::new(
+            input.span(),
+            "OperatorEnum needs at least one variant",
+        ));
+    }
+
+    let mut errors = ErrorAccumulator::default();
+    errors.extend(
+        data.variants
+            .iter()
+            .filter(|variant| !matches!(variant.fields, Fields::Unit))
+            .map(|variant| {
+                Error::new(
+                    variant.fields.span(),
+                    "variants of an OperatorEnum can't have fields",
+                )
+            }),
+    );
+    errors.accumulate()?;
+
+    let ident = &input.ident;
+    let variant_ident: Vec<_> = data.variants.iter().map(|v| &v.ident).collect();
+    let variant_name: Vec<String> = variant_ident.iter().map(|v| v.to_string()).collect();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl ::tidal_core::graph::OperatorEnum for #ident {
+            const VARIANTS: &'static [&'static str] = &[#(#variant_name),*];
+
+            fn name(&self) -> &'static str {
+                match self {
+                    #(#ident::#variant_ident => #variant_name),*
+                }
+            }
+
+            fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    #(#variant_name => Some(#ident::#variant_ident),)*
+                    _ => None,
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl TryFrom<::tidal_core::interpreter::Value> for #ident {
+            type Error = ::tidal_core::interpreter::EvaluateError;
+
+            fn try_from(value: ::tidal_core::interpreter::Value) -> Result<Self, Self::Error> {
+                ::tidal_core::interpreter::Value::to_enum(&value)
+            }
+        }
+
+        #[automatically_derived]
+        impl From<#ident> for ::tidal_core::interpreter::Value {
+            fn from(value: #ident) -> Self {
+                ::tidal_core::interpreter::Value::from_enum(value)
+            }
+        }
+    })
+}
@@ -1,121 +1,325 @@
-//use core::fmt::Debug;
-//
-// #[derive(Copy, Clone, Debug)]
-// pub struct KeyFrame<V> {
-//     pub x: Frame,
-//     pub y: V,
-//     pub i: Interpolation,
-// }
-//
-// #[derive(Clone, Debug)]
-// pub struct Animation<V> (Vec<KeyFrame<V>>);
-//
-// #[repr(transparent)]
-// #[derive(Copy, Clone)]
-// #[derive(Debug, Into, From, Ord, PartialOrd, Eq, PartialEq, Sub, Add)]
-// pub struct Frame(pub(crate) u32);
-//
-// impl Frame {
-//     const MIN: Frame = Frame(u32::MIN);
-//     const MAX: Frame = Frame(u32::MAX);
-//
-//     #[inline(always)]
-//     pub fn from_milliseconds(ms: f32) -> Frame {
-//         Frame(((ms * 120.0) / 1000.0) as u32)
-//     }
-// }
-//
-// #[derive(Copy, Clone, Debug)]
-// pub enum Interpolation {
-//     Linear,
-//     /// Special interpolation that only emits the start value
-//     Start,
-//     /// Special interpolation that only emits the end value
-//     End,
-// }
-//
-// /// How an interpolation should be performed between two points.
-// pub trait Interpolate {
-//     fn interpolate(interpolation: Interpolation, a: &Self, b: &Self, control: f32) -> Self;
-// }
-//
-// impl Interpolate for f32 {
-//     #[inline(always)]
-//     fn interpolate(interpolation: Interpolation, a: &Self, b: &Self, control: f32) -> Self {
-//         match interpolation {
-//             Interpolation::Linear => a + (b - a) * control,
-//             Interpolation::Start => *a,
-//             Interpolation::End => *b,
-//         }
-//     }
-// }
-//
-// impl Interpolate for Vector3 {
-//     fn interpolate(interpolation: Interpolation, a: &Self, b: &Self, control: f32) -> Self {
-//         let x = f32::interpolate(interpolation, &a.x, &b.x, control);
-//         let y = f32::interpolate(interpolation, &a.y, &b.y, control);
-//         let z = f32::interpolate(interpolation, &a.z, &b.z, control);
-//
-//         Vec3::new(x, y, z)
-//     }
-// }
-//
-// impl Interpolate for Vector2 {
-//     fn interpolate(interpolation: Interpolation, a: &Self, b: &Self, control: f32) -> Self {
-//         let x = f32::interpolate(interpolation, &a.x, &b.x, control);
-//         let y = f32::interpolate(interpolation, &a.y, &b.y, control);
-//
-//         Vec2::new(x, y)
-//     }
-// }
-//
-// pub trait Animate {
-//     type Output;
-//
-//     fn animate(&self, time: Frame) -> Self::Output;
-// }
-//
-// impl<V> Animation<V>
-//     where V: Interpolate + Copy + Default {
-//     pub fn from_constant(v: V) -> Self {
-//         Animation(vec![
-//             KeyFrame { x: Frame::MIN, y: v, i: Interpolation::Start },
-//             KeyFrame { x: Frame::MAX, y: v, i: Interpolation::Start },
-//         ])
-//     }
-//
-//     pub fn from_key_frames(mut key_frames: Vec<KeyFrame<V>>) -> Self {
-//         if key_frames.len() == 0 {
-//             Self::from_constant(Default::default())
-//         } else {
-//             // Add two dummy key frames repeating the minimum and maximum values. Will be used
-//             // when the animation starts after or ends before the demo's duration.
-//             let start = KeyFrame { x: Frame::MIN, y: key_frames.first().unwrap().y, i: Interpolation::End };
-//             let end = KeyFrame { x: Frame::MAX, y: key_frames.last().unwrap().y, i: Interpolation::End };
-//
-//             key_frames.insert(0, start);
-//             key_frames.push(end);
-//
-//             Animation(key_frames)
-//         }
-//     }
-// }
-//
-// impl<V> Animate for Animation<V>
-//     where V: Interpolate {
-//     type Output = V;
-//
-//     fn animate(&self, time: Frame) -> Self::Output {
-//         let (current, next) = self.0.windows(2)
-//             .map(|w| (&w[0], &w[1]))
-//             .find(|(current, next)| {
-//                 next.x > time
-//             })
-//             .unwrap(); // Guaranteed to be exist because of the constructor
-//
-//         let control = ((time - current.x).0 as f32) / ((next.x - current.x).0 as f32);
-//         let control = control.clamp(0.0, 1.0);
-//
-//         V::interpolate(Interpolation::Linear, &current.y, &next.y, control)
-//     }
-// }
+use core::fmt::Debug;
+use std::ops::{Add, Mul, Sub};
+
+use cgmath::Vector3;
+use serde::{Deserialize, Serialize};
+
+use crate::graph::OperatorEnum;
+
+/// How the value changes between a keyframe and the next one.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Interpolation {
+    /// Holds the keyframe's value until the next keyframe
+    Step,
+    #[default]
+    Linear,
+    /// Cubic Bézier curve. Tangents are the offsets of the control points from their keyframe's
+    /// value, placed a third of the way towards the neighbouring keyframe.
+    Bezier,
+    /// Cubic Hermite spline. Tangents are slopes, in value units per second.
+    Hermite,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct Keyframe<V> {
+    /// Time of the keyframe, in seconds
+    pub time: f32,
+    pub value: V,
+    /// Tangent arriving at this keyframe
+    pub in_tangent: V,
+    /// Tangent leaving this keyframe
+    pub out_tangent: V,
+    /// Interpolation towards the next keyframe
+    pub interpolation: Interpolation,
+}
+
+impl<V> Keyframe<V>
+where
+    V: Interpolate,
+{
+    /// Creates a keyframe with flat tangents.
+    pub fn new(time: f32, value: V, interpolation: Interpolation) -> Self {
+        Self {
+            time,
+            value,
+            in_tangent: V::zero(),
+            out_tangent: V::zero(),
+            interpolation,
+        }
+    }
+}
+
+/// Values that can be animated by a [`Curve`].
+pub trait Interpolate:
+    Copy + Debug + Add<Output = Self> + Sub<Output = Self> + Mul<f32, Output = Self>
+{
+    fn zero() -> Self;
+}
+
+impl Interpolate for f32 {
+    #[inline(always)]
+    fn zero() -> Self {
+        0.0
+    }
+}
+
+impl Interpolate for Vector3<f32> {
+    #[inline(always)]
+    fn zero() -> Self {
+        Vector3::new(0.0, 0.0, 0.0)
+    }
+}
+
+/// A value animated over time by keyframes, sorted by time.
+///
+/// Before the first keyframe and after the last one the curve holds their values.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Curve<V> {
+    keyframes: Vec<Keyframe<V>>,
+}
+
+pub type ScalarCurve = Curve<f32>;
+pub type VectorCurve = Curve<Vector3<f32>>;
+
+impl<V> Default for Curve<V> {
+    fn default() -> Self {
+        Self { keyframes: vec![] }
+    }
+}
+
+impl<V> Curve<V>
+where
+    V: Interpolate,
+{
+    pub fn new(mut keyframes: Vec<Keyframe<V>>) -> Self {
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        Self { keyframes }
+    }
+
+    /// Creates a curve going linearly from `from` to `to` in `duration` seconds.
+    pub fn ramp(from: V, to: V, duration: f32) -> Self {
+        Self::new(vec![
+            Keyframe::new(0.0, from, Interpolation::Linear),
+            Keyframe::new(duration, to, Interpolation::Linear),
+        ])
+    }
+
+    #[inline]
+    pub fn keyframes(&self) -> &[Keyframe<V>] {
+        &self.keyframes
+    }
+
+    /// Inserts a keyframe, keeping the keyframes sorted.
+    pub fn insert(&mut self, keyframe: Keyframe<V>) -> usize {
+        let index = self
+            .keyframes
+            .partition_point(|other| other.time <= keyframe.time);
+
+        self.keyframes.insert(index, keyframe);
+        index
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Keyframe<V>> {
+        (index < self.keyframes.len()).then(|| self.keyframes.remove(index))
+    }
+
+    /// Replaces a keyframe, moving it if its time changed.
+    pub fn update(&mut self, index: usize, keyframe: Keyframe<V>) -> Option<usize> {
+        self.remove(index)?;
+
+        Some(self.insert(keyframe))
+    }
+
+    /// Returns the value of the curve at `time`, in seconds. A NaN time samples the first
+    /// keyframe.
+    pub fn sample(&self, time: f32) -> V {
+        let (first, last) = match (self.keyframes.first(), self.keyframes.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return V::zero(),
+        };
+
+        if time.is_nan() || time <= first.time {
+            return first.value;
+        }
+
+        if time >= last.time {
+            return last.value;
+        }
+
+        // The first keyframe after `time`. `time` is a number strictly between the first and
+        // last keyframes after the checks above, so it has a previous keyframe.
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+        let a = &self.keyframes[next - 1];
+        let b = &self.keyframes[next];
+
+        let duration = b.time - a.time;
+        if duration <= f32::EPSILON {
+            return b.value;
+        }
+
+        let t = (time - a.time) / duration;
+
+        match a.interpolation {
+            Interpolation::Step => a.value,
+            Interpolation::Linear => a.value + (b.value - a.value) * t,
+            Interpolation::Bezier => {
+                let p1 = a.value + a.out_tangent;
+                let p2 = b.value + b.in_tangent;
+                let u = 1.0 - t;
+
+                a.value * (u * u * u)
+                    + p1 * (3.0 * u * u * t)
+                    + p2 * (3.0 * u * t * t)
+                    + b.value * (t * t * t)
+            }
+            Interpolation::Hermite => {
+                let t2 = t * t;
+                let t3 = t2 * t;
+
+                let h00 = 2.0 * t3 - 3.0 * t2 + 1.0;
+                let h10 = t3 - 2.0 * t2 + t;
+                let h01 = -2.0 * t3 + 3.0 * t2;
+                let h11 = t3 - t2;
+
+                a.value * h00
+                    + a.out_tangent * (h10 * duration)
+                    + b.value * h01
+                    + b.in_tangent * (h11 * duration)
+            }
+        }
+    }
+}
+
+/// Shape of an easing curve.
+#[derive(Serialize, Deserialize, OperatorEnum, Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Easing {
+    Linear,
+    Quad,
+    #[default]
+    Cubic,
+    Expo,
+    /// Overshoots backwards before moving
+    Back,
+    /// Oscillates like a plucked rubber band
+    Elastic,
+    /// Bounces like a dropped ball
+    Bounce,
+}
+
+/// Which end of the motion an easing curve applies to.
+#[derive(Serialize, Deserialize, OperatorEnum, Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum EasingMode {
+    /// Starts slowly
+    In,
+    /// Ends slowly
+    Out,
+    /// Starts and ends slowly
+    #[default]
+    InOut,
+}
+
+impl Easing {
+    /// Eases `t`, clamped to `0..1`. Returns 0 at 0 and 1 at 1, with back and elastic easing
+    /// leaving that range in between.
+    pub fn ease(&self, mode: EasingMode, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match mode {
+            EasingMode::In => self.ease_in(t),
+            EasingMode::Out => 1.0 - self.ease_in(1.0 - t),
+            EasingMode::InOut if t < 0.5 => self.ease_in(t * 2.0) * 0.5,
+            EasingMode::InOut => 1.0 - self.ease_in((1.0 - t) * 2.0) * 0.5,
+        }
+    }
+
+    fn ease_in(&self, t: f32) -> f32 {
+        use std::f32::consts::TAU;
+
+        const BACK_OVERSHOOT: f32 = 1.70158;
+
+        match self {
+            Easing::Linear => t,
+            Easing::Quad => t * t,
+            Easing::Cubic => t * t * t,
+            Easing::Expo if t <= 0.0 => 0.0,
+            Easing::Expo => (10.0 * (t - 1.0)).exp2(),
+            Easing::Back => t * t * ((BACK_OVERSHOOT + 1.0) * t - BACK_OVERSHOOT),
+            Easing::Elastic if t <= 0.0 || t >= 1.0 => t,
+            Easing::Elastic => -(10.0 * (t - 1.0)).exp2() * ((t - 1.075) * TAU / 0.3).sin(),
+            Easing::Bounce => 1.0 - bounce_out(1.0 - t),
+        }
+    }
+}
+
+/// Ease out curve bouncing three times against its end value before settling on it.
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+/// Longest integration step of a spring, so stiff springs stay stable at low frame rates.
+const SPRING_STEP: f32 = 1.0 / 240.0;
+
+/// Damped harmonic oscillator pulled towards a target.
+#[derive(Debug, Copy, Clone)]
+pub struct Spring<V> {
+    pub value: V,
+    pub velocity: V,
+}
+
+impl<V> Spring<V>
+where
+    V: Interpolate,
+{
+    /// Creates a spring at rest at `value`.
+    pub fn new(value: V) -> Self {
+        Self {
+            value,
+            velocity: V::zero(),
+        }
+    }
+
+    /// Advances the spring by `delta_time` seconds. `frequency` is the oscillation frequency
+    /// without damping, in hertz, and `damping` the damping ratio: below 1 the spring
+    /// overshoots the target, at 1 and above it doesn't.
+    pub fn step(&mut self, target: V, frequency: f32, damping: f32, delta_time: f32) {
+        let omega = std::f32::consts::TAU * frequency.max(0.0);
+        let damping = damping.max(0.0);
+
+        let steps = (delta_time / SPRING_STEP).ceil().max(1.0);
+        let dt = delta_time.max(0.0) / steps;
+
+        // Semi-implicit Euler, stable as long as `omega * dt` stays small.
+        for _ in 0..steps as u32 {
+            let acceleration =
+                (target - self.value) * (omega * omega) - self.velocity * (2.0 * damping * omega);
+
+            self.velocity = self.velocity + acceleration * dt;
+            self.value = self.value + self.velocity * dt;
+        }
+    }
+}
+
+/// Moves `value` towards `target` exponentially, halving the distance every `half_life`
+/// seconds regardless of the frame rate.
+pub fn damp<V: Interpolate>(value: V, target: V, half_life: f32, delta_time: f32) -> V {
+    if half_life <= 0.0 {
+        return target;
+    }
+
+    target + (value - target) * (-delta_time / half_life).exp2()
+}
@@ -0,0 +1,144 @@
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+use std::sync::Arc;
+
+use lewton::inside_ogg::OggStreamReader;
+
+use crate::audio::AudioError;
+
+/// Decoded audio, stored as interleaved samples in the `-1..1` range.
+#[derive(Clone)]
+pub struct AudioBuffer {
+    sample_rate: u32,
+    channels: u16,
+    samples: Arc<[f32]>,
+}
+
+impl std::fmt::Debug for AudioBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AudioBuffer")
+            .field("sample_rate", &self.sample_rate)
+            .field("channels", &self.channels)
+            .field("frames", &self.frames())
+            .finish()
+    }
+}
+
+impl AudioBuffer {
+    pub fn new(sample_rate: u32, channels: u16, samples: Vec<f32>) -> Self {
+        Self {
+            sample_rate,
+            channels: channels.max(1),
+            samples: samples.into(),
+        }
+    }
+
+    /// Decodes a WAV or OGG Vorbis file, picking the decoder from its extension.
+    pub fn load(path: &Path) -> Result<Self, AudioError> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        let reader = || -> Result<_, AudioError> { Ok(BufReader::new(File::open(path)?)) };
+
+        match extension.as_str() {
+            "wav" | "wave" => Self::decode_wav(reader()?),
+            "ogg" | "oga" => Self::decode_ogg(reader()?),
+            _ => Err(AudioError::UnsupportedFormat(extension)),
+        }
+    }
+
+    pub fn decode_wav(reader: impl Read) -> Result<Self, AudioError> {
+        let mut reader = hound::WavReader::new(reader)?;
+        let spec = reader.spec();
+
+        let samples = match spec.sample_format {
+            hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>()?,
+            hound::SampleFormat::Int => {
+                let scale = 1.0 / (1i64 << (spec.bits_per_sample - 1)) as f32;
+
+                reader
+                    .samples::<i32>()
+                    .map(|s| s.map(|s| s as f32 * scale))
+                    .collect::<Result<_, _>>()?
+            }
+        };
+
+        Ok(Self::new(spec.sample_rate, spec.channels, samples))
+    }
+
+    pub fn decode_ogg(reader: impl Read + Seek) -> Result<Self, AudioError> {
+        let mut reader = OggStreamReader::new(reader)?;
+        let mut samples = vec![];
+
+        while let Some(packet) = reader.read_dec_packet_itl()? {
+            samples.extend(packet.into_iter().map(|s| s as f32 / 32768.0));
+        }
+
+        Ok(Self::new(
+            reader.ident_hdr.audio_sample_rate,
+            reader.ident_hdr.audio_channels as u16,
+            samples,
+        ))
+    }
+
+    #[inline]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels as usize
+    }
+
+    /// Length of the audio, in seconds.
+    #[inline]
+    pub fn duration(&self) -> f32 {
+        self.frames() as f32 / self.sample_rate as f32
+    }
+
+    /// Returns the average of every channel at `frame`, silent outside of the buffer.
+    pub fn mixed_sample(&self, frame: usize) -> f32 {
+        let channels = self.channels as usize;
+
+        self.samples
+            .get(frame * channels..(frame + 1) * channels)
+            .map(|samples| samples.iter().sum::<f32>() / channels as f32)
+            .unwrap_or(0.0)
+    }
+
+    /// Returns the sample of `channel` at a fractional `frame`, linearly interpolated.
+    ///
+    /// Frames outside of the buffer are silent. Channels past the last one repeat it, so mono
+    /// audio plays on every output channel.
+    pub fn sample(&self, frame: f64, channel: u16) -> f32 {
+        if frame < 0.0 {
+            return 0.0;
+        }
+
+        let channel = channel.min(self.channels - 1) as usize;
+        let index = frame as usize;
+        let t = (frame - index as f64) as f32;
+
+        let at = |index: usize| {
+            self.samples
+                .get(index * self.channels as usize + channel)
+                .copied()
+                .unwrap_or(0.0)
+        };
+
+        let a = at(index);
+        let b = at(index + 1);
+
+        a + (b - a) * t
+    }
+}
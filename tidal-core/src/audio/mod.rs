@@ -0,0 +1,25 @@
+pub use buffer::*;
+pub use output::*;
+pub use player::*;
+pub use spectrum::*;
+pub use tempo::*;
+
+pub mod buffer;
+pub mod output;
+pub mod player;
+pub mod spectrum;
+pub mod tempo;
+
+#[derive(thiserror::Error, Debug)]
+pub enum AudioError {
+    #[error("io error")]
+    Io(#[from] std::io::Error),
+    #[error("wav decoding error")]
+    Wav(#[from] hound::Error),
+    #[error("ogg decoding error")]
+    Ogg(#[from] lewton::VorbisError),
+    #[error("unsupported audio format {0:?}")]
+    UnsupportedFormat(String),
+    #[error("audio output error: {0}")]
+    Output(String),
+}
@@ -0,0 +1,176 @@
+use crate::audio::{AudioError, PlaybackHandle};
+
+/// Backend sending the samples of a playback to an audio device.
+pub trait AudioOutput {
+    /// Starts pulling samples from `playback`.
+    fn start(&mut self, playback: PlaybackHandle) -> Result<(), AudioError>;
+
+    fn sample_rate(&self) -> u32;
+
+    fn channels(&self) -> u16;
+}
+
+/// Output without a device. Samples are only pulled when [`NullOutput::render`] is called, so
+/// decoding and timing can be driven deterministically.
+#[derive(Debug)]
+pub struct NullOutput {
+    sample_rate: u32,
+    channels: u16,
+    playback: Option<PlaybackHandle>,
+    samples: Vec<f32>,
+}
+
+impl NullOutput {
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        Self {
+            sample_rate,
+            channels: channels.max(1),
+            playback: None,
+            samples: vec![],
+        }
+    }
+
+    /// Pulls `frames` frames from the playback like a device callback would, returning the
+    /// interleaved samples.
+    pub fn render(&mut self, frames: usize) -> &[f32] {
+        self.samples.clear();
+        self.samples.resize(frames * self.channels as usize, 0.0);
+
+        if let Some(playback) = &self.playback {
+            playback.fill(&mut self.samples, self.channels, self.sample_rate);
+        }
+
+        &self.samples
+    }
+
+    /// Pulls as many frames as the output plays in `seconds`.
+    pub fn advance(&mut self, seconds: f32) -> &[f32] {
+        let frames = (seconds.max(0.0) * self.sample_rate as f32).round() as usize;
+
+        self.render(frames)
+    }
+}
+
+impl Default for NullOutput {
+    fn default() -> Self {
+        Self::new(48000, 2)
+    }
+}
+
+impl AudioOutput for NullOutput {
+    fn start(&mut self, playback: PlaybackHandle) -> Result<(), AudioError> {
+        self.playback = Some(playback);
+        Ok(())
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+}
+
+#[cfg(feature = "audio-device")]
+pub use device::*;
+
+#[cfg(feature = "audio-device")]
+mod device {
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+    use cpal::{FromSample, SizedSample};
+
+    use crate::audio::{AudioError, AudioOutput, PlaybackHandle};
+
+    /// Output playing on the default audio device of the system.
+    pub struct DeviceOutput {
+        device: cpal::Device,
+        config: cpal::SupportedStreamConfig,
+        stream: Option<cpal::Stream>,
+    }
+
+    impl std::fmt::Debug for DeviceOutput {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("DeviceOutput")
+                .field("config", &self.config)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl DeviceOutput {
+        pub fn new() -> Result<Self, AudioError> {
+            let device = cpal::default_host()
+                .default_output_device()
+                .ok_or_else(|| AudioError::Output("no output device available".into()))?;
+
+            let config = device
+                .default_output_config()
+                .map_err(|e| AudioError::Output(e.to_string()))?;
+
+            Ok(Self {
+                device,
+                config,
+                stream: None,
+            })
+        }
+
+        fn build_stream<T>(&self, playback: PlaybackHandle) -> Result<cpal::Stream, AudioError>
+        where
+            T: SizedSample + FromSample<f32>,
+        {
+            let config = self.config.config();
+            let channels = config.channels;
+            let sample_rate = config.sample_rate.0;
+            let mut samples: Vec<f32> = vec![];
+
+            self.device
+                .build_output_stream(
+                    &config,
+                    move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                        samples.resize(data.len(), 0.0);
+                        playback.fill(&mut samples, channels, sample_rate);
+
+                        for (out, sample) in data.iter_mut().zip(&samples) {
+                            *out = T::from_sample(*sample);
+                        }
+                    },
+                    |e| log::error!("audio output error: {e}"),
+                    None,
+                )
+                .map_err(|e| AudioError::Output(e.to_string()))
+        }
+    }
+
+    impl AudioOutput for DeviceOutput {
+        fn start(&mut self, playback: PlaybackHandle) -> Result<(), AudioError> {
+            let stream = match self.config.sample_format() {
+                cpal::SampleFormat::F32 => self.build_stream::<f32>(playback),
+                cpal::SampleFormat::I16 => self.build_stream::<i16>(playback),
+                cpal::SampleFormat::U16 => self.build_stream::<u16>(playback),
+                cpal::SampleFormat::I32 => self.build_stream::<i32>(playback),
+                format => Err(AudioError::Output(format!(
+                    "unsupported sample format {format}"
+                ))),
+            }?;
+
+            stream
+                .play()
+                .map_err(|e| AudioError::Output(e.to_string()))?;
+
+            self.stream = Some(stream);
+            Ok(())
+        }
+
+        #[inline]
+        fn sample_rate(&self) -> u32 {
+            self.config.sample_rate().0
+        }
+
+        #[inline]
+        fn channels(&self) -> u16 {
+            self.config.channels()
+        }
+    }
+}
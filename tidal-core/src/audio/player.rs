@@ -0,0 +1,146 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::audio::{AudioBuffer, AudioError, AudioOutput};
+
+/// How far apart, in seconds, the audio and the demo clock can drift before the audio seeks.
+pub const DEFAULT_SYNC_TOLERANCE: f32 = 0.05;
+
+#[derive(Debug)]
+struct Playback {
+    buffer: AudioBuffer,
+    /// Position in frames of the buffer
+    position: f64,
+    playing: bool,
+    volume: f32,
+}
+
+/// Playback state shared between an [`AudioPlayer`] and its output, which pulls samples from it.
+#[derive(Debug, Clone)]
+pub struct PlaybackHandle(Arc<Mutex<Playback>>);
+
+impl PlaybackHandle {
+    fn new(buffer: AudioBuffer) -> Self {
+        Self(Arc::new(Mutex::new(Playback {
+            buffer,
+            position: 0.0,
+            playing: false,
+            volume: 1.0,
+        })))
+    }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, Playback> {
+        // A panicking output callback leaves the playback state consistent.
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Fills `out` with interleaved samples for an output with the given layout, advancing the
+    /// playback. Writes silence while paused.
+    pub fn fill(&self, out: &mut [f32], channels: u16, sample_rate: u32) {
+        let mut playback = self.lock();
+
+        if !playback.playing {
+            out.fill(0.0);
+            return;
+        }
+
+        let step = playback.buffer.sample_rate() as f64 / sample_rate as f64;
+        let volume = playback.volume;
+
+        for frame in out.chunks_mut(channels as usize) {
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                *sample = playback.buffer.sample(playback.position, channel as u16) * volume;
+            }
+
+            playback.position += step;
+        }
+    }
+}
+
+/// Plays an audio buffer on an output, following the demo clock.
+#[derive(Debug)]
+pub struct AudioPlayer<O: AudioOutput> {
+    output: O,
+    playback: PlaybackHandle,
+    sync_tolerance: f32,
+}
+
+impl<O: AudioOutput> AudioPlayer<O> {
+    pub fn new(mut output: O, buffer: AudioBuffer) -> Result<Self, AudioError> {
+        let playback = PlaybackHandle::new(buffer);
+
+        output.start(playback.clone())?;
+
+        Ok(Self {
+            output,
+            playback,
+            sync_tolerance: DEFAULT_SYNC_TOLERANCE,
+        })
+    }
+
+    pub fn with_sync_tolerance(mut self, sync_tolerance: f32) -> Self {
+        self.sync_tolerance = sync_tolerance;
+        self
+    }
+
+    #[inline]
+    pub fn output(&self) -> &O {
+        &self.output
+    }
+
+    #[inline]
+    pub fn output_mut(&mut self) -> &mut O {
+        &mut self.output
+    }
+
+    pub fn play(&self) {
+        self.playback.lock().playing = true;
+    }
+
+    pub fn pause(&self) {
+        self.playback.lock().playing = false;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playback.lock().playing
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        self.playback.lock().volume = volume.max(0.0);
+    }
+
+    /// Moves the playback to `time`, in seconds.
+    pub fn seek(&self, time: f32) {
+        let mut playback = self.playback.lock();
+
+        playback.position = time.max(0.0) as f64 * playback.buffer.sample_rate() as f64;
+    }
+
+    /// Current playback position, in seconds.
+    pub fn time(&self) -> f32 {
+        let playback = self.playback.lock();
+
+        (playback.position / playback.buffer.sample_rate() as f64) as f32
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.playback.lock().buffer.duration()
+    }
+
+    /// Keeps the audio playing at the demo clock's `time`, seeking when it drifted further
+    /// than the sync tolerance.
+    ///
+    /// Outputs pull audio in blocks, so small drifts are expected and corrected by the next
+    /// block rather than by seeking, which would be audible.
+    pub fn sync(&self, time: f32) {
+        let mut playback = self.playback.lock();
+        let sample_rate = playback.buffer.sample_rate() as f64;
+        let current = (playback.position / sample_rate) as f32;
+
+        if (current - time).abs() > self.sync_tolerance {
+            playback.position = time.max(0.0) as f64 * sample_rate;
+        }
+
+        playback.playing = true;
+    }
+}
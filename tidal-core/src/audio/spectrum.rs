@@ -0,0 +1,162 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use realfft::num_complex::Complex;
+use realfft::{RealFftPlanner, RealToComplex};
+
+use crate::audio::AudioBuffer;
+
+/// Number of samples analyzed at once. About 43ms at 48kHz.
+pub const SPECTRUM_WINDOW_SIZE: usize = 2048;
+
+/// Frequency content of a window of audio.
+#[derive(Debug, Clone, Default)]
+pub struct Spectrum {
+    /// Amplitude of each frequency bin, a full scale sine peaking at 1
+    magnitudes: Vec<f32>,
+    /// Width of a bin, in Hz
+    bin_width: f32,
+    /// Root mean square of the window's samples
+    rms: f32,
+}
+
+impl Spectrum {
+    #[inline]
+    pub fn magnitudes(&self) -> &[f32] {
+        &self.magnitudes
+    }
+
+    #[inline]
+    pub fn rms(&self) -> f32 {
+        self.rms
+    }
+
+    /// Range of bins covering frequencies from `low` to `high`, in Hz. Never empty unless the
+    /// spectrum is.
+    fn bins(&self, low: f32, high: f32) -> std::ops::Range<usize> {
+        let count = self.magnitudes.len();
+        if count == 0 {
+            return 0..0;
+        }
+
+        let bin = |frequency: f32| (frequency / self.bin_width).round().max(0.0) as usize;
+
+        let start = bin(low.min(high)).min(count - 1);
+        let end = bin(low.max(high)).clamp(start + 1, count);
+
+        start..end
+    }
+
+    /// Energy of the frequencies from `low` to `high` Hz, as the RMS of their amplitudes.
+    pub fn band_energy(&self, low: f32, high: f32) -> f32 {
+        let bins = self.bins(low, high);
+        let count = bins.len();
+
+        if count == 0 {
+            return 0.0;
+        }
+
+        let sum: f32 = self.magnitudes[bins].iter().map(|m| m * m).sum();
+
+        (sum / count as f32).sqrt()
+    }
+
+    /// Spectral flux of the band since `previous`: how much its amplitudes increased.
+    pub fn flux(&self, previous: &Spectrum, low: f32, high: f32) -> f32 {
+        let bins = self.bins(low, high);
+        let count = bins.len();
+
+        if count == 0 || previous.magnitudes.len() != self.magnitudes.len() {
+            return 0.0;
+        }
+
+        let sum: f32 = self.magnitudes[bins.clone()]
+            .iter()
+            .zip(&previous.magnitudes[bins])
+            .map(|(current, previous)| (current - previous).max(0.0))
+            .sum();
+
+        sum / count as f32
+    }
+}
+
+/// Computes spectra from decoded audio.
+///
+/// Analysis only depends on the samples of the buffer and the requested time, so results are
+/// the same regardless of the frame rate or whether the audio is playing.
+pub struct SpectrumAnalyzer {
+    fft: Arc<dyn RealToComplex<f32>>,
+    window: Vec<f32>,
+    input: Vec<f32>,
+    output: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+}
+
+impl std::fmt::Debug for SpectrumAnalyzer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpectrumAnalyzer")
+            .field("size", &self.window.len())
+            .finish()
+    }
+}
+
+impl Default for SpectrumAnalyzer {
+    fn default() -> Self {
+        Self::new(SPECTRUM_WINDOW_SIZE)
+    }
+}
+
+impl SpectrumAnalyzer {
+    pub fn new(size: usize) -> Self {
+        let size = size.max(2);
+        let fft = RealFftPlanner::<f32>::new().plan_fft_forward(size);
+
+        // Hann window, reducing the leakage between bins.
+        let window = (0..size)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / (size - 1) as f32).cos())
+            .collect();
+
+        Self {
+            input: fft.make_input_vec(),
+            output: fft.make_output_vec(),
+            scratch: fft.make_scratch_vec(),
+            fft,
+            window,
+        }
+    }
+
+    /// Analyzes the window of audio ending at `time`, in seconds, with the channels mixed down.
+    pub fn analyze(&mut self, buffer: &AudioBuffer, time: f32) -> Spectrum {
+        let size = self.window.len();
+        let end = (time as f64 * buffer.sample_rate() as f64).round() as i64;
+        let start = end - size as i64;
+
+        let mut squares = 0.0;
+
+        for (i, (input, window)) in self.input.iter_mut().zip(&self.window).enumerate() {
+            let frame = start + i as i64;
+            let sample = if frame < 0 {
+                0.0
+            } else {
+                buffer.mixed_sample(frame as usize)
+            };
+
+            squares += sample * sample;
+            *input = sample * window;
+        }
+
+        // Only fails if the buffers have the wrong length, which they can't.
+        self.fft
+            .process_with_scratch(&mut self.input, &mut self.output, &mut self.scratch)
+            .expect("buffers planned by the fft");
+
+        // The window halves the amplitude on average.
+        let scale = 4.0 / size as f32;
+
+        Spectrum {
+            magnitudes: self.output.iter().map(|c| c.norm() * scale).collect(),
+            bin_width: buffer.sample_rate() as f32 / size as f32,
+            rms: (squares / size as f32).sqrt(),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+/// Musical tempo of a soundtrack, used to lock visuals to the beat.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct Tempo {
+    /// Beats per minute
+    pub bpm: f32,
+    /// Time of the first beat, in seconds
+    pub offset: f32,
+    pub beats_per_bar: u32,
+}
+
+impl Default for Tempo {
+    fn default() -> Self {
+        Self {
+            bpm: 120.0,
+            offset: 0.0,
+            beats_per_bar: 4,
+        }
+    }
+}
+
+/// Position in the music at a given time.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct BeatPosition {
+    /// Beats elapsed since the first beat, with the fraction of the current one
+    pub beats: f32,
+    /// Index of the current beat
+    pub beat: u32,
+    /// Index of the current bar
+    pub bar: u32,
+    /// Index of the current beat inside its bar
+    pub beat_in_bar: u32,
+    /// Progress through the current beat, from 0 to 1
+    pub beat_phase: f32,
+    /// Progress through the current bar, from 0 to 1
+    pub bar_phase: f32,
+}
+
+impl Tempo {
+    /// Length of a beat, in seconds.
+    #[inline]
+    pub fn beat_duration(&self) -> f32 {
+        60.0 / self.bpm.max(f32::EPSILON)
+    }
+
+    /// Returns the position in the music at `time`, in seconds. Times before the first beat are
+    /// at the start of the first beat.
+    pub fn position(&self, time: f32) -> BeatPosition {
+        let beats = ((time - self.offset) / self.beat_duration()).max(0.0);
+        let beats_per_bar = self.beats_per_bar.max(1);
+
+        let beat = beats as u32;
+        let bars = beats / beats_per_bar as f32;
+
+        BeatPosition {
+            beats,
+            beat,
+            bar: beat / beats_per_bar,
+            beat_in_bar: beat % beats_per_bar,
+            beat_phase: beats.fract(),
+            bar_phase: bars.fract(),
+        }
+    }
+}
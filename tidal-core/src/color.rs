@@ -0,0 +1,62 @@
+use cgmath::{Vector3, Vector4};
+use serde::{Deserialize, Serialize};
+
+/// Linear RGBA color, with straight (not premultiplied) alpha.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const WHITE: Color = Color::new(1.0, 1.0, 1.0, 1.0);
+    pub const BLACK: Color = Color::new(0.0, 0.0, 0.0, 1.0);
+
+    #[inline]
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Creates an opaque color.
+    #[inline]
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::new(r, g, b, 1.0)
+    }
+
+    #[inline]
+    pub fn to_rgb(&self) -> Vector3<f32> {
+        Vector3::new(self.r, self.g, self.b)
+    }
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Self::WHITE
+    }
+}
+
+impl From<Vector3<f32>> for Color {
+    fn from(v: Vector3<f32>) -> Self {
+        Self::rgb(v.x, v.y, v.z)
+    }
+}
+
+impl From<Vector4<f32>> for Color {
+    fn from(v: Vector4<f32>) -> Self {
+        Self::new(v.x, v.y, v.z, v.w)
+    }
+}
+
+impl From<Color> for Vector4<f32> {
+    fn from(c: Color) -> Self {
+        Vector4::new(c.r, c.g, c.b, c.a)
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(c: Color) -> Self {
+        [c.r, c.g, c.b, c.a]
+    }
+}
@@ -1,8 +1,32 @@
+use std::path::{Path, PathBuf};
+
 use serde::{Deserialize, Serialize};
 
-use crate::graph::Graph;
+use crate::audio::{AudioBuffer, AudioError, Tempo};
+use crate::graph::{Graph, Subgraph};
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Demo {
     pub graph: Graph,
+    #[serde(default)]
+    pub soundtrack: Option<Soundtrack>,
+    /// Subgraphs saved as reusable assets, instanced by group nodes
+    #[serde(default)]
+    pub library: Vec<Subgraph>,
+}
+
+/// Music the demo is synced to.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Soundtrack {
+    /// Path of the WAV or OGG file, relative to the project directory
+    pub path: PathBuf,
+    #[serde(default)]
+    pub tempo: Tempo,
+}
+
+impl Soundtrack {
+    /// Decodes the soundtrack, its path being relative to the project directory `root`.
+    pub fn load(&self, root: &Path) -> Result<AudioBuffer, AudioError> {
+        AudioBuffer::load(&root.join(&self.path))
+    }
 }
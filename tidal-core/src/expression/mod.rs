@@ -0,0 +1,171 @@
+//! A small math language for formulas such as `sin(t * 2.0) * a + vec3(x, 0, 1)`.
+//!
+//! Formulas are parsed, type checked and compiled once, when created, and then evaluated by a
+//! stack machine. Values are scalars or 3D vectors; operators and most functions apply to each
+//! component, broadcasting scalars when mixed with vectors.
+
+use cgmath::Vector3;
+use serde::{Deserialize, Serialize};
+
+use crate::expression::program::Program;
+use crate::graph::DataType;
+
+mod parser;
+mod program;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+#[error("{message} at {position}")]
+pub struct ExpressionError {
+    pub message: String,
+    /// Byte offset of the error in the source
+    pub position: usize,
+}
+
+impl ExpressionError {
+    fn new(message: impl Into<String>, position: usize) -> Self {
+        Self {
+            message: message.into(),
+            position,
+        }
+    }
+}
+
+/// Type of a value in an expression. Ordered so that mixing types results in the greatest one.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum ExpressionType {
+    Scalar,
+    Vector,
+}
+
+impl ExpressionType {
+    pub fn data_type(&self) -> DataType {
+        match self {
+            ExpressionType::Scalar => DataType::Scalar,
+            ExpressionType::Vector => DataType::Vector,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ExpressionValue {
+    Scalar(f32),
+    Vector(Vector3<f32>),
+}
+
+impl ExpressionValue {
+    #[inline]
+    pub fn as_scalar(&self) -> f32 {
+        match self {
+            ExpressionValue::Scalar(x) => *x,
+            ExpressionValue::Vector(v) => v.x,
+        }
+    }
+
+    /// Returns the vector, or a vector with every component set to the scalar.
+    #[inline]
+    pub fn as_vector(&self) -> Vector3<f32> {
+        match self {
+            ExpressionValue::Scalar(x) => Vector3::new(*x, *x, *x),
+            ExpressionValue::Vector(v) => *v,
+        }
+    }
+
+    #[inline]
+    fn map(self, f: impl Fn(f32) -> f32) -> Self {
+        match self {
+            ExpressionValue::Scalar(x) => ExpressionValue::Scalar(f(x)),
+            ExpressionValue::Vector(v) => ExpressionValue::Vector(v.map(f)),
+        }
+    }
+
+    /// Combines two values component by component, broadcasting a scalar mixed with a vector.
+    #[inline]
+    fn zip(self, other: Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        match (self, other) {
+            (ExpressionValue::Scalar(a), ExpressionValue::Scalar(b)) => {
+                ExpressionValue::Scalar(f(a, b))
+            }
+            (a, b) => {
+                let (a, b) = (a.as_vector(), b.as_vector());
+                ExpressionValue::Vector(Vector3::new(f(a.x, b.x), f(a.y, b.y), f(a.z, b.z)))
+            }
+        }
+    }
+}
+
+/// A free variable of an expression, provided when evaluating it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variable {
+    pub name: String,
+    pub data_type: ExpressionType,
+}
+
+/// A formula and its compiled program. Formulas that fail to compile keep their source and
+/// error so they can still be saved and edited.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct Expression {
+    source: String,
+    program: Result<Program, ExpressionError>,
+}
+
+impl Expression {
+    pub fn new(source: impl Into<String>) -> Self {
+        let source = source.into();
+        let program = parser::parse(&source).and_then(|ast| Program::compile(&ast));
+
+        Self { source, program }
+    }
+
+    #[inline]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    #[inline]
+    pub fn error(&self) -> Option<&ExpressionError> {
+        self.program.as_ref().err()
+    }
+
+    /// Free variables, in order of appearance. Empty if the formula doesn't compile.
+    pub fn variables(&self) -> &[Variable] {
+        self.program
+            .as_ref()
+            .map(|program| &program.variables[..])
+            .unwrap_or_default()
+    }
+
+    pub fn result_type(&self) -> ExpressionType {
+        self.program
+            .as_ref()
+            .map(|program| program.result_type)
+            .unwrap_or(ExpressionType::Scalar)
+    }
+
+    /// Evaluates the formula with the values of its [variables](Self::variables), in order.
+    /// Formulas that don't compile evaluate to zero.
+    ///
+    /// `stack` holds intermediate values and can be reused between evaluations.
+    pub fn evaluate(
+        &self,
+        variables: &[ExpressionValue],
+        stack: &mut Vec<ExpressionValue>,
+    ) -> ExpressionValue {
+        match &self.program {
+            Ok(program) => program.run(variables, stack),
+            Err(_) => ExpressionValue::Scalar(0.0),
+        }
+    }
+}
+
+impl From<String> for Expression {
+    fn from(source: String) -> Self {
+        Self::new(source)
+    }
+}
+
+impl From<Expression> for String {
+    fn from(expression: Expression) -> Self {
+        expression.source
+    }
+}
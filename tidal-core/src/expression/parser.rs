@@ -0,0 +1,331 @@
+use crate::expression::ExpressionError;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Power,
+}
+
+/// Syntax tree of a formula. Every node keeps its position in the source for error reporting.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Ast {
+    Number(f32, usize),
+    Identifier(String, usize),
+    Negate(Box<Ast>, usize),
+    Binary(BinaryOperator, Box<Ast>, Box<Ast>, usize),
+    Call(String, Vec<Ast>, usize),
+    /// Component access, such as `v.x` or `v.zyx`
+    Swizzle(Box<Ast>, String, usize),
+}
+
+impl Ast {
+    pub(crate) fn position(&self) -> usize {
+        match self {
+            Ast::Number(_, position)
+            | Ast::Identifier(_, position)
+            | Ast::Negate(_, position)
+            | Ast::Binary(_, _, _, position)
+            | Ast::Call(_, _, position)
+            | Ast::Swizzle(_, _, position) => *position,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f32),
+    Identifier(String),
+    Symbol(char),
+    End,
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, ExpressionError> {
+    let mut tokens = vec![];
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(position, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit()
+            || (c == '.' && source[position + 1..].starts_with(|c: char| c.is_ascii_digit()))
+        {
+            let mut end = position;
+            let mut previous = ' ';
+
+            while let Some(&(i, c)) = chars.peek() {
+                let exponent_sign = (c == '-' || c == '+') && matches!(previous, 'e' | 'E');
+
+                if !(c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || exponent_sign) {
+                    break;
+                }
+
+                previous = c;
+                end = i + c.len_utf8();
+                chars.next();
+            }
+
+            let number = source[position..end]
+                .parse()
+                .map_err(|_| ExpressionError::new("invalid number", position))?;
+
+            tokens.push((Token::Number(number), position));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = position;
+
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+
+                end = i + c.len_utf8();
+                chars.next();
+            }
+
+            tokens.push((Token::Identifier(source[position..end].into()), position));
+        } else if "+-*/%^(),.".contains(c) {
+            tokens.push((Token::Symbol(c), position));
+            chars.next();
+        } else {
+            return Err(ExpressionError::new(
+                format!("unexpected character '{c}'"),
+                position,
+            ));
+        }
+    }
+
+    tokens.push((Token::End, source.len()));
+    Ok(tokens)
+}
+
+/// Deepest nesting of a parsed expression, so deeply nested expressions fail to parse instead
+/// of overflowing the stack of the parser, or of the compiler walking their tree.
+const MAXIMUM_DEPTH: usize = 256;
+
+/// Recursive descent parser. From lowest to highest precedence: `+ -`, `* / %`, unary `-`,
+/// `^` (right associative), then calls and component access.
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    cursor: usize,
+    /// Levels of the tree above the expression being parsed
+    depth: usize,
+}
+
+impl Parser {
+    #[inline]
+    fn peek(&self) -> &(Token, usize) {
+        &self.tokens[self.cursor]
+    }
+
+    fn next(&mut self) -> (Token, usize) {
+        let token = self.tokens[self.cursor].clone();
+
+        if token.0 != Token::End {
+            self.cursor += 1;
+        }
+
+        token
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek().0 == Token::Symbol(symbol) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), ExpressionError> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(ExpressionError::new(
+                format!("expected '{symbol}'"),
+                self.peek().1,
+            ))
+        }
+    }
+
+    /// Enters a level of the tree, failing past [`MAXIMUM_DEPTH`].
+    fn nest(&mut self, position: usize) -> Result<(), ExpressionError> {
+        self.depth += 1;
+
+        if self.depth > MAXIMUM_DEPTH {
+            Err(ExpressionError::new(
+                "expression is nested too deeply",
+                position,
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn additive(&mut self) -> Result<Ast, ExpressionError> {
+        let depth = self.depth;
+        let mut lhs = self.multiplicative()?;
+
+        loop {
+            let operator = match self.peek() {
+                (Token::Symbol('+'), _) => BinaryOperator::Add,
+                (Token::Symbol('-'), _) => BinaryOperator::Subtract,
+                _ => break,
+            };
+
+            let (_, position) = self.next();
+            // Each operation of a chain nests the previous ones one level deeper.
+            self.nest(position)?;
+
+            let rhs = self.multiplicative()?;
+            lhs = Ast::Binary(operator, Box::new(lhs), Box::new(rhs), position);
+        }
+
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    fn multiplicative(&mut self) -> Result<Ast, ExpressionError> {
+        let depth = self.depth;
+        let mut lhs = self.unary()?;
+
+        loop {
+            let operator = match self.peek() {
+                (Token::Symbol('*'), _) => BinaryOperator::Multiply,
+                (Token::Symbol('/'), _) => BinaryOperator::Divide,
+                (Token::Symbol('%'), _) => BinaryOperator::Remainder,
+                _ => break,
+            };
+
+            let (_, position) = self.next();
+            self.nest(position)?;
+
+            let rhs = self.unary()?;
+            lhs = Ast::Binary(operator, Box::new(lhs), Box::new(rhs), position);
+        }
+
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    /// Every nested expression, in parentheses, arguments or operations, is parsed from here.
+    fn unary(&mut self) -> Result<Ast, ExpressionError> {
+        self.nest(self.peek().1)?;
+
+        let ast = match self.peek() {
+            (Token::Symbol('-'), position) => {
+                let position = *position;
+                self.next();
+
+                Ast::Negate(Box::new(self.unary()?), position)
+            }
+            (Token::Symbol('+'), _) => {
+                self.next();
+                self.unary()?
+            }
+            _ => self.power()?,
+        };
+
+        self.depth -= 1;
+        Ok(ast)
+    }
+
+    fn power(&mut self) -> Result<Ast, ExpressionError> {
+        let base = self.postfix()?;
+
+        if let (Token::Symbol('^'), position) = self.peek() {
+            let position = *position;
+            self.next();
+
+            // Binds tighter than the unary minus on its left, but accepts one on its right.
+            let exponent = self.unary()?;
+
+            return Ok(Ast::Binary(
+                BinaryOperator::Power,
+                Box::new(base),
+                Box::new(exponent),
+                position,
+            ));
+        }
+
+        Ok(base)
+    }
+
+    fn postfix(&mut self) -> Result<Ast, ExpressionError> {
+        let depth = self.depth;
+        let mut ast = self.primary()?;
+
+        while let (Token::Symbol('.'), position) = self.peek() {
+            let position = *position;
+            self.next();
+            self.nest(position)?;
+
+            match self.next() {
+                (Token::Identifier(components), _) => {
+                    ast = Ast::Swizzle(Box::new(ast), components, position);
+                }
+                (_, position) => {
+                    return Err(ExpressionError::new("expected components", position));
+                }
+            }
+        }
+
+        self.depth = depth;
+        Ok(ast)
+    }
+
+    fn primary(&mut self) -> Result<Ast, ExpressionError> {
+        match self.next() {
+            (Token::Number(number), position) => Ok(Ast::Number(number, position)),
+            (Token::Identifier(name), position) => {
+                if !self.eat('(') {
+                    return Ok(Ast::Identifier(name, position));
+                }
+
+                let mut arguments = vec![];
+
+                if !self.eat(')') {
+                    loop {
+                        arguments.push(self.additive()?);
+
+                        if self.eat(')') {
+                            break;
+                        }
+
+                        self.expect(',')?;
+                    }
+                }
+
+                Ok(Ast::Call(name, arguments, position))
+            }
+            (Token::Symbol('('), _) => {
+                let ast = self.additive()?;
+                self.expect(')')?;
+
+                Ok(ast)
+            }
+            (Token::End, position) => Err(ExpressionError::new(
+                "unexpected end of expression",
+                position,
+            )),
+            (_, position) => Err(ExpressionError::new("expected a value", position)),
+        }
+    }
+}
+
+pub(crate) fn parse(source: &str) -> Result<Ast, ExpressionError> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        cursor: 0,
+        depth: 0,
+    };
+
+    let ast = parser.additive()?;
+
+    match parser.peek() {
+        (Token::End, _) => Ok(ast),
+        (_, position) => Err(ExpressionError::new("unexpected token", *position)),
+    }
+}
@@ -0,0 +1,525 @@
+use std::collections::HashSet;
+use std::f32::consts::{PI, TAU};
+
+use cgmath::{InnerSpace, Vector3};
+
+use crate::expression::parser::{Ast, BinaryOperator};
+use crate::expression::{ExpressionError, ExpressionType, ExpressionValue, Variable};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Atan2,
+    Sqrt,
+    Abs,
+    Sign,
+    Floor,
+    Ceil,
+    Round,
+    Fract,
+    Exp,
+    Log,
+    Pow,
+    Mod,
+    Min,
+    Max,
+    Step,
+    Clamp,
+    Mix,
+    Smoothstep,
+    Length,
+    Normalize,
+    Distance,
+    Dot,
+    Cross,
+    Vec3,
+}
+
+/// What a function accepts and returns.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Signature {
+    /// Applied to each component. Scalar arguments are broadcast when mixed with vectors.
+    Componentwise(usize),
+    /// Takes vectors only, returning the given type
+    Vectors(usize, ExpressionType),
+    /// `vec3(s)` or `vec3(x, y, z)`
+    Constructor,
+}
+
+const FUNCTIONS: [(&str, Function, Signature); 30] = [
+    ("sin", Function::Sin, Signature::Componentwise(1)),
+    ("cos", Function::Cos, Signature::Componentwise(1)),
+    ("tan", Function::Tan, Signature::Componentwise(1)),
+    ("asin", Function::Asin, Signature::Componentwise(1)),
+    ("acos", Function::Acos, Signature::Componentwise(1)),
+    ("atan", Function::Atan, Signature::Componentwise(1)),
+    ("atan2", Function::Atan2, Signature::Componentwise(2)),
+    ("sqrt", Function::Sqrt, Signature::Componentwise(1)),
+    ("abs", Function::Abs, Signature::Componentwise(1)),
+    ("sign", Function::Sign, Signature::Componentwise(1)),
+    ("floor", Function::Floor, Signature::Componentwise(1)),
+    ("ceil", Function::Ceil, Signature::Componentwise(1)),
+    ("round", Function::Round, Signature::Componentwise(1)),
+    ("fract", Function::Fract, Signature::Componentwise(1)),
+    ("exp", Function::Exp, Signature::Componentwise(1)),
+    ("log", Function::Log, Signature::Componentwise(1)),
+    ("pow", Function::Pow, Signature::Componentwise(2)),
+    ("mod", Function::Mod, Signature::Componentwise(2)),
+    ("min", Function::Min, Signature::Componentwise(2)),
+    ("max", Function::Max, Signature::Componentwise(2)),
+    ("step", Function::Step, Signature::Componentwise(2)),
+    ("clamp", Function::Clamp, Signature::Componentwise(3)),
+    ("mix", Function::Mix, Signature::Componentwise(3)),
+    (
+        "smoothstep",
+        Function::Smoothstep,
+        Signature::Componentwise(3),
+    ),
+    (
+        "length",
+        Function::Length,
+        Signature::Vectors(1, ExpressionType::Scalar),
+    ),
+    (
+        "normalize",
+        Function::Normalize,
+        Signature::Vectors(1, ExpressionType::Vector),
+    ),
+    (
+        "distance",
+        Function::Distance,
+        Signature::Vectors(2, ExpressionType::Scalar),
+    ),
+    (
+        "dot",
+        Function::Dot,
+        Signature::Vectors(2, ExpressionType::Scalar),
+    ),
+    (
+        "cross",
+        Function::Cross,
+        Signature::Vectors(2, ExpressionType::Vector),
+    ),
+    ("vec3", Function::Vec3, Signature::Constructor),
+];
+
+const CONSTANTS: [(&str, f32); 2] = [("pi", PI), ("tau", TAU)];
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Instruction {
+    Constant(ExpressionValue),
+    Variable(usize),
+    Negate,
+    Binary(BinaryOperator),
+    Call(Function, usize),
+    Swizzle([u8; 3], usize),
+}
+
+/// A formula compiled into instructions for a stack machine.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Program {
+    instructions: Vec<Instruction>,
+    pub(crate) variables: Vec<Variable>,
+    pub(crate) result_type: ExpressionType,
+}
+
+fn find_function(name: &str) -> Option<(Function, Signature)> {
+    FUNCTIONS
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .map(|(_, function, signature)| (*function, *signature))
+}
+
+/// Free variables in order of appearance. Variables needed as vectors, such as `v` in
+/// `length(v)`, `length(v * 2.0)` or `v.x`, are vectors; every other variable is a scalar.
+fn collect_variables(ast: &Ast, variables: &mut Vec<Variable>, vectors: &mut HashSet<String>) {
+    match ast {
+        Ast::Number(..) => {}
+        Ast::Identifier(name, _) => {
+            if !is_constant(name) && variables.iter().all(|v| v.name != *name) {
+                variables.push(Variable {
+                    name: name.clone(),
+                    data_type: ExpressionType::Scalar,
+                });
+            }
+        }
+        Ast::Negate(ast, _) => collect_variables(ast, variables, vectors),
+        Ast::Binary(_, lhs, rhs, _) => {
+            collect_variables(lhs, variables, vectors);
+            collect_variables(rhs, variables, vectors);
+        }
+        Ast::Call(name, arguments, _) => {
+            if let Some((_, Signature::Vectors(..))) = find_function(name) {
+                for argument in arguments {
+                    require_vector(argument, vectors);
+                }
+            }
+
+            for argument in arguments {
+                collect_variables(argument, variables, vectors);
+            }
+        }
+        Ast::Swizzle(ast, _, _) => {
+            require_vector(ast, vectors);
+            collect_variables(ast, variables, vectors);
+        }
+    }
+}
+
+#[inline]
+fn is_constant(name: &str) -> bool {
+    CONSTANTS.iter().any(|(n, _)| *n == name)
+}
+
+/// Type of `ast` when it doesn't depend on the type of its variables.
+fn fixed_type(ast: &Ast) -> Option<ExpressionType> {
+    match ast {
+        Ast::Number(..) => Some(ExpressionType::Scalar),
+        Ast::Identifier(name, _) => is_constant(name).then_some(ExpressionType::Scalar),
+        Ast::Negate(ast, _) => fixed_type(ast),
+        Ast::Binary(_, lhs, rhs, _) => fixed_componentwise_type([&**lhs, &**rhs]),
+        Ast::Call(name, arguments, _) => match find_function(name)? {
+            (_, Signature::Componentwise(_)) => fixed_componentwise_type(arguments),
+            (_, Signature::Vectors(_, result)) => Some(result),
+            (_, Signature::Constructor) => Some(ExpressionType::Vector),
+        },
+        Ast::Swizzle(_, components, _) if components.len() == 1 => Some(ExpressionType::Scalar),
+        Ast::Swizzle(..) => Some(ExpressionType::Vector),
+    }
+}
+
+/// Type of a componentwise operation: a vector if any operand is, a scalar if all of them are.
+fn fixed_componentwise_type<'a>(
+    operands: impl IntoIterator<Item = &'a Ast>,
+) -> Option<ExpressionType> {
+    let mut result = Some(ExpressionType::Scalar);
+
+    for operand in operands {
+        match fixed_type(operand) {
+            Some(ExpressionType::Vector) => return Some(ExpressionType::Vector),
+            Some(ExpressionType::Scalar) => {}
+            None => result = None,
+        }
+    }
+
+    result
+}
+
+/// Marks the variables `ast` needs to be vectors for it to be one. Componentwise operations are
+/// vectors when any operand is, so unless one of them already is, every operand whose type
+/// depends on its variables is required to be a vector.
+fn require_vector(ast: &Ast, vectors: &mut HashSet<String>) {
+    let require_operands = |operands: &[&Ast], vectors: &mut HashSet<String>| {
+        if fixed_componentwise_type(operands.iter().copied()) == Some(ExpressionType::Vector) {
+            return;
+        }
+
+        for operand in operands {
+            if fixed_type(operand).is_none() {
+                require_vector(operand, vectors);
+            }
+        }
+    };
+
+    match ast {
+        Ast::Identifier(name, _) if !is_constant(name) => {
+            vectors.insert(name.clone());
+        }
+        Ast::Negate(ast, _) => require_vector(ast, vectors),
+        Ast::Binary(_, lhs, rhs, _) => require_operands(&[lhs, rhs], vectors),
+        Ast::Call(name, arguments, _) => {
+            if let Some((_, Signature::Componentwise(_))) = find_function(name) {
+                require_operands(&arguments.iter().collect::<Vec<_>>(), vectors);
+            }
+        }
+        // Numbers, constants, swizzles and other functions have a fixed type.
+        _ => {}
+    }
+}
+
+struct Compiler<'a> {
+    variables: &'a [Variable],
+    instructions: Vec<Instruction>,
+}
+
+impl<'a> Compiler<'a> {
+    /// Emits the instructions of `ast`, returning the type it leaves on the stack.
+    fn compile(&mut self, ast: &Ast) -> Result<ExpressionType, ExpressionError> {
+        match ast {
+            Ast::Number(number, _) => {
+                self.emit(Instruction::Constant(ExpressionValue::Scalar(*number)));
+                Ok(ExpressionType::Scalar)
+            }
+            Ast::Identifier(name, _) => {
+                if let Some((_, value)) = CONSTANTS.iter().find(|(n, _)| n == name) {
+                    self.emit(Instruction::Constant(ExpressionValue::Scalar(*value)));
+                    return Ok(ExpressionType::Scalar);
+                }
+
+                let index = self
+                    .variables
+                    .iter()
+                    .position(|v| v.name == *name)
+                    .expect("variables are collected before compiling");
+
+                self.emit(Instruction::Variable(index));
+                Ok(self.variables[index].data_type)
+            }
+            Ast::Negate(ast, _) => {
+                let data_type = self.compile(ast)?;
+                self.emit(Instruction::Negate);
+
+                Ok(data_type)
+            }
+            Ast::Binary(operator, lhs, rhs, _) => {
+                let lhs = self.compile(lhs)?;
+                let rhs = self.compile(rhs)?;
+                self.emit(Instruction::Binary(*operator));
+
+                Ok(lhs.max(rhs))
+            }
+            Ast::Call(name, arguments, position) => {
+                let (function, signature) = find_function(name).ok_or_else(|| {
+                    ExpressionError::new(format!("unknown function '{name}'"), *position)
+                })?;
+
+                let arity = match signature {
+                    Signature::Componentwise(arity) | Signature::Vectors(arity, _) => arity,
+                    Signature::Constructor if arguments.len() == 1 => 1,
+                    Signature::Constructor => 3,
+                };
+
+                if arguments.len() != arity {
+                    return Err(ExpressionError::new(
+                        format!("'{name}' takes {arity} arguments"),
+                        *position,
+                    ));
+                }
+
+                let mut types = Vec::with_capacity(arguments.len());
+                for argument in arguments {
+                    types.push((self.compile(argument)?, argument.position()));
+                }
+
+                self.emit(Instruction::Call(function, arity));
+
+                match signature {
+                    Signature::Componentwise(_) => Ok(types
+                        .iter()
+                        .map(|(data_type, _)| *data_type)
+                        .max()
+                        .unwrap_or(ExpressionType::Scalar)),
+                    Signature::Vectors(_, result) => {
+                        match types.iter().find(|(t, _)| *t != ExpressionType::Vector) {
+                            Some((_, position)) => Err(ExpressionError::new(
+                                format!("'{name}' expects vectors"),
+                                *position,
+                            )),
+                            None => Ok(result),
+                        }
+                    }
+                    Signature::Constructor => {
+                        match types.iter().find(|(t, _)| *t != ExpressionType::Scalar) {
+                            Some((_, position)) => Err(ExpressionError::new(
+                                format!("'{name}' expects scalars"),
+                                *position,
+                            )),
+                            None => Ok(ExpressionType::Vector),
+                        }
+                    }
+                }
+            }
+            Ast::Swizzle(ast, components, position) => {
+                if self.compile(ast)? != ExpressionType::Vector {
+                    return Err(ExpressionError::new("components of a scalar", *position));
+                }
+
+                let mut indices = [0; 3];
+                for (i, c) in components.chars().enumerate() {
+                    let index = match c {
+                        'x' | 'r' => 0,
+                        'y' | 'g' => 1,
+                        'z' | 'b' => 2,
+                        _ => {
+                            return Err(ExpressionError::new(
+                                format!("unknown component '{c}'"),
+                                *position,
+                            ))
+                        }
+                    };
+
+                    if let Some(slot) = indices.get_mut(i) {
+                        *slot = index;
+                    }
+                }
+
+                match components.len() {
+                    1 => {
+                        self.emit(Instruction::Swizzle(indices, 1));
+                        Ok(ExpressionType::Scalar)
+                    }
+                    3 => {
+                        self.emit(Instruction::Swizzle(indices, 3));
+                        Ok(ExpressionType::Vector)
+                    }
+                    _ => Err(ExpressionError::new(
+                        "expected one or three components",
+                        *position,
+                    )),
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn emit(&mut self, instruction: Instruction) {
+        self.instructions.push(instruction)
+    }
+}
+
+impl Program {
+    pub(crate) fn compile(ast: &Ast) -> Result<Self, ExpressionError> {
+        let mut variables = vec![];
+        let mut vectors = HashSet::new();
+        collect_variables(ast, &mut variables, &mut vectors);
+
+        for variable in variables.iter_mut() {
+            if vectors.contains(&variable.name) {
+                variable.data_type = ExpressionType::Vector;
+            }
+        }
+
+        let mut compiler = Compiler {
+            variables: &variables,
+            instructions: vec![],
+        };
+
+        let result_type = compiler.compile(ast)?;
+        let instructions = compiler.instructions;
+
+        Ok(Self {
+            instructions,
+            variables,
+            result_type,
+        })
+    }
+
+    /// Runs the program with the values of its variables, in the order of `variables`.
+    ///
+    /// The types are checked at compile time, so variables are expected to have their declared
+    /// type. `stack` is only passed in to reuse its allocation.
+    pub(crate) fn run(
+        &self,
+        variables: &[ExpressionValue],
+        stack: &mut Vec<ExpressionValue>,
+    ) -> ExpressionValue {
+        stack.clear();
+
+        for instruction in &self.instructions {
+            match *instruction {
+                Instruction::Constant(value) => stack.push(value),
+                Instruction::Variable(index) => stack.push(variables[index]),
+                Instruction::Negate => {
+                    let value = stack.pop().unwrap();
+                    stack.push(value.map(|x| -x));
+                }
+                Instruction::Binary(operator) => {
+                    let rhs = stack.pop().unwrap();
+                    let lhs = stack.pop().unwrap();
+
+                    let f = match operator {
+                        BinaryOperator::Add => |a: f32, b: f32| a + b,
+                        BinaryOperator::Subtract => |a, b| a - b,
+                        BinaryOperator::Multiply => |a, b| a * b,
+                        BinaryOperator::Divide => |a, b| a / b,
+                        BinaryOperator::Remainder => |a: f32, b| a.rem_euclid(b),
+                        BinaryOperator::Power => |a: f32, b| a.powf(b),
+                    };
+
+                    stack.push(lhs.zip(rhs, f));
+                }
+                Instruction::Call(function, arity) => {
+                    let arguments = stack.split_off(stack.len() - arity);
+                    stack.push(call(function, &arguments));
+                }
+                Instruction::Swizzle(indices, len) => {
+                    let value = stack.pop().unwrap().as_vector();
+
+                    stack.push(match len {
+                        1 => ExpressionValue::Scalar(value[indices[0] as usize]),
+                        _ => ExpressionValue::Vector(Vector3::new(
+                            value[indices[0] as usize],
+                            value[indices[1] as usize],
+                            value[indices[2] as usize],
+                        )),
+                    });
+                }
+            }
+        }
+
+        stack.pop().unwrap_or(ExpressionValue::Scalar(0.0))
+    }
+}
+
+fn call(function: Function, arguments: &[ExpressionValue]) -> ExpressionValue {
+    use ExpressionValue::{Scalar, Vector};
+
+    let a = arguments[0];
+
+    match function {
+        Function::Sin => a.map(f32::sin),
+        Function::Cos => a.map(f32::cos),
+        Function::Tan => a.map(f32::tan),
+        Function::Asin => a.map(f32::asin),
+        Function::Acos => a.map(f32::acos),
+        Function::Atan => a.map(f32::atan),
+        Function::Atan2 => a.zip(arguments[1], f32::atan2),
+        Function::Sqrt => a.map(f32::sqrt),
+        Function::Abs => a.map(f32::abs),
+        Function::Sign => a.map(|x| if x == 0.0 { 0.0 } else { x.signum() }),
+        Function::Floor => a.map(f32::floor),
+        Function::Ceil => a.map(f32::ceil),
+        Function::Round => a.map(f32::round),
+        Function::Fract => a.map(|x| x - x.floor()),
+        Function::Exp => a.map(f32::exp),
+        Function::Log => a.map(f32::ln),
+        Function::Pow => a.zip(arguments[1], f32::powf),
+        Function::Mod => a.zip(arguments[1], f32::rem_euclid),
+        Function::Min => a.zip(arguments[1], f32::min),
+        Function::Max => a.zip(arguments[1], f32::max),
+        Function::Step => a.zip(arguments[1], |edge, x| (x >= edge) as u32 as f32),
+        Function::Clamp => a.zip(arguments[1], f32::max).zip(arguments[2], f32::min),
+        Function::Mix => {
+            let t = arguments[2];
+            a.zip(t, |a, t| a * (1.0 - t))
+                .zip(arguments[1].zip(t, |b, t| b * t), |a, b| a + b)
+        }
+        Function::Smoothstep => {
+            let t = arguments[2]
+                .zip(a, |x, e0| x - e0)
+                .zip(arguments[1].zip(a, |e1, e0| e1 - e0), |x, w| {
+                    (x / w).clamp(0.0, 1.0)
+                });
+            t.map(|t| t * t * (3.0 - 2.0 * t))
+        }
+        Function::Length => Scalar(a.as_vector().magnitude()),
+        Function::Normalize => {
+            let v = a.as_vector();
+            let length = v.magnitude();
+
+            Vector(if length > 0.0 { v / length } else { v })
+        }
+        Function::Distance => Scalar((a.as_vector() - arguments[1].as_vector()).magnitude()),
+        Function::Dot => Scalar(a.as_vector().dot(arguments[1].as_vector())),
+        Function::Cross => Vector(a.as_vector().cross(arguments[1].as_vector())),
+        Function::Vec3 => match arguments {
+            [x, y, z] => Vector(Vector3::new(x.as_scalar(), y.as_scalar(), z.as_scalar())),
+            _ => Vector(a.as_vector()),
+        },
+    }
+}
@@ -1,61 +1,147 @@
 use std::borrow::Cow;
+use std::rc::Rc;
 
-use cgmath::Vector3;
+use cgmath::{Vector2, Vector3, Vector4};
+use serde::{Deserialize, Serialize};
 
+use crate::animation::Curve;
+use crate::color::Color;
+use crate::expression::Expression;
 use crate::graph::{Constant, InputState, PortId};
 
-#[derive(Copy, Clone, Debug, Ord, PartialOrd, PartialEq, Eq)]
+pub use tidal_core_derive::OperatorEnum;
+
+#[derive(Copy, Clone, Debug, Ord, PartialOrd, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DataType {
     Scalar,
+    Int,
+    Bool,
+    String,
+    Vector2,
     Vector,
+    Vector4,
+    Color,
     Mesh,
     Texture,
     Command,
+    ScalarCurve,
+    VectorCurve,
+    Timeline,
+    Expression,
+    Group,
+    GroupPorts,
+    /// Type deriving [`OperatorEnum`], whose variants are listed by the input's metadata
+    Enum,
 }
 
 impl DataType {
     pub fn default_constant(&self) -> Option<Constant> {
         match self {
             DataType::Scalar => Some(Constant::Scalar(0.0)),
+            DataType::Int => Some(Constant::Int(0)),
+            DataType::Bool => Some(Constant::Bool(false)),
+            DataType::String => Some(Constant::String(String::new())),
+            DataType::Vector2 => Some(Constant::Vector2(Vector2::new(0.0, 0.0))),
             DataType::Vector => Some(Constant::Vector(Vector3::new(0.0, 0.0, 0.0))),
+            DataType::Vector4 => Some(Constant::Vector4(Vector4::new(0.0, 0.0, 0.0, 0.0))),
+            DataType::Color => Some(Constant::Color(Color::WHITE)),
+            DataType::ScalarCurve => {
+                Some(Constant::ScalarCurve(Rc::new(Curve::ramp(0.0, 1.0, 1.0))))
+            }
+            DataType::VectorCurve => Some(Constant::VectorCurve(Rc::new(Curve::ramp(
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(1.0, 1.0, 1.0),
+                1.0,
+            )))),
+            DataType::Timeline => Some(Constant::Timeline(Default::default())),
+            DataType::Expression => Some(Constant::Expression(Rc::new(Expression::new("a")))),
+            DataType::Group => Some(Constant::Group(Default::default())),
+            DataType::GroupPorts => Some(Constant::GroupPorts(Rc::new([]))),
             _ => None,
         }
     }
 
+    /// Whether an output of this type can feed an input of type `other`. Numbers convert
+    /// between each other: integers round scalars to the nearest, booleans are 0 or 1 and
+    /// integers are true when not zero. Integers also pick the variant of an enum by index.
     pub fn can_connect_to(&self, other: DataType) -> bool {
         match (self, other) {
             (DataType::Mesh, DataType::Command) => true,
             (DataType::Texture, DataType::Command) => true,
+            (DataType::Int | DataType::Bool, DataType::Scalar) => true,
+            (DataType::Scalar | DataType::Bool, DataType::Int) => true,
+            (DataType::Int, DataType::Bool) => true,
+            (DataType::Int, DataType::Enum) => true,
             _ => *self == other,
         }
     }
 }
 
+/// Range and step of the widget editing a numeric input. The input still accepts any value
+/// from a connection, the bounds only restrict what the editor lets the user type in.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct InputHint {
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+    pub step: Option<f32>,
+}
+
 #[derive(Debug, Clone)]
 pub struct InputMetadata {
-    pub name: &'static str,
+    pub name: Cow<'static, str>,
+    pub description: Option<&'static str>,
     pub required: bool,
     pub multiple: bool,
     pub default: Option<Constant>,
     pub data_type: DataType,
+    pub hint: InputHint,
+    /// Unconnected, the operator receives no value rather than a constant
+    pub optional: bool,
+    /// Variants of an enum input, empty for other types
+    pub variants: &'static [&'static str],
 }
 
 impl InputMetadata {
     /// Return the default constant value for this input
     pub fn default_constant(&self) -> Option<Constant> {
-        self.default.or_else(|| self.data_type.default_constant())
+        self.default
+            .clone()
+            .or_else(|| self.data_type.default_constant())
     }
 
+    /// State of the input before being connected. Required and optional inputs start
+    /// unconnected.
     pub fn default_state(&self) -> InputState {
+        if self.required || self.optional {
+            return InputState::default();
+        }
+
         self.default_constant()
             .map(|constant| InputState::Constant(constant))
             .unwrap_or_default()
     }
 }
 
+/// Enum without fields taken as input or output by operators. Its values are stored by the
+/// name of their variant, so reordering variants keeps saved graphs intact.
+///
+/// Derive it with `#[derive(OperatorEnum)]`.
+pub trait OperatorEnum: Sized {
+    /// Names of the variants, in declaration order
+    const VARIANTS: &'static [&'static str];
+
+    fn name(&self) -> &'static str;
+
+    fn from_name(name: &str) -> Option<Self>;
+
+    fn from_index(index: usize) -> Option<Self> {
+        Self::from_name(Self::VARIANTS.get(index)?)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct OutputMetadata {
-    pub name: &'static str,
+    pub name: Cow<'static, str>,
     pub data_type: DataType,
 }
 
@@ -63,6 +149,8 @@ pub struct OutputMetadata {
 pub struct Metadata {
     pub name: &'static str,
     pub description: Option<&'static str>,
+    /// Slash separated path of the menu listing the operator, such as `Math/Trig`
+    pub category: Option<&'static str>,
     pub inputs: Vec<InputMetadata>,
     pub outputs: Vec<OutputMetadata>,
 }
@@ -88,6 +176,12 @@ impl Metadata {
         self.outputs.get(id.0)
     }
 
+    #[inline]
+    pub fn get_output_mut(&mut self, id: impl Into<PortId>) -> Option<&mut OutputMetadata> {
+        let id = id.into();
+        self.outputs.get_mut(id.0)
+    }
+
     #[inline]
     pub fn iter_outputs(&self) -> impl Iterator<Item = (PortId, &OutputMetadata)> {
         self.outputs
@@ -45,11 +45,11 @@ impl Graph {
 
     fn can_connect_impl(&self, output: &NodePortId, input: &NodePortId) -> Option<bool> {
         let output_node = self.get_node(output.get_node_id())?;
-        let output_metadata = output_node.operator.describe();
+        let output_metadata = output_node.describe();
         let output_port = output_metadata.get_output(output.get_port_id())?;
 
         let input_node = self.get_node(input.get_node_id())?;
-        let input_metadata = input_node.operator.describe();
+        let input_metadata = input_node.describe();
         let input_port = input_metadata.get_input(input.get_port_id())?;
 
         if !output_port.data_type.can_connect_to(input_port.data_type) {
@@ -73,6 +73,13 @@ impl Graph {
         };
     }
 
+    /// Replaces the operator of the root node, resetting its inputs.
+    pub fn set_root_operator(&mut self, operator: Operator) {
+        let root = &mut self.nodes[NodeId::root().0];
+
+        *root = Node::new(operator, root.position);
+    }
+
     #[inline]
     pub fn can_remove_node(&self, node_id: &NodeId) -> bool {
         return !node_id.is_root();
@@ -3,9 +3,13 @@ pub use graph::*;
 pub use ids::*;
 pub use node::*;
 pub use operators::*;
+pub use subgraph::*;
+pub use validate::*;
 
 pub mod describe;
 pub mod graph;
 pub mod ids;
 pub mod node;
 pub mod operators;
+pub mod subgraph;
+pub mod validate;
@@ -1,19 +1,38 @@
 use std::borrow::Cow;
+use std::rc::Rc;
 use std::vec::IntoIter;
 
-use cgmath::{Vector2, Vector3};
+use cgmath::{Vector2, Vector3, Vector4};
 use derive_more::IsVariant;
 use serde::{Deserialize, Serialize};
 
-use crate::graph::{InputMetadata, Metadata, NodePortId, PortId};
+use crate::animation::{ScalarCurve, VectorCurve};
+use crate::color::Color;
+use crate::expression::Expression;
+use crate::graph::{GroupPort, InputMetadata, Metadata, NodePortId, PortId, Subgraph};
 use crate::operator::Operator;
+use crate::sequencer::Timeline;
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "type", content = "value")]
 pub enum Constant {
     Scalar(f32),
-    I32(i32),
+    #[serde(alias = "I32")]
+    Int(i32),
+    Bool(bool),
+    String(String),
+    Vector2(Vector2<f32>),
     Vector(Vector3<f32>),
+    Vector4(Vector4<f32>),
+    Color(Color),
+    ScalarCurve(Rc<ScalarCurve>),
+    VectorCurve(Rc<VectorCurve>),
+    Timeline(Rc<Timeline>),
+    Expression(Rc<Expression>),
+    Group(Rc<Subgraph>),
+    GroupPorts(Rc<[GroupPort]>),
+    /// Variant of an [`crate::graph::OperatorEnum`], stored by name
+    Enum(Rc<str>),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, IsVariant)]
@@ -49,19 +68,75 @@ pub enum Placement {
 
 impl Node {
     pub fn new(operator: Operator, position: Vector2<f32>) -> Self {
-        let metadata = operator.describe();
+        let mut node = Self {
+            operator,
+            inputs: vec![],
+            position,
+        };
+
+        // Ports can depend on the values of the inputs before them, so they are added one by
+        // one until the description stops growing.
+        loop {
+            let metadata = node.describe();
+
+            if node.inputs.len() >= metadata.inputs.len() {
+                break;
+            }
+
+            node.inputs.extend(
+                metadata.inputs[node.inputs.len()..]
+                    .iter()
+                    .map(InputMetadata::default_state),
+            );
+        }
+
+        node
+    }
+
+    /// Describes the ports of this node. Unlike [`Operator::describe`], it includes the ports
+    /// depending on the node's inputs, such as the variables of an expression, see
+    /// [`Operator::describe_node`].
+    #[inline]
+    pub fn describe(&self) -> Metadata {
+        self.operator.describe_node(&self.inputs)
+    }
 
-        let inputs = metadata
+    /// Sets the constant of an input, updating the ports that depend on it. Inputs keep their
+    /// state when a port with the same name and type remains.
+    pub fn set_constant(&mut self, id: impl Into<PortId>, constant: Constant) {
+        let id = id.into();
+        let previous = self.describe();
+
+        let Some(input) = self.get_input_state_mut(id) else {
+            return;
+        };
+
+        *input = InputState::Constant(constant);
+
+        let metadata = self.describe();
+        let mut previous_inputs: Vec<_> = previous
             .inputs
-            .iter()
-            .map(InputMetadata::default_state)
+            .into_iter()
+            .zip(std::mem::take(&mut self.inputs))
+            .map(Some)
             .collect();
 
-        Self {
-            operator,
-            inputs,
-            position,
-        }
+        self.inputs = metadata
+            .inputs
+            .iter()
+            .map(|input| {
+                previous_inputs
+                    .iter_mut()
+                    .find(|previous| {
+                        previous.as_ref().is_some_and(|(meta, _)| {
+                            meta.name == input.name && meta.data_type == input.data_type
+                        })
+                    })
+                    .and_then(Option::take)
+                    .map(|(_, state)| state)
+                    .unwrap_or_else(|| input.default_state())
+            })
+            .collect();
     }
 
     #[inline]
@@ -81,7 +156,7 @@ impl Node {
         id: impl Into<PortId>,
     ) -> Option<(&InputState, InputMetadata)> {
         let id = id.into();
-        let metadata = self.operator.describe();
+        let metadata = self.describe();
 
         let input_metadata = metadata.get_input(id)?;
         let input = self.get_input_state(id)?;
@@ -94,7 +169,7 @@ impl Node {
         id: impl Into<PortId>,
     ) -> Option<(&mut InputState, InputMetadata)> {
         let id = id.into();
-        let metadata = self.operator.describe();
+        let metadata = self.describe();
 
         let input_metadata = metadata.get_input(id)?;
         let input = self.get_input_state_mut(id)?;
@@ -105,7 +180,7 @@ impl Node {
     pub fn iter_described_inputs(
         &self,
     ) -> impl Iterator<Item = (PortId, &InputState, InputMetadata)> {
-        let metadata = self.operator.describe();
+        let metadata = self.describe();
 
         self.inputs
             .iter()
@@ -117,7 +192,7 @@ impl Node {
     pub fn iter_described_inputs_mut(
         &mut self,
     ) -> impl Iterator<Item = (PortId, &mut InputState, InputMetadata)> {
-        let metadata = self.operator.describe();
+        let metadata = self.describe();
 
         self.inputs
             .iter_mut()
@@ -129,13 +204,12 @@ impl Node {
     /// Creates a new connection from this node to the source node, inserting at index
     pub fn connect(&mut self, input_id: PortId, output: NodePortId, strategy: Placement) {
         if let Some((input, meta)) = self.get_described_input_mut(input_id) {
-            // Fix the strategy if not compatible with the input meta. If the input does not
-            // support multiple values, then we always will use the Direct strategy.
-            // let strategy = if meta.multiple {
-            //     strategy
-            // } else {
-            //     Placement::Replace(0)
-            // };
+            // An input receiving a single value can only have one connection, which the new
+            // one replaces wherever it was placed.
+            if !meta.multiple {
+                *input = InputState::Connection(vec![output]);
+                return;
+            }
 
             match input {
                 // If is a constant, then connect it.
@@ -0,0 +1,162 @@
+use std::rc::Rc;
+
+use cgmath::Vector2;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::graph::{Constant, DataType, Graph, InputState, Node, NodeId, PortId};
+use crate::operator::Operator;
+
+/// Port exposed by a group, declared by its `GroupInput` and `GroupOutput` nodes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GroupPort {
+    pub name: String,
+    pub data_type: DataType,
+}
+
+/// Graph instanced by `Group` nodes. Its root is a `GroupOutput` node, whose inputs are the
+/// outputs of the group, and its `GroupInput` node outputs the values the group receives.
+///
+/// Each group node holds a copy of its subgraph. Copies sharing an id are instances of the
+/// same library asset, kept in sync with [`Graph::update_groups`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Subgraph {
+    pub id: Uuid,
+    pub name: String,
+    pub graph: Graph,
+}
+
+impl Default for Subgraph {
+    fn default() -> Self {
+        Self::new("Group")
+    }
+}
+
+impl Subgraph {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            graph: Graph {
+                nodes: vec![
+                    Node::new(Operator::group_output(), Vector2::new(0.0, 0.0)),
+                    Node::new(Operator::group_input(), Vector2::new(-400.0, 0.0)),
+                ],
+            },
+        }
+    }
+
+    /// Ports the group receives, declared by its `GroupInput` node.
+    pub fn inputs(&self) -> &[GroupPort] {
+        self.graph
+            .nodes
+            .iter()
+            .find(|node| node.operator == Operator::group_input())
+            .map(group_ports)
+            .unwrap_or_default()
+    }
+
+    /// Ports the group outputs, declared by its root `GroupOutput` node.
+    pub fn outputs(&self) -> &[GroupPort] {
+        self.graph
+            .get_node(NodeId::root())
+            .map(group_ports)
+            .unwrap_or_default()
+    }
+}
+
+/// Ports declared by a `GroupInput` or `GroupOutput` node.
+pub(crate) fn group_ports(node: &Node) -> &[GroupPort] {
+    match node.get_input_state(PortId(0)) {
+        Some(InputState::Constant(Constant::GroupPorts(ports))) => ports,
+        _ => &[],
+    }
+}
+
+impl Node {
+    /// Subgraph of a `Group` node.
+    pub fn subgraph(&self) -> Option<&Subgraph> {
+        match self.get_input_state(PortId(0))? {
+            InputState::Constant(Constant::Group(subgraph))
+                if self.operator == Operator::group() =>
+            {
+                Some(subgraph)
+            }
+            _ => None,
+        }
+    }
+
+    /// Replaces the subgraph of a `Group` node, updating its ports.
+    pub fn set_subgraph(&mut self, subgraph: Subgraph) {
+        if self.operator == Operator::group() {
+            self.set_constant(PortId(0), Constant::Group(Rc::new(subgraph)));
+        }
+    }
+}
+
+impl Graph {
+    /// Subgraph of the group reached by following the group nodes of `path` from this graph.
+    pub fn group(&self, path: &[NodeId]) -> Option<&Subgraph> {
+        let (node_id, path) = path.split_last()?;
+
+        self.group_graph(path)?.get_node(*node_id)?.subgraph()
+    }
+
+    /// Graph of the group at `path`, or this graph when `path` is empty.
+    pub fn group_graph(&self, path: &[NodeId]) -> Option<&Graph> {
+        path.iter().try_fold(self, |graph, node_id| {
+            Some(&graph.get_node(*node_id)?.subgraph()?.graph)
+        })
+    }
+
+    /// Edits the subgraph of the group at `path`, updating the ports of the group nodes
+    /// containing it.
+    pub fn update_group(&mut self, path: &[NodeId], f: impl FnOnce(&mut Subgraph)) {
+        let Some((node_id, path)) = path.split_first() else {
+            return;
+        };
+
+        let Some(node) = self.get_node_mut(*node_id) else {
+            return;
+        };
+
+        let Some(mut subgraph) = node.subgraph().cloned() else {
+            return;
+        };
+
+        if path.is_empty() {
+            f(&mut subgraph);
+        } else {
+            subgraph.graph.update_group(path, f);
+        }
+
+        node.set_subgraph(subgraph);
+    }
+
+    /// Replaces every instance of `subgraph`, including the ones nested in other groups, so
+    /// they follow the edits made to one of them.
+    pub fn update_groups(&mut self, subgraph: &Subgraph) {
+        for node in self.nodes.iter_mut() {
+            let Some(current) = node.subgraph() else {
+                continue;
+            };
+
+            if current.id == subgraph.id {
+                node.set_subgraph(subgraph.clone());
+            } else if current.graph.contains_group(subgraph.id) {
+                let mut current = current.clone();
+                current.graph.update_groups(subgraph);
+
+                node.set_subgraph(current);
+            }
+        }
+    }
+
+    /// Whether an instance of the subgraph `id` is in this graph or nested in its groups.
+    pub fn contains_group(&self, id: Uuid) -> bool {
+        self.nodes
+            .iter()
+            .filter_map(Node::subgraph)
+            .any(|subgraph| subgraph.id == id || subgraph.graph.contains_group(id))
+    }
+}
@@ -0,0 +1,82 @@
+use thiserror::Error;
+
+use crate::graph::{Constant, Graph, InputState, NodeId, NodePortId};
+
+/// Problem preventing an input of a graph from being evaluated.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("input {input} {kind}")]
+pub struct ValidationError {
+    /// Group nodes leading to the graph of the input, empty for the validated graph itself
+    pub path: Vec<NodeId>,
+    pub input: NodePortId,
+    pub kind: ValidationErrorKind,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ValidationErrorKind {
+    #[error("is required but not connected")]
+    MissingConnection,
+    #[error("receives a single value but has {0} connections")]
+    TooManyConnections(usize),
+    #[error("is connected to output {0} of another type or that doesn't exist")]
+    InvalidConnection(NodePortId),
+    #[error("holds {0}, which isn't one of its variants")]
+    UnknownVariant(String),
+}
+
+impl Graph {
+    /// Checks the connections of every node against the metadata of its inputs, including the
+    /// nodes of its groups.
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = vec![];
+        self.validate_at(&mut vec![], &mut errors);
+
+        errors
+    }
+
+    fn validate_at(&self, path: &mut Vec<NodeId>, errors: &mut Vec<ValidationError>) {
+        for (node_id, node) in self.iter_nodes() {
+            for (port_id, state, metadata) in node.iter_described_inputs() {
+                let input = NodePortId(node_id, port_id);
+                let mut error = |kind| {
+                    errors.push(ValidationError {
+                        path: path.clone(),
+                        input,
+                        kind,
+                    })
+                };
+
+                if let InputState::Constant(Constant::Enum(name)) = state {
+                    if !metadata.variants.contains(&&**name) {
+                        error(ValidationErrorKind::UnknownVariant(name.to_string()));
+                    }
+                }
+
+                let connections = match state {
+                    InputState::Connection(connections) => connections.as_slice(),
+                    InputState::Constant(_) => &[],
+                };
+
+                if metadata.required && connections.is_empty() {
+                    error(ValidationErrorKind::MissingConnection);
+                }
+
+                if !metadata.multiple && connections.len() > 1 {
+                    error(ValidationErrorKind::TooManyConnections(connections.len()));
+                }
+
+                for output in connections {
+                    if !self.can_connect(output, &input) {
+                        error(ValidationErrorKind::InvalidConnection(*output));
+                    }
+                }
+            }
+
+            if let Some(subgraph) = node.subgraph() {
+                path.push(node_id);
+                subgraph.graph.validate_at(path, errors);
+                path.pop();
+            }
+        }
+    }
+}
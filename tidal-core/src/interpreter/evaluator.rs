@@ -1,15 +1,27 @@
+use std::cell::RefMut;
+use std::marker::PhantomData;
+
 use cgmath::Vector3;
 
 use tidal_core_derive::evaluator;
 
-use crate::graph::{NodeId, PortId};
-use crate::interpreter::{Interpreter, InterpreterContext, Multiple, MultipleValue, Value};
+use crate::audio::{AudioBuffer, Tempo};
+use crate::graph::{Graph, NodeId, NodePortId, PortId};
+use crate::interpreter::{
+    GraphState, Interpreter, InterpreterContext, Iteration, Lazy, LazyMultiple, Multiple,
+    MultipleValue, Scope, Value,
+};
 use crate::operator::Operator;
-use crate::renderer::Renderer;
+use crate::renderer::{RenderGraph, RenderGraphError, Renderer};
 
 #[derive(Copy, Clone, Debug)]
 pub enum EvaluateError {
     GenericError,
+    /// An input evaluated by the operator isn't connected
+    MissingConnection(NodePortId),
+    /// An output wasn't written by its node or isn't part of its metadata
+    MissingOutput(NodePortId),
+    RenderGraph(RenderGraphError),
 }
 
 pub trait Evaluate {
@@ -20,38 +32,185 @@ pub struct EvaluateContext<'a, 'i> {
     pub node_id: NodeId,
     pub interpreter: &'i Interpreter,
     pub interpreter_context: &'i mut InterpreterContext<'a>,
+    pub(crate) scope: Scope<'i>,
 }
 
 impl<'a, 'i> EvaluateContext<'a, 'i> {
     #[inline(always)]
-    pub(crate) fn evaluate_input(
-        &mut self,
-        port_id: impl Into<PortId>,
-    ) -> Result<Value, EvaluateError> {
-        self.interpreter
-            .evaluate_input(self.interpreter_context, self.node_id, port_id.into())
+    pub fn evaluate_input(&mut self, port_id: impl Into<PortId>) -> Result<Value, EvaluateError> {
+        self.interpreter.evaluate_input(
+            self.interpreter_context,
+            self.scope,
+            self.node_id,
+            port_id.into(),
+        )
     }
 
     #[inline(always)]
-    pub(crate) fn evaluate_input_multiple<T>(
+    pub fn evaluate_input_multiple(
         &mut self,
         port_id: impl Into<PortId>,
     ) -> Result<MultipleValue, EvaluateError> {
         self.interpreter.evaluate_input_multiple(
             self.interpreter_context,
+            self.scope,
             self.node_id,
             port_id.into(),
         )
     }
 
+    /// Evaluates the connection at `index` of a multiple input.
     #[inline(always)]
-    pub(crate) fn write_output(&mut self, port_id: impl Into<PortId>, value: Value) {
+    pub fn evaluate_input_at(
+        &mut self,
+        port_id: impl Into<PortId>,
+        index: usize,
+    ) -> Result<Value, EvaluateError> {
+        self.interpreter.evaluate_input_at(
+            self.interpreter_context,
+            self.scope,
+            self.node_id,
+            port_id.into(),
+            index,
+        )
+    }
+
+    /// Evaluates an input, or returns `None` when it has no connection nor constant.
+    #[inline]
+    pub fn evaluate_optional_input(
+        &mut self,
+        port_id: impl Into<PortId>,
+    ) -> Result<Option<Value>, EvaluateError> {
+        let port_id = port_id.into();
+
+        if self
+            .interpreter
+            .input_len(self.scope, self.node_id, port_id)
+            == 0
+        {
+            return Ok(None);
+        }
+
+        self.evaluate_input(port_id).map(Some)
+    }
+
+    #[inline]
+    pub fn lazy_input<T>(&self, port_id: impl Into<PortId>) -> Lazy<T> {
+        let port_id = port_id.into();
+
+        Lazy {
+            port_id,
+            connected: self
+                .interpreter
+                .input_len(self.scope, self.node_id, port_id)
+                > 0,
+            marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn lazy_input_multiple<T>(&self, port_id: impl Into<PortId>) -> LazyMultiple<T> {
+        let port_id = port_id.into();
+
+        LazyMultiple {
+            port_id,
+            len: self
+                .interpreter
+                .input_len(self.scope, self.node_id, port_id),
+            marker: PhantomData,
+        }
+    }
+
+    /// Evaluates `f` as the iteration `index` of `count` of a repeat. Upstream nodes see it
+    /// through [`EvaluateContext::iteration`] and keep a separate state in each iteration.
+    #[inline]
+    pub fn repeat<T>(&mut self, index: u32, count: u32, f: impl FnOnce(&mut Self) -> T) -> T {
+        let interpreter = self.interpreter;
+
+        interpreter.repeat(Iteration { index, count }, || f(self))
+    }
+
+    /// Iteration of the repeat `level` levels out from the innermost one being evaluated, or
+    /// `None` outside repeats.
+    #[inline]
+    pub fn iteration(&self, level: usize) -> Option<Iteration> {
+        self.interpreter.iteration(level)
+    }
+
+    /// Evaluates an output of the group being evaluated, an input of the root of its subgraph.
+    /// `state` holds the outputs and evaluators of the subgraph, kept by the group.
+    #[inline]
+    pub(crate) fn evaluate_group_output(
+        &mut self,
+        graph: &Graph,
+        state: &GraphState,
+        port_id: impl Into<PortId>,
+    ) -> Result<Value, EvaluateError> {
+        self.interpreter.evaluate_group_output(
+            self.interpreter_context,
+            &self.scope,
+            self.node_id,
+            graph,
+            state,
+            port_id.into(),
+        )
+    }
+
+    /// Evaluates an input of the group node whose subgraph is being evaluated.
+    #[inline]
+    pub(crate) fn evaluate_group_input(
+        &mut self,
+        port_id: impl Into<PortId>,
+    ) -> Result<Value, EvaluateError> {
+        let (parent, node_id) = self.scope.parent().ok_or(EvaluateError::GenericError)?;
+
+        self.interpreter
+            .evaluate_input(self.interpreter_context, *parent, node_id, port_id.into())
+    }
+
+    /// Writes an output of the node. Fails with [`EvaluateError::MissingOutput`] when the port
+    /// isn't part of the node's metadata.
+    #[inline(always)]
+    pub fn write_output(
+        &mut self,
+        port_id: impl Into<PortId>,
+        value: Value,
+    ) -> Result<(), EvaluateError> {
         self.interpreter
-            .write_output(self.node_id, port_id.into(), value.into())
+            .write_output(self.scope, self.node_id, port_id.into(), value.into())
     }
 
     #[inline(always)]
-    pub(crate) fn renderer(&mut self) -> &mut Renderer {
+    pub fn renderer(&mut self) -> &mut Renderer {
         self.interpreter_context.renderer
     }
+
+    /// Current time of the demo clock, in seconds.
+    #[inline(always)]
+    pub fn time(&self) -> f32 {
+        self.interpreter_context.time
+    }
+
+    /// Tempo of the demo's soundtrack, or the default tempo without one.
+    #[inline]
+    pub fn tempo(&self) -> Tempo {
+        self.interpreter
+            .demo()
+            .soundtrack
+            .as_ref()
+            .map(|soundtrack| soundtrack.tempo)
+            .unwrap_or_default()
+    }
+
+    /// Decoded audio of the demo's soundtrack, if it has one.
+    #[inline]
+    pub fn soundtrack(&self) -> Option<&'i AudioBuffer> {
+        self.interpreter.soundtrack()
+    }
+
+    /// The render graph of the frame being evaluated, borrowed until the guard is dropped.
+    #[inline(always)]
+    pub fn render_graph(&mut self) -> RefMut<'_, RenderGraph> {
+        self.interpreter.render_graph()
+    }
 }
@@ -1,74 +1,190 @@
-use std::cell::UnsafeCell;
+use std::cell::{Cell, RefCell, RefMut};
+use std::collections::HashMap;
+use std::rc::Rc;
 
+use crate::audio::AudioBuffer;
 use crate::demo::Demo;
-use crate::graph::{Constant, Graph, InputState, NodeId, PortId};
+use crate::graph::{Constant, Graph, InputState, NodeId, NodePortId, PortId};
 use crate::interpreter::evaluator::{Evaluate, EvaluateError};
 use crate::interpreter::value::Value;
 use crate::interpreter::{EvaluateContext, MultipleValue};
-use crate::renderer::Renderer;
-
-const MAXIMUM_OUTPUTS: usize = 16;
+use crate::renderer::{RenderGraph, Renderer};
 
+/// Values output by the nodes of a graph, stored one after the other. Each node gets a slot
+/// per output of its metadata, empty until its evaluator writes it.
 #[derive(Clone)]
-pub(crate) struct Outputs(Box<[Value; MAXIMUM_OUTPUTS]>);
+pub(crate) struct OutputStorage {
+    values: Vec<Option<Value>>,
+    /// Index of the first slot of each node, followed by the number of slots
+    offsets: Vec<usize>,
+}
+
+impl OutputStorage {
+    pub fn new(graph: &Graph) -> Self {
+        let mut offsets = Vec::with_capacity(graph.nodes.len() + 1);
+        offsets.push(0);
 
-impl Outputs {
-    pub fn new() -> Self {
-        /// SAFETY: Considering enums are stored sequentially in memory as (variant, union(values)),
-        /// by creating a zeroed memory location the default variant will be the first one, which is
-        /// [`Value::Scalar`], which internally is an f32. A float with all it's bits set to zero
-        /// represents a 0.0f32, which is also a valid value.
-        let outputs: Box<[Value; MAXIMUM_OUTPUTS]> = unsafe { Box::new_zeroed().assume_init() };
+        for node in graph.nodes.iter() {
+            let outputs = node.describe().outputs.len();
 
-        Self(outputs)
+            offsets.push(offsets.last().unwrap() + outputs);
+        }
+
+        Self {
+            values: vec![None; *offsets.last().unwrap()],
+            offsets,
+        }
     }
 
-    #[inline]
-    pub fn write_output(&mut self, port_id: PortId, value: Value) {
-        let value_ref = self.0.get_mut(port_id.0).expect("port out of range");
-        *value_ref = value
+    pub fn get_output(&self, node_id: NodeId, port_id: PortId) -> Result<Value, EvaluateError> {
+        self.get_node_outputs(node_id)
+            .get(port_id.0)
+            .cloned()
+            .flatten()
+            .ok_or(EvaluateError::MissingOutput(NodePortId(node_id, port_id)))
     }
 
-    #[inline]
-    pub fn get_output(&self, port_id: PortId) -> Value {
-        self.0.get(port_id.0).expect("port out of range").clone()
+    /// Writes an output of a node, which must be part of the node's metadata.
+    pub fn write_output(
+        &mut self,
+        node_id: NodeId,
+        port_id: PortId,
+        value: Value,
+    ) -> Result<(), EvaluateError> {
+        let slot = self
+            .get_node_outputs_mut(node_id)
+            .get_mut(port_id.0)
+            .ok_or(EvaluateError::MissingOutput(NodePortId(node_id, port_id)))?;
+
+        *slot = Some(value);
+
+        Ok(())
     }
-}
 
-#[derive(Clone)]
-pub(crate) struct OutputStorage(Vec<Outputs>);
+    /// Empties the outputs of a node before it evaluates, so the ports it no longer writes
+    /// don't keep the values of previous evaluations.
+    pub fn clear_outputs(&mut self, node_id: NodeId) {
+        self.get_node_outputs_mut(node_id).fill(None);
+    }
 
-impl OutputStorage {
-    pub fn new(nodes_count: usize) -> Self {
-        Self(vec![Outputs::new(); nodes_count])
+    fn get_node_outputs(&self, node_id: NodeId) -> &[Option<Value>] {
+        let range = self.offsets[node_id.0]..self.offsets[node_id.0 + 1];
+
+        &self.values[range]
     }
 
-    pub fn get_output(&self, node_id: NodeId, port_id: PortId) -> Value {
-        self.get_node_outputs(node_id).get_output(port_id)
+    fn get_node_outputs_mut(&mut self, node_id: NodeId) -> &mut [Option<Value>] {
+        let range = self.offsets[node_id.0]..self.offsets[node_id.0 + 1];
+
+        &mut self.values[range]
+    }
+}
+
+/// Iteration of a `Repeat` operator being evaluated.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Iteration {
+    pub index: u32,
+    pub count: u32,
+}
+
+/// Evaluators of the nodes evaluated in an iteration of a repeat.
+struct IterationEvaluators {
+    evaluators: Vec<RefCell<Option<Box<dyn Evaluate>>>>,
+    /// Last frame the iteration was evaluated in
+    frame: Cell<u64>,
+}
+
+/// Output storage and evaluators of a graph: the demo's graph, or the subgraph of a group.
+///
+/// Evaluating a node evaluates the nodes it depends on, so each evaluator is borrowed on its
+/// own while it runs and the storage only for as long as a value is read or written.
+pub(crate) struct GraphState {
+    storage: RefCell<OutputStorage>,
+    evaluators: Vec<RefCell<Box<dyn Evaluate>>>,
+    /// Evaluators of the nodes evaluated inside repeats, by iteration path from the outermost
+    /// repeat, so stateful operators keep a separate state for each copy.
+    iteration_evaluators: RefCell<HashMap<Vec<Iteration>, Rc<IterationEvaluators>>>,
+    /// Last frame the graph was evaluated in
+    frame: Cell<u64>,
+}
+
+impl GraphState {
+    pub(crate) fn new(graph: &Graph) -> Self {
+        Self {
+            storage: RefCell::new(OutputStorage::new(graph)),
+            evaluators: graph
+                .nodes
+                .iter()
+                .map(|n| RefCell::new(n.operator.to_evaluator()))
+                .collect(),
+            iteration_evaluators: RefCell::new(HashMap::new()),
+            frame: Cell::new(0),
+        }
     }
 
-    pub fn write_output(&mut self, node_id: NodeId, port_id: PortId, value: Value) {
-        self.get_node_outputs_mut(node_id)
-            .write_output(port_id, value)
+    /// Starts evaluating the graph in `frame`. The first time in a frame, drops the state of
+    /// the iterations that weren't evaluated the last time the graph was, such as the ones
+    /// past the count of a repeat since then.
+    fn begin_frame(&self, frame: u64) {
+        let last_frame = self.frame.replace(frame);
+
+        if last_frame != frame {
+            self.iteration_evaluators
+                .borrow_mut()
+                .retain(|_, iteration| iteration.frame.get() == last_frame);
+        }
     }
 
-    fn get_node_outputs(&self, node_id: NodeId) -> &Outputs {
-        self.0.get(node_id.0).expect("node out of range")
+    /// Evaluators of the nodes evaluated in the iteration `iterations`, created the first time
+    /// it is evaluated.
+    fn iteration_evaluators(&self, iterations: &[Iteration]) -> Rc<IterationEvaluators> {
+        let mut iteration_evaluators = self.iteration_evaluators.borrow_mut();
+
+        let iteration = iteration_evaluators
+            .entry(iterations.to_vec())
+            .or_insert_with(|| {
+                Rc::new(IterationEvaluators {
+                    evaluators: self.evaluators.iter().map(|_| RefCell::new(None)).collect(),
+                    frame: Cell::new(self.frame.get()),
+                })
+            });
+        iteration.frame.set(self.frame.get());
+
+        iteration.clone()
     }
+}
+
+/// Graph being evaluated, with the state it writes to.
+#[derive(Copy, Clone)]
+pub(crate) struct Scope<'s> {
+    graph: &'s Graph,
+    state: &'s GraphState,
+    /// Scope and node of the group evaluating this graph, `None` for the demo's graph
+    parent: Option<(&'s Scope<'s>, NodeId)>,
+}
 
-    fn get_node_outputs_mut(&mut self, node_id: NodeId) -> &mut Outputs {
-        self.0.get_mut(node_id.0).expect("node out of range")
+impl<'s> Scope<'s> {
+    #[inline]
+    pub(crate) fn parent(&self) -> Option<(&'s Scope<'s>, NodeId)> {
+        self.parent
     }
 }
 
+/// State of the frame being evaluated, shared by every node.
 pub(crate) struct InterpreterState {
-    storage: OutputStorage,
-    evaluators: Vec<Box<dyn Evaluate>>,
+    /// Number of frames run, counting the one being evaluated
+    frame: Cell<u64>,
+    /// Repeats being evaluated, from the outermost
+    iterations: RefCell<Vec<Iteration>>,
+    render_graph: RefCell<RenderGraph>,
 }
 
 pub struct Interpreter {
     demo: Demo,
-    state: UnsafeCell<InterpreterState>,
+    /// Decoded soundtrack of the demo, analyzed by audio operators
+    soundtrack: Option<AudioBuffer>,
+    graph_state: GraphState,
+    state: InterpreterState,
 }
 
 impl Default for Interpreter {
@@ -80,128 +196,305 @@ impl Default for Interpreter {
 pub struct InterpreterContext<'a> {
     pub renderer: &'a mut Renderer,
     pub render_target: &'a wgpu::TextureView,
+    pub render_target_size: cgmath::Vector2<u32>,
     pub frame: f32,
+    /// Demo clock, in seconds
+    pub time: f32,
 }
 
 impl Interpreter {
     pub fn new(demo: Demo) -> Self {
-        let nodes_count = demo.graph.nodes.len();
-        let evaluators = demo
-            .graph
-            .nodes
-            .iter()
-            .map(|n| n.operator.to_evaluator())
-            .collect();
+        let graph_state = GraphState::new(&demo.graph);
 
         Self {
             demo,
-            state: UnsafeCell::new(InterpreterState {
-                storage: OutputStorage::new(nodes_count),
-                evaluators,
-            }),
+            soundtrack: None,
+            graph_state,
+            state: InterpreterState {
+                frame: Cell::new(0),
+                iterations: RefCell::new(vec![]),
+                render_graph: Default::default(),
+            },
         }
     }
 
+    #[inline]
+    pub fn demo(&self) -> &Demo {
+        &self.demo
+    }
+
+    #[inline]
+    pub fn soundtrack(&self) -> Option<&AudioBuffer> {
+        self.soundtrack.as_ref()
+    }
+
+    /// Sets the decoded audio of the demo's soundtrack. Loading it is up to the caller, which
+    /// knows where the project lives.
+    pub fn set_soundtrack(&mut self, soundtrack: Option<AudioBuffer>) {
+        self.soundtrack = soundtrack;
+    }
+
+    /// Evaluates the demo and submits every render pass it scheduled at once.
     pub fn run(&self, context: &mut InterpreterContext) -> Result<(), EvaluateError> {
-        self.evaluate(context, NodeId::root())
+        self.state.render_graph.replace(RenderGraph::new());
+        self.state.frame.set(self.state.frame.get() + 1);
+
+        let scope = Scope {
+            graph: &self.demo.graph,
+            state: &self.graph_state,
+            parent: None,
+        };
+
+        self.evaluate(context, scope, NodeId::root())?;
+
+        let render_graph = self.state.render_graph.take();
+
+        context
+            .renderer
+            .execute(
+                render_graph,
+                context.render_target,
+                context.render_target_size,
+            )
+            .map_err(EvaluateError::RenderGraph)
     }
 
     pub(crate) fn evaluate(
         &self,
         context: &mut InterpreterContext,
+        scope: Scope,
         node_id: NodeId,
     ) -> Result<(), EvaluateError> {
-        let state = self.state_mut();
+        let state = scope.state;
+
+        state.begin_frame(self.state.frame.get());
+
+        // Inside repeats, the evaluators of the current iteration.
+        let iteration = {
+            let iterations = self.state.iterations.borrow();
+
+            (!iterations.is_empty()).then(|| state.iteration_evaluators(&iterations))
+        };
+
+        state.storage.borrow_mut().clear_outputs(node_id);
 
         let mut evaluate_context = EvaluateContext {
             node_id,
             interpreter: self,
             interpreter_context: context,
+            scope,
         };
 
-        state
-            .evaluators
-            .get_mut(node_id.0)
-            .unwrap()
-            .evaluate(&mut evaluate_context)
+        // A node already being evaluated depends on itself.
+        if let Some(iteration) = iteration {
+            let mut evaluator = iteration.evaluators[node_id.0]
+                .try_borrow_mut()
+                .map_err(|_| EvaluateError::GenericError)?;
+
+            evaluator
+                .get_or_insert_with(|| {
+                    let node = scope.graph.get_node(node_id).unwrap();
+                    node.operator.to_evaluator()
+                })
+                .evaluate(&mut evaluate_context)
+        } else {
+            state.evaluators[node_id.0]
+                .try_borrow_mut()
+                .map_err(|_| EvaluateError::GenericError)?
+                .evaluate(&mut evaluate_context)
+        }
     }
 
-    pub(crate) fn write_output(&self, node_id: NodeId, port_id: PortId, value: Value) {
-        self.state_mut()
+    /// Evaluates an input of the root node of `graph`, the subgraph of the group `node_id` of
+    /// `parent`. `state` keeps the outputs and evaluators of the subgraph between frames.
+    pub(crate) fn evaluate_group_output(
+        &self,
+        context: &mut InterpreterContext,
+        parent: &Scope,
+        node_id: NodeId,
+        graph: &Graph,
+        state: &GraphState,
+        port_id: PortId,
+    ) -> Result<Value, EvaluateError> {
+        let scope = Scope {
+            graph,
+            state,
+            parent: Some((parent, node_id)),
+        };
+
+        self.evaluate_input(context, scope, NodeId::root(), port_id)
+    }
+
+    /// Evaluates `f` as an iteration of a repeat, nested in the repeats being evaluated.
+    pub(crate) fn repeat<T>(&self, iteration: Iteration, f: impl FnOnce() -> T) -> T {
+        self.state.iterations.borrow_mut().push(iteration);
+        let result = f();
+        self.state.iterations.borrow_mut().pop();
+
+        result
+    }
+
+    /// Iteration of the repeat `level` levels out from the innermost one being evaluated.
+    pub(crate) fn iteration(&self, level: usize) -> Option<Iteration> {
+        self.state
+            .iterations
+            .borrow()
+            .iter()
+            .rev()
+            .nth(level)
+            .copied()
+    }
+
+    pub(crate) fn write_output(
+        &self,
+        scope: Scope,
+        node_id: NodeId,
+        port_id: PortId,
+        value: Value,
+    ) -> Result<(), EvaluateError> {
+        scope
+            .state
             .storage
+            .borrow_mut()
             .write_output(node_id, port_id, value)
     }
 
     pub(crate) fn evaluate_input(
         &self,
         context: &mut InterpreterContext,
+        scope: Scope,
         node_id: NodeId,
         port_id: PortId,
     ) -> Result<Value, EvaluateError> {
-        let node = self.demo.graph.get_node(node_id).unwrap();
+        let node = scope.graph.get_node(node_id).unwrap();
         let input_state = node.get_input_state(port_id).unwrap();
 
         match input_state {
             InputState::Constant(c) => match c {
                 Constant::Scalar(c) => Ok(Value::Scalar(*c)),
-                Constant::I32(_) => todo!(),
+                Constant::Int(i) => Ok(Value::Int(*i)),
+                Constant::Bool(b) => Ok(Value::Bool(*b)),
+                Constant::String(s) => Ok(Value::String(s.clone())),
+                Constant::Vector2(c) => Ok(Value::Vector2(*c)),
                 Constant::Vector(c) => Ok(Value::Vector3(*c)),
+                Constant::Vector4(c) => Ok(Value::Vector4(*c)),
+                Constant::Color(c) => Ok(Value::Color(*c)),
+                Constant::ScalarCurve(curve) => Ok(Value::ScalarCurve(curve.clone())),
+                Constant::VectorCurve(curve) => Ok(Value::VectorCurve(curve.clone())),
+                Constant::Timeline(timeline) => Ok(Value::Timeline(timeline.clone())),
+                Constant::Expression(expression) => Ok(Value::Expression(expression.clone())),
+                Constant::Group(subgraph) => Ok(Value::Group(subgraph.clone())),
+                Constant::GroupPorts(ports) => Ok(Value::GroupPorts(ports.clone())),
+                Constant::Enum(name) => Ok(Value::Enum(name.clone())),
             },
             InputState::Connection(cs) => {
-                let connection = cs.first().ok_or_else(|| EvaluateError::GenericError)?;
+                let connection = cs
+                    .first()
+                    .ok_or(EvaluateError::MissingConnection(NodePortId(
+                        node_id, port_id,
+                    )))?;
 
-                self.evaluate(context, connection.get_node_id())?;
+                self.evaluate_connection(context, scope, connection)
+            }
+        }
+    }
+
+    /// Number of values a multiple input receives.
+    pub(crate) fn input_len(&self, scope: Scope, node_id: NodeId, port_id: PortId) -> usize {
+        let node = scope.graph.get_node(node_id).unwrap();
 
-                let value = self
-                    .state_mut()
-                    .storage
-                    .get_output(connection.get_node_id(), connection.get_port_id());
+        match node.get_input_state(port_id).unwrap() {
+            InputState::Constant(_) => 1,
+            InputState::Connection(cs) => cs.len(),
+        }
+    }
+
+    /// Evaluates a single connection of a multiple input.
+    pub(crate) fn evaluate_input_at(
+        &self,
+        context: &mut InterpreterContext,
+        scope: Scope,
+        node_id: NodeId,
+        port_id: PortId,
+        index: usize,
+    ) -> Result<Value, EvaluateError> {
+        let node = scope.graph.get_node(node_id).unwrap();
+        let input_state = node.get_input_state(port_id).unwrap();
+
+        match input_state {
+            InputState::Constant(_) if index == 0 => {
+                self.evaluate_input(context, scope, node_id, port_id)
+            }
+            InputState::Constant(_) => Err(EvaluateError::GenericError),
+            InputState::Connection(cs) => {
+                let connection = cs.get(index).ok_or(EvaluateError::GenericError)?;
 
-                Ok(value)
+                self.evaluate_connection(context, scope, connection)
             }
         }
     }
 
+    fn evaluate_connection(
+        &self,
+        context: &mut InterpreterContext,
+        scope: Scope,
+        connection: &NodePortId,
+    ) -> Result<Value, EvaluateError> {
+        self.evaluate(context, scope, connection.get_node_id())?;
+
+        scope
+            .state
+            .storage
+            .borrow()
+            .get_output(connection.get_node_id(), connection.get_port_id())
+    }
+
     pub(crate) fn evaluate_input_multiple(
         &self,
         context: &mut InterpreterContext,
+        scope: Scope,
         node_id: NodeId,
         port_id: PortId,
     ) -> Result<MultipleValue, EvaluateError> {
-        let node = self.demo.graph.get_node(node_id).unwrap();
+        let node = scope.graph.get_node(node_id).unwrap();
         let input_state = node.get_input_state(port_id).unwrap();
 
         match input_state {
-            InputState::Constant(c) => Err(EvaluateError::GenericError),
+            // An unconnected input holding a constant behaves as a single connection.
+            InputState::Constant(_) => Ok(MultipleValue {
+                values: vec![self.evaluate_input(context, scope, node_id, port_id)?],
+            }),
             InputState::Connection(cs) => {
                 for c in cs {
-                    self.evaluate(context, c.get_node_id())?;
+                    self.evaluate(context, scope, c.get_node_id())?;
                 }
 
                 let values = cs
                     .iter()
                     .map(|output| {
-                        self.state_mut()
+                        scope
+                            .state
                             .storage
+                            .borrow()
                             .get_output(output.get_node_id(), output.get_port_id())
                     })
-                    .collect();
+                    .collect::<Result<_, _>>()?;
 
                 Ok(MultipleValue { values })
             }
         }
     }
 
-    fn state_mut(&self) -> &mut InterpreterState {
-        /// SAFETY: The interpreter is largely immutable.
-        /// The only places where mutation is possible is inside each Evaluator
-        /// and inside the OutputStorage, which none break the interpreter.
-        unsafe {
-            &mut *self.state.get()
-        }
+    /// The render graph of the frame. Borrowed until the returned guard is dropped, so passes
+    /// must be added before evaluating other nodes.
+    #[inline]
+    pub(crate) fn render_graph(&self) -> RefMut<'_, RenderGraph> {
+        self.state.render_graph.borrow_mut()
     }
 }
 
+// SAFETY: The interpreter isn't `Sync`, its state lives in cells. The editor keeps it behind
+// an `Arc<Mutex<_>>` for egui's paint callbacks, which must be `Send`, but they run on the
+// thread of the UI building the demo, so the `Rc`s the interpreter shares with the editor's
+// copy of the demo are never used from two threads.
 unsafe impl Send for Interpreter {}
-
-unsafe impl Sync for Interpreter {}
@@ -1,25 +1,66 @@
 use std::marker::PhantomData;
 use std::rc::Rc;
 
-use cgmath::Vector3;
+use cgmath::{Vector2, Vector3, Vector4};
 use derive_more::From;
 
-use crate::graph::NodePortId;
-use crate::interpreter::{EvaluateError, InterpreterContext, InterpreterState};
+use crate::animation::{ScalarCurve, VectorCurve};
+use crate::color::Color;
+use crate::expression::Expression;
+use crate::graph::{GroupPort, OperatorEnum, PortId, Subgraph};
+use crate::interpreter::{EvaluateContext, EvaluateError, InterpreterContext, InterpreterState};
 use crate::renderer::{Command, CommandList, Mesh, Texture};
+use crate::sequencer::Timeline;
 
 pub(crate) enum Order {
     Single(Value),
     Multiple(Vec<Value>),
 }
 
+/// Value flowing through a connection. Operators convert their inputs with `TryInto` and
+/// their outputs with `From`, which convert between numbers like connections do.
 #[derive(Debug, Clone, From)]
-pub(crate) enum Value {
+pub enum Value {
     Scalar(f32),
+    Int(i32),
+    Bool(bool),
+    String(String),
+    Vector2(Vector2<f32>),
     Vector3(Vector3<f32>),
+    Vector4(Vector4<f32>),
+    Color(Color),
     Mesh(Rc<Mesh>),
     Texture(Rc<Texture>),
     CommandList(CommandList),
+    ScalarCurve(Rc<ScalarCurve>),
+    VectorCurve(Rc<VectorCurve>),
+    Timeline(Rc<Timeline>),
+    Expression(Rc<Expression>),
+    Group(Rc<Subgraph>),
+    GroupPorts(Rc<[GroupPort]>),
+    /// Name of the variant of an [`OperatorEnum`]
+    Enum(Rc<str>),
+}
+
+impl Value {
+    /// Converts to an enum from the name of its variant. Numbers pick the variant at their
+    /// index, the way enum inputs were set before having their own type.
+    pub fn to_enum<T: OperatorEnum>(&self) -> Result<T, EvaluateError> {
+        let variant = match self {
+            Value::Enum(name) => T::from_name(name),
+            Value::Int(index) => usize::try_from(*index).ok().and_then(T::from_index),
+            Value::Scalar(index) => usize::try_from(index.round() as i64)
+                .ok()
+                .and_then(T::from_index),
+            _ => None,
+        };
+
+        variant.ok_or(EvaluateError::GenericError)
+    }
+
+    pub fn from_enum<T: OperatorEnum>(value: T) -> Self {
+        Value::Enum(value.name().into())
+    }
 }
 
 impl TryInto<Rc<Mesh>> for Value {
@@ -33,6 +74,83 @@ impl TryInto<Rc<Mesh>> for Value {
     }
 }
 
+impl TryInto<Rc<Texture>> for Value {
+    type Error = EvaluateError;
+
+    fn try_into(self) -> Result<Rc<Texture>, Self::Error> {
+        match self {
+            Value::Texture(texture) => Ok(texture),
+            _ => Err(EvaluateError::GenericError),
+        }
+    }
+}
+
+impl TryInto<Rc<ScalarCurve>> for Value {
+    type Error = EvaluateError;
+
+    fn try_into(self) -> Result<Rc<ScalarCurve>, Self::Error> {
+        match self {
+            Value::ScalarCurve(curve) => Ok(curve),
+            _ => Err(EvaluateError::GenericError),
+        }
+    }
+}
+
+impl TryInto<Rc<VectorCurve>> for Value {
+    type Error = EvaluateError;
+
+    fn try_into(self) -> Result<Rc<VectorCurve>, Self::Error> {
+        match self {
+            Value::VectorCurve(curve) => Ok(curve),
+            _ => Err(EvaluateError::GenericError),
+        }
+    }
+}
+
+impl TryInto<Rc<Timeline>> for Value {
+    type Error = EvaluateError;
+
+    fn try_into(self) -> Result<Rc<Timeline>, Self::Error> {
+        match self {
+            Value::Timeline(timeline) => Ok(timeline),
+            _ => Err(EvaluateError::GenericError),
+        }
+    }
+}
+
+impl TryInto<Rc<Expression>> for Value {
+    type Error = EvaluateError;
+
+    fn try_into(self) -> Result<Rc<Expression>, Self::Error> {
+        match self {
+            Value::Expression(expression) => Ok(expression),
+            _ => Err(EvaluateError::GenericError),
+        }
+    }
+}
+
+impl TryInto<Rc<Subgraph>> for Value {
+    type Error = EvaluateError;
+
+    fn try_into(self) -> Result<Rc<Subgraph>, Self::Error> {
+        match self {
+            Value::Group(subgraph) => Ok(subgraph),
+            _ => Err(EvaluateError::GenericError),
+        }
+    }
+}
+
+impl TryInto<Rc<[GroupPort]>> for Value {
+    type Error = EvaluateError;
+
+    fn try_into(self) -> Result<Rc<[GroupPort]>, Self::Error> {
+        match self {
+            Value::GroupPorts(ports) => Ok(ports),
+            _ => Err(EvaluateError::GenericError),
+        }
+    }
+}
+
 impl TryInto<CommandList> for Value {
     type Error = EvaluateError;
 
@@ -51,6 +169,77 @@ impl TryInto<f32> for Value {
     fn try_into(self) -> Result<f32, Self::Error> {
         match self {
             Value::Scalar(c) => Ok(c),
+            Value::Int(i) => Ok(i as f32),
+            Value::Bool(b) => Ok(b as i32 as f32),
+            _ => Err(EvaluateError::GenericError),
+        }
+    }
+}
+
+impl TryInto<i32> for Value {
+    type Error = EvaluateError;
+
+    fn try_into(self) -> Result<i32, Self::Error> {
+        match self {
+            Value::Int(i) => Ok(i),
+            Value::Bool(b) => Ok(b as i32),
+            Value::Scalar(c) => Ok(c.round() as i32),
+            _ => Err(EvaluateError::GenericError),
+        }
+    }
+}
+
+impl TryInto<bool> for Value {
+    type Error = EvaluateError;
+
+    fn try_into(self) -> Result<bool, Self::Error> {
+        match self {
+            Value::Bool(b) => Ok(b),
+            Value::Int(i) => Ok(i != 0),
+            _ => Err(EvaluateError::GenericError),
+        }
+    }
+}
+
+impl TryInto<String> for Value {
+    type Error = EvaluateError;
+
+    fn try_into(self) -> Result<String, Self::Error> {
+        match self {
+            Value::String(s) => Ok(s),
+            _ => Err(EvaluateError::GenericError),
+        }
+    }
+}
+
+impl TryInto<Vector2<f32>> for Value {
+    type Error = EvaluateError;
+
+    fn try_into(self) -> Result<Vector2<f32>, Self::Error> {
+        match self {
+            Value::Vector2(v) => Ok(v),
+            _ => Err(EvaluateError::GenericError),
+        }
+    }
+}
+
+impl TryInto<Vector4<f32>> for Value {
+    type Error = EvaluateError;
+
+    fn try_into(self) -> Result<Vector4<f32>, Self::Error> {
+        match self {
+            Value::Vector4(v) => Ok(v),
+            _ => Err(EvaluateError::GenericError),
+        }
+    }
+}
+
+impl TryInto<Color> for Value {
+    type Error = EvaluateError;
+
+    fn try_into(self) -> Result<Color, Self::Error> {
+        match self {
+            Value::Color(c) => Ok(c),
             _ => Err(EvaluateError::GenericError),
         }
     }
@@ -88,13 +277,63 @@ pub struct Multiple<V> {
     pub(crate) marker: PhantomData<V>,
 }
 
-// impl<T> Multiple<T>
-// where
-//     Value: TryInto<T>,
-// {
-//     pub fn into_iter(self) -> impl Iterator<Item = T> {
-//         self.values
-//             .into_iter()
-//             .flat_map(|v| -> Option<T> { v.try_into().ok() })
-//     }
-// }
+impl<T> Multiple<T>
+where
+    Value: TryInto<T>,
+{
+    /// Iterates over the connected values, skipping the ones that can't be converted.
+    pub fn into_values(self) -> impl Iterator<Item = T> {
+        self.values.into_iter().filter_map(|v| v.try_into().ok())
+    }
+}
+
+/// Input evaluated only when the operator asks for it, so the subgraph connected to it costs
+/// nothing on frames it isn't used.
+pub struct Lazy<V> {
+    pub(crate) port_id: PortId,
+    pub(crate) connected: bool,
+    pub(crate) marker: PhantomData<V>,
+}
+
+impl<T> Lazy<T>
+where
+    Value: TryInto<T, Error = EvaluateError>,
+{
+    /// Whether the input holds a constant or a connection, without which evaluating it fails.
+    #[inline]
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    pub fn evaluate(&self, ctx: &mut EvaluateContext) -> Result<T, EvaluateError> {
+        ctx.evaluate_input(self.port_id)?.try_into()
+    }
+}
+
+/// Input with multiple connections, each evaluated only when the operator asks for it.
+pub struct LazyMultiple<V> {
+    pub(crate) port_id: PortId,
+    pub(crate) len: usize,
+    pub(crate) marker: PhantomData<V>,
+}
+
+impl<T> LazyMultiple<T>
+where
+    Value: TryInto<T, Error = EvaluateError>,
+{
+    /// Number of connected values, or one for an unconnected input holding a constant.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Evaluates the connection at `index`.
+    pub fn evaluate(&self, ctx: &mut EvaluateContext, index: usize) -> Result<T, EvaluateError> {
+        ctx.evaluate_input_at(self.port_id, index)?.try_into()
+    }
+}
@@ -1,12 +1,22 @@
-#![feature(new_uninit)]
 extern crate core;
+// Lets `#[evaluator]`, which refers to `::tidal_core`, be used inside this crate.
+extern crate self as tidal_core;
 
 // Re-exports
 pub use cgmath;
+pub use inventory;
+pub use tidal_core_derive::evaluator;
 
+pub mod animation;
+pub mod audio;
+pub mod color;
 pub mod demo;
 pub mod engine;
+pub mod expression;
 pub mod graph;
 pub mod interpreter;
+pub mod noise;
 pub mod operator;
+pub mod random;
 pub mod renderer;
+pub mod sequencer;
@@ -0,0 +1,282 @@
+//! Seeded gradient, value and cellular noise in one to four dimensions.
+//!
+//! Lattice points are hashed instead of looked up in permutation tables, so any `u32` is a
+//! valid seed and `noise.wgsl` computes the same values on the GPU.
+
+/// Kind of noise to sample.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum NoiseKind {
+    /// Gradient noise on a hypercube lattice, in `-1..1`
+    #[default]
+    Perlin,
+    /// Gradient noise on a simplex lattice, in `-1..1`, with fewer directional artifacts
+    Simplex,
+    /// Random values interpolated on a hypercube lattice, in `-1..1`
+    Value,
+    /// Distance to the closest of randomly scattered points, in `0..1`
+    Worley,
+}
+
+impl NoiseKind {
+    #[inline]
+    pub(crate) fn index(&self) -> u32 {
+        match self {
+            NoiseKind::Perlin => 0,
+            NoiseKind::Simplex => 1,
+            NoiseKind::Value => 2,
+            NoiseKind::Worley => 3,
+        }
+    }
+}
+
+/// Noise summed over octaves of increasing frequency (fractional Brownian motion).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Fbm {
+    pub kind: NoiseKind,
+    pub seed: u32,
+    /// Frequency of the first octave, in cycles per unit
+    pub frequency: f32,
+    /// Number of octaves. One samples the noise alone.
+    pub octaves: u32,
+    /// Frequency multiplier between octaves
+    pub lacunarity: f32,
+    /// Amplitude multiplier between octaves
+    pub gain: f32,
+}
+
+impl Default for Fbm {
+    fn default() -> Self {
+        Self {
+            kind: NoiseKind::Perlin,
+            seed: 0,
+            frequency: 1.0,
+            octaves: 1,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+}
+
+/// Most octaves summed, past which details are smaller than a pixel anyway.
+pub const MAX_OCTAVES: u32 = 16;
+
+/// Offsets the seed of each channel of vector noise so the channels are decorrelated.
+const CHANNEL_SEED_STEP: u32 = 0x9E37_79B9;
+
+impl Fbm {
+    /// Samples the noise at `point`. The result stays in the range of a single octave.
+    pub fn sample<const N: usize>(&self, point: [f32; N]) -> f32 {
+        let octaves = self.octaves.clamp(1, MAX_OCTAVES);
+
+        let mut sum = 0.0;
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = self.frequency;
+
+        for octave in 0..octaves {
+            let seed = self.seed.wrapping_add(octave);
+
+            sum += amplitude * sample(self.kind, point.map(|x| x * frequency), seed);
+            total += amplitude;
+
+            amplitude *= self.gain;
+            frequency *= self.lacunarity;
+        }
+
+        if total > 0.0 {
+            sum / total
+        } else {
+            0.0
+        }
+    }
+
+    /// Samples three decorrelated channels at `point`. The first one equals [`Fbm::sample`].
+    pub fn sample_vector<const N: usize>(&self, point: [f32; N]) -> [f32; 3] {
+        [0u32, 1, 2].map(|channel| {
+            Fbm {
+                seed: self
+                    .seed
+                    .wrapping_add(channel.wrapping_mul(CHANNEL_SEED_STEP)),
+                ..*self
+            }
+            .sample(point)
+        })
+    }
+}
+
+/// Samples a single octave of noise.
+pub fn sample<const N: usize>(kind: NoiseKind, point: [f32; N], seed: u32) -> f32 {
+    match kind {
+        NoiseKind::Perlin => perlin(point, seed),
+        NoiseKind::Simplex => simplex(point, seed),
+        NoiseKind::Value => value(point, seed),
+        NoiseKind::Worley => worley(point, seed),
+    }
+}
+
+/// Integer hash with good avalanche (PCG output permutation).
+#[inline]
+pub(crate) fn hash(x: u32) -> u32 {
+    let state = x.wrapping_mul(747_796_405).wrapping_add(2_891_336_453);
+    let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277_803_737);
+
+    (word >> 22) ^ word
+}
+
+#[inline]
+fn hash_cell<const N: usize>(cell: [i32; N], seed: u32) -> u32 {
+    cell.iter().fold(hash(seed), |h, c| hash(h ^ *c as u32))
+}
+
+/// Maps a hash to `0..1`.
+#[inline]
+fn unit(h: u32) -> f32 {
+    (h >> 8) as f32 / (1 << 24) as f32
+}
+
+/// Random point in the `-1..1` hypercube, one hash per component.
+#[inline]
+fn random_point<const N: usize>(h: u32) -> [f32; N] {
+    std::array::from_fn(|i| unit(hash(h.wrapping_add(i as u32))) * 2.0 - 1.0)
+}
+
+#[inline]
+fn gradient<const N: usize>(cell: [i32; N], seed: u32) -> [f32; N] {
+    let g: [f32; N] = random_point(hash_cell(cell, seed));
+    let length = dot(g, g).sqrt();
+
+    if length > 0.0 {
+        g.map(|x| x / length)
+    } else {
+        g
+    }
+}
+
+#[inline]
+fn dot<const N: usize>(a: [f32; N], b: [f32; N]) -> f32 {
+    a.iter().zip(b.iter()).map(|(a, b)| a * b).sum()
+}
+
+/// Quintic fade, with zero first and second derivatives at 0 and 1.
+#[inline]
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+/// Interpolates values at the corners of the lattice cell containing `point`. `corner` returns
+/// the value of a corner given its cell and the offset of `point` from it.
+#[inline]
+fn interpolate_cell<const N: usize>(
+    point: [f32; N],
+    corner: impl Fn([i32; N], [f32; N]) -> f32,
+) -> f32 {
+    let base = point.map(|x| x.floor());
+    let local: [f32; N] = std::array::from_fn(|i| point[i] - base[i]);
+    let weights = local.map(fade);
+
+    let mut sum = 0.0;
+
+    for bits in 0..(1u32 << N) {
+        let mut weight = 1.0;
+        let mut cell = [0; N];
+        let mut offset = [0.0; N];
+
+        for i in 0..N {
+            let bit = (bits >> i) & 1;
+
+            cell[i] = base[i] as i32 + bit as i32;
+            offset[i] = local[i] - bit as f32;
+            weight *= if bit == 1 {
+                weights[i]
+            } else {
+                1.0 - weights[i]
+            };
+        }
+
+        sum += weight * corner(cell, offset);
+    }
+
+    sum
+}
+
+/// Scale bringing Perlin noise of each dimension to about `-1..1`.
+const PERLIN_SCALE: [f32; 4] = [2.0, 1.45, 1.55, 1.6];
+
+pub fn perlin<const N: usize>(point: [f32; N], seed: u32) -> f32 {
+    let value = interpolate_cell(point, |cell, offset| dot(gradient(cell, seed), offset));
+
+    (value * PERLIN_SCALE[N - 1]).clamp(-1.0, 1.0)
+}
+
+pub fn value<const N: usize>(point: [f32; N], seed: u32) -> f32 {
+    interpolate_cell(point, |cell, _| unit(hash_cell(cell, seed)) * 2.0 - 1.0)
+}
+
+/// Scale bringing simplex noise of each dimension to about `-1..1`.
+const SIMPLEX_SCALE: [f32; 4] = [72.0, 99.0, 42.0, 46.0];
+
+pub fn simplex<const N: usize>(point: [f32; N], seed: u32) -> f32 {
+    let n = N as f32;
+    let skew = ((n + 1.0).sqrt() - 1.0) / n;
+    let unskew = (1.0 - 1.0 / (n + 1.0).sqrt()) / n;
+
+    let s = point.iter().sum::<f32>() * skew;
+    let cell: [i32; N] = point.map(|x| (x + s).floor() as i32);
+    let t = cell.iter().sum::<i32>() as f32 * unskew;
+    let origin: [f32; N] = std::array::from_fn(|i| point[i] - (cell[i] as f32 - t));
+
+    // Walking the axes from the largest offset to the smallest visits the corners of the
+    // simplex containing the point.
+    let mut axes: [usize; N] = std::array::from_fn(|i| i);
+    axes.sort_by(|a, b| origin[*b].total_cmp(&origin[*a]));
+
+    let radius = if N <= 2 { 0.5 } else { 0.6 };
+
+    let mut corner = [0; N];
+    let mut sum = 0.0;
+
+    for k in 0..=N {
+        if k > 0 {
+            corner[axes[k - 1]] = 1;
+        }
+
+        let offset: [f32; N] =
+            std::array::from_fn(|i| origin[i] - corner[i] as f32 + k as f32 * unskew);
+        let falloff = radius - dot(offset, offset);
+
+        if falloff > 0.0 {
+            let cell: [i32; N] = std::array::from_fn(|i| cell[i] + corner[i]);
+            let falloff = falloff * falloff;
+
+            sum += falloff * falloff * dot(gradient(cell, seed), offset);
+        }
+    }
+
+    (sum * SIMPLEX_SCALE[N - 1]).clamp(-1.0, 1.0)
+}
+
+pub fn worley<const N: usize>(point: [f32; N], seed: u32) -> f32 {
+    let base = point.map(|x| x.floor() as i32);
+    let mut closest = f32::MAX;
+
+    for neighbour in 0..3u32.pow(N as u32) {
+        let mut digits = neighbour;
+        let cell: [i32; N] = std::array::from_fn(|i| {
+            let offset = (digits % 3) as i32 - 1;
+            digits /= 3;
+            base[i] + offset
+        });
+
+        let feature: [f32; N] = random_point(hash_cell(cell, seed));
+        let distance: f32 = (0..N)
+            .map(|i| {
+                let d = cell[i] as f32 + feature[i] * 0.5 + 0.5 - point[i];
+                d * d
+            })
+            .sum();
+
+        closest = closest.min(distance);
+    }
+
+    closest.sqrt().min(1.0)
+}
@@ -0,0 +1,219 @@
+use std::rc::Rc;
+
+use cgmath::Vector3;
+
+use tidal_core_derive::evaluator;
+
+use crate::animation::{damp, Easing, EasingMode, ScalarCurve, Spring, VectorCurve};
+use crate::interpreter::EvaluateContext;
+
+#[evaluator(AnimationCurveEvaluator for AnimationCurve)]
+#[category("Animation")]
+#[output(name = "value")]
+fn evaluate_animation_curve(
+    #[context] ctx: &mut EvaluateContext,
+    curve: Rc<ScalarCurve>,
+    #[default(1.0)] speed: f32,
+    #[default(0.0)] offset: f32,
+) -> f32 {
+    curve.sample(ctx.time() * speed + offset)
+}
+
+#[evaluator(VectorAnimationCurveEvaluator for VectorAnimationCurve)]
+#[category("Animation")]
+#[output(name = "vector")]
+fn evaluate_vector_animation_curve(
+    #[context] ctx: &mut EvaluateContext,
+    curve: Rc<VectorCurve>,
+    #[default(1.0)] speed: f32,
+    #[default(0.0)] offset: f32,
+) -> Vector3<f32> {
+    curve.sample(ctx.time() * speed + offset)
+}
+
+/// Longest gap between frames integrated by the stateful operators. Longer gaps, and the time
+/// going backwards, come from seeking and restart them at their target.
+const MAXIMUM_FRAME_GAP: f32 = 0.5;
+
+/// Tracks the demo time between frames for operators integrating over time.
+#[derive(Debug, Default)]
+struct FrameClock {
+    last_time: Option<f32>,
+}
+
+impl FrameClock {
+    /// Returns the time elapsed since the previous frame, or `None` if the operator must
+    /// restart.
+    fn tick(&mut self, time: f32) -> Option<f32> {
+        let delta_time = self.last_time.map(|last_time| time - last_time);
+        self.last_time = Some(time);
+
+        delta_time.filter(|delta_time| (0.0..=MAXIMUM_FRAME_GAP).contains(delta_time))
+    }
+}
+
+/// Progress of the demo clock from `start_time` to `end_time`, in `0..1`.
+fn progress(time: f32, start_time: f32, end_time: f32) -> f32 {
+    if end_time > start_time {
+        (time - start_time) / (end_time - start_time)
+    } else if time >= start_time {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Eases `t` in `0..1`.
+#[evaluator(EaseEvaluator for Ease)]
+#[category("Animation")]
+#[output(name = "value")]
+fn evaluate_ease(
+    #[default(0.0)] t: f32,
+    #[default(Cubic)] function: Easing,
+    #[default(InOut)] mode: EasingMode,
+) -> f32 {
+    function.ease(mode, t)
+}
+
+/// Goes from `from` to `to` between `start_time` and `end_time` of the demo clock, eased like
+/// [`Ease`].
+#[evaluator(TweenEvaluator for Tween)]
+#[category("Animation")]
+#[output(name = "value")]
+fn evaluate_tween(
+    #[context] ctx: &mut EvaluateContext,
+    #[default(0.0)] start_time: f32,
+    #[default(1.0)] end_time: f32,
+    #[default(0.0)] from: f32,
+    #[default(1.0)] to: f32,
+    #[default(Cubic)] function: Easing,
+    #[default(InOut)] mode: EasingMode,
+) -> f32 {
+    let t = progress(ctx.time(), start_time, end_time);
+    let t = function.ease(mode, t);
+
+    from + (to - from) * t
+}
+
+#[evaluator(VectorTweenEvaluator for VectorTween)]
+#[category("Animation")]
+#[output(name = "vector")]
+fn evaluate_vector_tween(
+    #[context] ctx: &mut EvaluateContext,
+    #[default(0.0)] start_time: f32,
+    #[default(1.0)] end_time: f32,
+    #[default(0.0, 0.0, 0.0)] from: Vector3<f32>,
+    #[default(1.0, 1.0, 1.0)] to: Vector3<f32>,
+    #[default(Cubic)] function: Easing,
+    #[default(InOut)] mode: EasingMode,
+) -> Vector3<f32> {
+    let t = progress(ctx.time(), start_time, end_time);
+    let t = function.ease(mode, t);
+
+    from + (to - from) * t
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct SpringEvaluator {
+    clock: FrameClock,
+    spring: Option<Spring<f32>>,
+}
+
+/// Follows `target` like a mass on a spring. `frequency` is how fast it oscillates, in hertz,
+/// and `damping` how quickly oscillations die out: 1 reaches the target without overshooting.
+#[evaluator(impl SpringEvaluator for Spring)]
+#[category("Animation")]
+#[output(name = "value")]
+#[output(name = "velocity")]
+fn evaluate_spring(
+    #[state] state: &mut SpringEvaluator,
+    #[context] ctx: &mut EvaluateContext,
+    #[default(0.0)] target: f32,
+    #[default(1.0)] frequency: f32,
+    #[default(0.5)] damping: f32,
+) -> (f32, f32) {
+    let spring = match (state.clock.tick(ctx.time()), &mut state.spring) {
+        (Some(delta_time), Some(spring)) => {
+            spring.step(target, frequency, damping, delta_time);
+            spring
+        }
+        (_, spring) => spring.insert(Spring::new(target)),
+    };
+
+    (spring.value, spring.velocity)
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct VectorSpringEvaluator {
+    clock: FrameClock,
+    spring: Option<Spring<Vector3<f32>>>,
+}
+
+#[evaluator(impl VectorSpringEvaluator for VectorSpring)]
+#[category("Animation")]
+#[output(name = "vector")]
+#[output(name = "velocity")]
+fn evaluate_vector_spring(
+    #[state] state: &mut VectorSpringEvaluator,
+    #[context] ctx: &mut EvaluateContext,
+    #[default(0.0, 0.0, 0.0)] target: Vector3<f32>,
+    #[default(1.0)] frequency: f32,
+    #[default(0.5)] damping: f32,
+) -> (Vector3<f32>, Vector3<f32>) {
+    let spring = match (state.clock.tick(ctx.time()), &mut state.spring) {
+        (Some(delta_time), Some(spring)) => {
+            spring.step(target, frequency, damping, delta_time);
+            spring
+        }
+        (_, spring) => spring.insert(Spring::new(target)),
+    };
+
+    (spring.value, spring.velocity)
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct DampEvaluator {
+    clock: FrameClock,
+    value: Option<f32>,
+}
+
+/// Smoothly follows `target`, halving the distance to it every `half_life` seconds.
+#[evaluator(impl DampEvaluator for Damp)]
+#[category("Animation")]
+#[output(name = "value")]
+fn evaluate_damp(
+    #[state] state: &mut DampEvaluator,
+    #[context] ctx: &mut EvaluateContext,
+    #[default(0.0)] target: f32,
+    #[default(0.25)] half_life: f32,
+) -> f32 {
+    let value = match (state.clock.tick(ctx.time()), state.value) {
+        (Some(delta_time), Some(value)) => damp(value, target, half_life, delta_time),
+        _ => target,
+    };
+
+    *state.value.insert(value)
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct VectorDampEvaluator {
+    clock: FrameClock,
+    value: Option<Vector3<f32>>,
+}
+
+#[evaluator(impl VectorDampEvaluator for VectorDamp)]
+#[category("Animation")]
+#[output(name = "vector")]
+fn evaluate_vector_damp(
+    #[state] state: &mut VectorDampEvaluator,
+    #[context] ctx: &mut EvaluateContext,
+    #[default(0.0, 0.0, 0.0)] target: Vector3<f32>,
+    #[default(0.25)] half_life: f32,
+) -> Vector3<f32> {
+    let value = match (state.clock.tick(ctx.time()), state.value) {
+        (Some(delta_time), Some(value)) => damp(value, target, half_life, delta_time),
+        _ => target,
+    };
+
+    *state.value.insert(value)
+}
@@ -0,0 +1,137 @@
+use tidal_core_derive::evaluator;
+
+use crate::audio::{Spectrum, SpectrumAnalyzer, SPECTRUM_WINDOW_SIZE};
+use crate::graph::{Constant, DataType, InputState, Metadata, OutputMetadata};
+use crate::interpreter::{EvaluateContext, EvaluateError};
+
+/// Number of previous analysis windows the current flux is compared against to detect onsets.
+const ONSET_HISTORY: usize = 8;
+
+/// Flux below which a band is considered silent, so noise in quiet parts isn't an onset.
+const MINIMUM_ONSET_FLUX: f32 = 1e-4;
+
+/// Bands of a `SpectrumBands` node when its count isn't known.
+const DEFAULT_BANDS: usize = 8;
+
+/// Most bands of a `SpectrumBands` node, which also has as many outputs when its count is
+/// connected.
+const MAXIMUM_BANDS: usize = 64;
+
+#[derive(Debug, Default)]
+pub(crate) struct AudioSpectrumEvaluator {
+    analyzer: Option<SpectrumAnalyzer>,
+}
+
+/// Energy of a frequency band of the soundtrack at the current time, its loudness and whether a
+/// note or hit starts in the band.
+#[evaluator(impl AudioSpectrumEvaluator for AudioSpectrum)]
+#[category("Audio")]
+#[output(name = "energy")]
+#[output(name = "rms")]
+#[output(name = "onset")]
+fn evaluate_audio_spectrum(
+    #[state] state: &mut AudioSpectrumEvaluator,
+    #[context] ctx: &mut EvaluateContext,
+    #[default(20.0)] low_frequency: f32,
+    #[default(20000.0)] high_frequency: f32,
+    #[default(1.5)] onset_threshold: f32,
+) -> (f32, f32, f32) {
+    let Some(buffer) = ctx.soundtrack() else {
+        return (0.0, 0.0, 0.0);
+    };
+
+    let analyzer = state.analyzer.get_or_insert_with(Default::default);
+    let time = ctx.time();
+
+    // Windows overlap by three quarters, the usual hop for onset detection.
+    let hop = SPECTRUM_WINDOW_SIZE as f32 / 4.0 / buffer.sample_rate() as f32;
+
+    // Spectra from the oldest window to the current one. Recomputed every frame rather than
+    // kept between frames, so the result only depends on the time.
+    let spectra: Vec<Spectrum> = (0..=ONSET_HISTORY + 1)
+        .rev()
+        .map(|i| analyzer.analyze(buffer, time - i as f32 * hop))
+        .collect();
+
+    let fluxes: Vec<f32> = spectra
+        .windows(2)
+        .map(|pair| pair[1].flux(&pair[0], low_frequency, high_frequency))
+        .collect();
+
+    let (flux, history) = fluxes.split_last().expect("at least one flux");
+    let average = history.iter().sum::<f32>() / history.len() as f32;
+    let onset = *flux > MINIMUM_ONSET_FLUX && *flux > average * onset_threshold;
+
+    let current = spectra.last().expect("at least one spectrum");
+
+    (
+        current.band_energy(low_frequency, high_frequency),
+        current.rms(),
+        onset as u32 as f32,
+    )
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct SpectrumBandsEvaluator {
+    analyzer: Option<SpectrumAnalyzer>,
+}
+
+/// Energy of the soundtrack at the current time in `bands` frequency bands, spread evenly on a
+/// logarithmic scale from `low_frequency` to `high_frequency`. Each band has its own output.
+#[evaluator(impl SpectrumBandsEvaluator for SpectrumBands)]
+#[category("Audio")]
+#[describe(describe_spectrum_bands)]
+#[fallible]
+fn evaluate_spectrum_bands(
+    #[state] state: &mut SpectrumBandsEvaluator,
+    #[context] ctx: &mut EvaluateContext,
+    /// Number of bands, each adding an output
+    #[input(min = 1, max = 64, step = 1)]
+    #[default(8)]
+    bands: i32,
+    #[default(20.0)] low_frequency: f32,
+    #[default(20000.0)] high_frequency: f32,
+) -> Result<(), EvaluateError> {
+    let bands = band_count(bands);
+
+    let spectrum = ctx.soundtrack().map(|buffer| {
+        let analyzer = state.analyzer.get_or_insert_with(Default::default);
+
+        analyzer.analyze(buffer, ctx.time())
+    });
+
+    let low_frequency = low_frequency.max(1.0);
+    let ratio = high_frequency.max(low_frequency) / low_frequency;
+    let edge = |band: usize| low_frequency * ratio.powf(band as f32 / bands as f32);
+
+    for band in 0..bands {
+        let energy = spectrum.as_ref().map_or(0.0, |spectrum| {
+            spectrum.band_energy(edge(band), edge(band + 1))
+        });
+
+        ctx.write_output(band, energy.into())?;
+    }
+
+    Ok(())
+}
+
+fn band_count(bands: i32) -> usize {
+    bands.clamp(1, MAXIMUM_BANDS as i32) as usize
+}
+
+/// Adds an output per band. A connected count is only known while evaluating, so the node
+/// then exposes the most bands it can output.
+fn describe_spectrum_bands(metadata: &mut Metadata, inputs: &[InputState]) {
+    let bands = match inputs.first() {
+        Some(InputState::Constant(Constant::Int(bands))) => band_count(*bands),
+        Some(InputState::Connection(connections)) if !connections.is_empty() => MAXIMUM_BANDS,
+        _ => DEFAULT_BANDS,
+    };
+
+    metadata
+        .outputs
+        .extend((0..bands).map(|band| OutputMetadata {
+            name: format!("band {}", band + 1).into(),
+            data_type: DataType::Scalar,
+        }));
+}
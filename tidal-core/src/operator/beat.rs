@@ -0,0 +1,24 @@
+use tidal_core_derive::evaluator;
+
+use crate::interpreter::EvaluateContext;
+
+/// Position in the soundtrack's music at the current time, from the tempo of the demo.
+#[evaluator(BeatEvaluator for Beat)]
+#[category("Audio")]
+#[output(name = "bpm")]
+#[output(name = "beat")]
+#[output(name = "bar")]
+#[output(name = "beat_phase")]
+#[output(name = "bar_phase")]
+fn evaluate_beat(#[context] ctx: &mut EvaluateContext) -> (f32, f32, f32, f32, f32) {
+    let tempo = ctx.tempo();
+    let position = tempo.position(ctx.time());
+
+    (
+        tempo.bpm,
+        position.beat as f32,
+        position.bar as f32,
+        position.beat_phase,
+        position.bar_phase,
+    )
+}
@@ -0,0 +1,112 @@
+use std::rc::Rc;
+
+use crate::expression::{Expression, ExpressionType, ExpressionValue};
+use crate::graph::{
+    Constant, DataType, InputMetadata, InputState, Metadata, OutputMetadata, PortId,
+};
+use crate::interpreter::{Evaluate, EvaluateContext, EvaluateError, Value};
+use crate::operator::{
+    EvaluatorRegistryNode, NodeMetadataRegistryNode, OperatorMetadataRegistryNode,
+};
+
+/// Evaluates a formula. Its ports depend on the formula, so unlike most operators it is not
+/// declared with `#[evaluator]`: the `expression` input is followed by one input per free
+/// variable, see [`describe_expression`].
+#[derive(Debug, Default)]
+pub(crate) struct ExpressionEvaluator {
+    variables: Vec<ExpressionValue>,
+    stack: Vec<ExpressionValue>,
+}
+
+impl Evaluate for ExpressionEvaluator {
+    fn evaluate(&mut self, ctx: &mut EvaluateContext) -> Result<(), EvaluateError> {
+        let expression: Rc<Expression> = ctx.evaluate_input(0)?.try_into()?;
+
+        self.variables.clear();
+
+        for (i, variable) in expression.variables().iter().enumerate() {
+            let value = ctx.evaluate_input(i + 1)?;
+
+            self.variables.push(match variable.data_type {
+                ExpressionType::Scalar => ExpressionValue::Scalar(value.try_into()?),
+                ExpressionType::Vector => ExpressionValue::Vector(value.try_into()?),
+            });
+        }
+
+        let value = match expression.evaluate(&self.variables, &mut self.stack) {
+            ExpressionValue::Scalar(x) => Value::Scalar(x),
+            ExpressionValue::Vector(v) => Value::Vector3(v),
+        };
+
+        ctx.write_output(0, value)?;
+
+        Ok(())
+    }
+}
+
+/// Adds an input for each variable of the node's expression and sets the type of the result.
+pub(crate) fn describe_expression(metadata: &mut Metadata, inputs: &[InputState]) {
+    let Some(InputState::Constant(Constant::Expression(expression))) = inputs.first() else {
+        return;
+    };
+
+    metadata
+        .inputs
+        .extend(expression.variables().iter().map(|variable| InputMetadata {
+            name: variable.name.clone().into(),
+            description: None,
+            required: false,
+            multiple: false,
+            default: None,
+            data_type: variable.data_type.data_type(),
+            hint: Default::default(),
+            optional: false,
+            variants: &[],
+        }));
+
+    if let Some(output) = metadata.get_output_mut(PortId(0)) {
+        output.data_type = expression.result_type().data_type();
+    }
+}
+
+fn create_operator() -> Box<dyn Evaluate> {
+    Box::<ExpressionEvaluator>::default()
+}
+
+fn create_metadata() -> Metadata {
+    Metadata {
+        name: "Expression",
+        description: Some("Evaluates a formula, with an input for each of its variables"),
+        category: Some("Math"),
+        inputs: vec![InputMetadata {
+            name: "expression".into(),
+            description: Some("Formula, whose variables become inputs"),
+            required: false,
+            multiple: false,
+            default: None,
+            data_type: DataType::Expression,
+            hint: Default::default(),
+            optional: false,
+            variants: &[],
+        }],
+        outputs: vec![OutputMetadata {
+            name: "result".into(),
+            data_type: DataType::Scalar,
+        }],
+    }
+}
+
+inventory::submit!(EvaluatorRegistryNode {
+    operator: "Expression",
+    create_operator
+});
+
+inventory::submit!(OperatorMetadataRegistryNode {
+    operator: "Expression",
+    create_metadata
+});
+
+inventory::submit!(NodeMetadataRegistryNode {
+    operator: "Expression",
+    describe: describe_expression
+});
@@ -0,0 +1,246 @@
+use std::rc::Rc;
+
+use crate::graph::{
+    Constant, DataType, GroupPort, InputMetadata, InputState, Metadata, OutputMetadata, Subgraph,
+};
+use crate::interpreter::{Evaluate, EvaluateContext, EvaluateError, GraphState};
+use crate::operator::{
+    EvaluatorRegistryNode, NodeMetadataRegistryNode, OperatorMetadataRegistryNode,
+};
+
+/// Evaluates a subgraph. Like [`crate::operator::expression::ExpressionEvaluator`], its ports depend on
+/// its `group` input: one input per port of the subgraph's `GroupInput` node and one output
+/// per input of its `GroupOutput` root, see [`describe_group`].
+///
+/// The nodes of the subgraph write to their own output storage and keep their own evaluators,
+/// so every instance of a group has a separate state.
+#[derive(Default)]
+pub(crate) struct GroupEvaluator {
+    subgraph: Option<Rc<Subgraph>>,
+    state: Option<GraphState>,
+}
+
+impl Evaluate for GroupEvaluator {
+    fn evaluate(&mut self, ctx: &mut EvaluateContext) -> Result<(), EvaluateError> {
+        let subgraph: Rc<Subgraph> = ctx.evaluate_input(0)?.try_into()?;
+
+        // The graph changed, so the state of its nodes no longer applies.
+        if !self
+            .subgraph
+            .as_ref()
+            .is_some_and(|current| Rc::ptr_eq(current, &subgraph))
+        {
+            self.state = Some(GraphState::new(&subgraph.graph));
+            self.subgraph = Some(subgraph.clone());
+        }
+
+        let state = self.state.as_ref().unwrap();
+
+        for port in 0..subgraph.outputs().len() {
+            let value = ctx.evaluate_group_output(&subgraph.graph, state, port + 1)?;
+
+            ctx.write_output(port, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Outputs the inputs of the group node evaluating the subgraph.
+#[derive(Debug, Default)]
+pub(crate) struct GroupInputEvaluator;
+
+impl Evaluate for GroupInputEvaluator {
+    fn evaluate(&mut self, ctx: &mut EvaluateContext) -> Result<(), EvaluateError> {
+        let ports: Rc<[GroupPort]> = ctx.evaluate_input(0)?.try_into()?;
+
+        for port in 0..ports.len() {
+            let value = ctx.evaluate_group_input(port + 1)?;
+
+            ctx.write_output(port, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Root of a subgraph. Its inputs are read by the group node evaluating the subgraph, so it
+/// does nothing by itself.
+#[derive(Debug, Default)]
+pub(crate) struct GroupOutputEvaluator;
+
+impl Evaluate for GroupOutputEvaluator {
+    fn evaluate(&mut self, _ctx: &mut EvaluateContext) -> Result<(), EvaluateError> {
+        Ok(())
+    }
+}
+
+/// Adds an input for each input of the node's subgraph and an output for each of its outputs.
+pub(crate) fn describe_group(metadata: &mut Metadata, inputs: &[InputState]) {
+    let Some(InputState::Constant(Constant::Group(subgraph))) = inputs.first() else {
+        return;
+    };
+
+    metadata
+        .inputs
+        .extend(subgraph.inputs().iter().map(input_metadata));
+    metadata
+        .outputs
+        .extend(subgraph.outputs().iter().map(output_metadata));
+}
+
+/// Adds an output for each port declared by the node.
+pub(crate) fn describe_group_input(metadata: &mut Metadata, inputs: &[InputState]) {
+    let Some(InputState::Constant(Constant::GroupPorts(ports))) = inputs.first() else {
+        return;
+    };
+
+    metadata.outputs.extend(ports.iter().map(output_metadata));
+}
+
+/// Adds an input for each port declared by the node.
+pub(crate) fn describe_group_output(metadata: &mut Metadata, inputs: &[InputState]) {
+    let Some(InputState::Constant(Constant::GroupPorts(ports))) = inputs.first() else {
+        return;
+    };
+
+    metadata.inputs.extend(ports.iter().map(input_metadata));
+}
+
+fn input_metadata(port: &GroupPort) -> InputMetadata {
+    InputMetadata {
+        name: port.name.clone().into(),
+        description: None,
+        required: false,
+        multiple: false,
+        default: None,
+        data_type: port.data_type,
+        hint: Default::default(),
+        optional: false,
+        variants: &[],
+    }
+}
+
+fn output_metadata(port: &GroupPort) -> OutputMetadata {
+    OutputMetadata {
+        name: port.name.clone().into(),
+        data_type: port.data_type,
+    }
+}
+
+fn create_group_operator() -> Box<dyn Evaluate> {
+    Box::<GroupEvaluator>::default()
+}
+
+fn create_group_metadata() -> Metadata {
+    Metadata {
+        name: "Group",
+        description: Some("Evaluates a reusable subgraph"),
+        category: Some("Group"),
+        inputs: vec![InputMetadata {
+            name: "group".into(),
+            description: Some("Subgraph evaluated by the node"),
+            required: false,
+            multiple: false,
+            default: None,
+            data_type: DataType::Group,
+            hint: Default::default(),
+            optional: false,
+            variants: &[],
+        }],
+        outputs: vec![],
+    }
+}
+
+fn create_group_input_operator() -> Box<dyn Evaluate> {
+    Box::<GroupInputEvaluator>::default()
+}
+
+fn create_group_input_metadata() -> Metadata {
+    Metadata {
+        name: "GroupInput",
+        description: Some("Outputs the values received by the group"),
+        category: Some("Group"),
+        inputs: vec![InputMetadata {
+            name: "ports".into(),
+            description: Some("Name and type of each port of the group"),
+            required: false,
+            multiple: false,
+            default: None,
+            data_type: DataType::GroupPorts,
+            hint: Default::default(),
+            optional: false,
+            variants: &[],
+        }],
+        outputs: vec![],
+    }
+}
+
+fn create_group_output_operator() -> Box<dyn Evaluate> {
+    Box::<GroupOutputEvaluator>::default()
+}
+
+fn create_group_output_metadata() -> Metadata {
+    Metadata {
+        name: "GroupOutput",
+        description: Some("Receives the values output by the group"),
+        category: Some("Group"),
+        inputs: vec![InputMetadata {
+            name: "ports".into(),
+            description: Some("Name and type of each port of the group"),
+            required: false,
+            multiple: false,
+            default: None,
+            data_type: DataType::GroupPorts,
+            hint: Default::default(),
+            optional: false,
+            variants: &[],
+        }],
+        outputs: vec![],
+    }
+}
+
+inventory::submit!(EvaluatorRegistryNode {
+    operator: "Group",
+    create_operator: create_group_operator
+});
+
+inventory::submit!(OperatorMetadataRegistryNode {
+    operator: "Group",
+    create_metadata: create_group_metadata
+});
+
+inventory::submit!(NodeMetadataRegistryNode {
+    operator: "Group",
+    describe: describe_group
+});
+
+inventory::submit!(EvaluatorRegistryNode {
+    operator: "GroupInput",
+    create_operator: create_group_input_operator
+});
+
+inventory::submit!(OperatorMetadataRegistryNode {
+    operator: "GroupInput",
+    create_metadata: create_group_input_metadata
+});
+
+inventory::submit!(NodeMetadataRegistryNode {
+    operator: "GroupInput",
+    describe: describe_group_input
+});
+
+inventory::submit!(EvaluatorRegistryNode {
+    operator: "GroupOutput",
+    create_operator: create_group_output_operator
+});
+
+inventory::submit!(OperatorMetadataRegistryNode {
+    operator: "GroupOutput",
+    create_metadata: create_group_output_metadata
+});
+
+inventory::submit!(NodeMetadataRegistryNode {
+    operator: "GroupOutput",
+    describe: describe_group_output
+});
@@ -0,0 +1,184 @@
+use std::f32::consts::TAU;
+use std::rc::Rc;
+
+use cgmath::{InnerSpace, Matrix4, Rad, Vector3, Vector4};
+
+use tidal_core_derive::evaluator;
+
+use crate::interpreter::Multiple;
+use crate::random::Random;
+use crate::renderer::{Command, CommandList, Instance, Mesh};
+
+#[inline]
+fn gradient(from: Vector3<f32>, to: Vector3<f32>, index: usize, count: usize) -> Vector4<f32> {
+    let t = if count > 1 {
+        index as f32 / (count - 1) as f32
+    } else {
+        0.0
+    };
+
+    (from + (to - from) * t).extend(1.0)
+}
+
+#[inline]
+fn instanced(mesh: Rc<Mesh>, instances: Vec<Instance>) -> CommandList {
+    CommandList::from(Command::AddInstancedObject(mesh, instances.into()))
+}
+
+#[evaluator(InstanceGridEvaluator for InstanceGrid)]
+#[category("Render/Instance")]
+#[output(name = "commands")]
+fn evaluate_instance_grid(
+    mesh: Rc<Mesh>,
+    #[default(3.0)] count_x: f32,
+    #[default(3.0)] count_y: f32,
+    #[default(1.0)] count_z: f32,
+    #[default(1.0, 1.0, 1.0)] spacing: Vector3<f32>,
+    #[default(1.0, 1.0, 1.0)] color_from: Vector3<f32>,
+    #[default(1.0, 1.0, 1.0)] color_to: Vector3<f32>,
+) -> CommandList {
+    let counts = [count_x, count_y, count_z].map(|count| count.round().max(1.0) as usize);
+    let total = counts.iter().product();
+
+    // Center the grid around the origin.
+    let offset = Vector3::new(
+        (counts[0] - 1) as f32 * spacing.x,
+        (counts[1] - 1) as f32 * spacing.y,
+        (counts[2] - 1) as f32 * spacing.z,
+    ) * 0.5;
+
+    let mut instances = Vec::with_capacity(total);
+
+    for z in 0..counts[2] {
+        for y in 0..counts[1] {
+            for x in 0..counts[0] {
+                let position = Vector3::new(
+                    x as f32 * spacing.x,
+                    y as f32 * spacing.y,
+                    z as f32 * spacing.z,
+                ) - offset;
+
+                instances.push(Instance {
+                    transform: Matrix4::from_translation(position),
+                    color: gradient(color_from, color_to, instances.len(), total),
+                });
+            }
+        }
+    }
+
+    instanced(mesh, instances)
+}
+
+#[evaluator(InstanceRingEvaluator for InstanceRing)]
+#[category("Render/Instance")]
+#[output(name = "commands")]
+fn evaluate_instance_ring(
+    mesh: Rc<Mesh>,
+    #[default(8.0)] count: f32,
+    #[default(1.0)] radius: f32,
+    #[default(0.0)] phase: f32,
+    #[default(1.0, 1.0, 1.0)] color_from: Vector3<f32>,
+    #[default(1.0, 1.0, 1.0)] color_to: Vector3<f32>,
+) -> CommandList {
+    let count = count.round().max(1.0) as usize;
+
+    let instances = (0..count)
+        .map(|i| {
+            let angle = phase + TAU * i as f32 / count as f32;
+            let position = Vector3::new(angle.cos(), angle.sin(), 0.0) * radius;
+
+            // Rotate each copy so it follows the ring.
+            Instance {
+                transform: Matrix4::from_translation(position) * Matrix4::from_angle_z(Rad(angle)),
+                color: gradient(color_from, color_to, i, count),
+            }
+        })
+        .collect();
+
+    instanced(mesh, instances)
+}
+
+#[evaluator(InstanceScatterEvaluator for InstanceScatter)]
+#[category("Render/Instance")]
+#[output(name = "commands")]
+fn evaluate_instance_scatter(
+    mesh: Rc<Mesh>,
+    surface: Rc<Mesh>,
+    #[default(64.0)] count: f32,
+    #[default(0.0)] seed: f32,
+    #[default(1.0)] scale: f32,
+    #[default(1.0, 1.0, 1.0)] color_from: Vector3<f32>,
+    #[default(1.0, 1.0, 1.0)] color_to: Vector3<f32>,
+) -> CommandList {
+    let count = count.round().max(0.0) as usize;
+
+    // Pick triangles proportionally to their area so the distribution is uniform over
+    // the surface.
+    let triangles: Vec<[Vector3<f32>; 3]> = surface
+        .vertices()
+        .chunks_exact(3)
+        .map(|t| [0, 1, 2].map(|i| Vector3::from(t[i].positions)))
+        .collect();
+
+    let mut cumulative_areas = Vec::with_capacity(triangles.len());
+    let mut total_area = 0.0;
+
+    for [a, b, c] in triangles.iter() {
+        total_area += (b - a).cross(c - a).magnitude() * 0.5;
+        cumulative_areas.push(total_area);
+    }
+
+    if total_area <= 0.0 {
+        return instanced(mesh, vec![]);
+    }
+
+    let mut random = Random::from_f32(seed);
+
+    let instances = (0..count)
+        .map(|i| {
+            let target = random.next_f32() * total_area;
+            let triangle = cumulative_areas
+                .partition_point(|area| *area < target)
+                .min(triangles.len() - 1);
+            let [a, b, c] = triangles[triangle];
+
+            // Uniform barycentric coordinates
+            let (mut u, mut v) = (random.next_f32(), random.next_f32());
+            if u + v > 1.0 {
+                (u, v) = (1.0 - u, 1.0 - v);
+            }
+
+            let position = a + (b - a) * u + (c - a) * v;
+
+            Instance {
+                transform: Matrix4::from_translation(position) * Matrix4::from_scale(scale),
+                color: gradient(color_from, color_to, i, count),
+            }
+        })
+        .collect();
+
+    instanced(mesh, instances)
+}
+
+#[evaluator(InstanceListEvaluator for InstanceList)]
+#[category("Render/Instance")]
+#[output(name = "commands")]
+fn evaluate_instance_list(
+    mesh: Rc<Mesh>,
+    positions: Multiple<Vector3<f32>>,
+    #[default(1.0, 1.0, 1.0)] color_from: Vector3<f32>,
+    #[default(1.0, 1.0, 1.0)] color_to: Vector3<f32>,
+) -> CommandList {
+    let positions: Vec<Vector3<f32>> = positions.into_values().collect();
+
+    let instances = positions
+        .iter()
+        .enumerate()
+        .map(|(i, position)| Instance {
+            transform: Matrix4::from_translation(*position),
+            color: gradient(color_from, color_to, i, positions.len()),
+        })
+        .collect();
+
+    instanced(mesh, instances)
+}
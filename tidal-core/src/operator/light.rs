@@ -0,0 +1,102 @@
+use cgmath::{EuclideanSpace, InnerSpace, Point3, Vector3};
+
+use tidal_core_derive::evaluator;
+
+use crate::renderer::{Command, CommandList, Light, LightKind, ShadowSettings, MAX_CASCADES};
+
+/// Light shining in a single direction from infinitely far away, like the sun.
+#[evaluator(DirectionalLightEvaluator for DirectionalLight)]
+#[category("Render/Light")]
+#[output(name = "commands")]
+fn directional_light(
+    mut command_list: CommandList,
+    #[default(- 0.5, - 1.0, - 0.3)] direction: Vector3<f32>,
+    #[default(1.0, 1.0, 1.0)] color: Vector3<f32>,
+    #[input(min = 0.0, step = 0.01)]
+    #[default(1.0)]
+    intensity: f32,
+    #[default(1.0)] cast_shadows: f32,
+    #[default(0.002)] shadow_bias: f32,
+    #[default(2048.0)] shadow_resolution: f32,
+    /// Number of shadow maps splitting the view distance, nearer ones being sharper
+    #[input(min = 1, max = 4, step = 1)]
+    #[default(3.0)]
+    cascades: f32,
+    #[default(50.0)] shadow_distance: f32,
+) -> CommandList {
+    let direction = normalize_or(direction, -Vector3::unit_y());
+
+    let shadow = (cast_shadows > 0.5).then(|| ShadowSettings {
+        bias: shadow_bias.max(0.0),
+        resolution: shadow_resolution.max(0.0) as u32,
+        cascades: (cascades.round().max(1.0) as u32).min(MAX_CASCADES),
+        distance: shadow_distance.max(1.0),
+    });
+
+    command_list.add(Command::AddLight(Light {
+        kind: LightKind::Directional { direction },
+        color,
+        intensity: intensity.max(0.0),
+        shadow,
+    }));
+    command_list
+}
+
+/// Cone of light from `position` towards `target`.
+#[evaluator(SpotLightEvaluator for SpotLight)]
+#[category("Render/Light")]
+#[output(name = "commands")]
+fn spot_light(
+    mut command_list: CommandList,
+    #[default(0.0, 5.0, 0.0)] position: Vector3<f32>,
+    #[default(0.0, 0.0, 0.0)] target: Vector3<f32>,
+    #[default(1.0, 1.0, 1.0)] color: Vector3<f32>,
+    #[input(min = 0.0, step = 0.01)]
+    #[default(1.0)]
+    intensity: f32,
+    #[default(20.0)] range: f32,
+    /// Half angle of the cone in degrees
+    #[input(min = 1.0, max = 89.0)]
+    #[default(30.0)]
+    angle: f32,
+    /// Fraction of the cone fading out towards its edge
+    #[input(min = 0.0, max = 1.0, step = 0.01)]
+    #[default(0.2)]
+    softness: f32,
+    #[default(1.0)] cast_shadows: f32,
+    #[default(0.0005)] shadow_bias: f32,
+    #[default(1024.0)] shadow_resolution: f32,
+) -> CommandList {
+    let direction = normalize_or(target - position, -Vector3::unit_y());
+    let outer_angle = angle.clamp(1.0, 89.0).to_radians();
+
+    let shadow = (cast_shadows > 0.5).then(|| ShadowSettings {
+        bias: shadow_bias.max(0.0),
+        resolution: shadow_resolution.max(0.0) as u32,
+        cascades: 1,
+        distance: range,
+    });
+
+    command_list.add(Command::AddLight(Light {
+        kind: LightKind::Spot {
+            position: Point3::from_vec(position),
+            direction,
+            range: range.max(0.1),
+            inner_angle: outer_angle * (1.0 - softness.clamp(0.0, 1.0)),
+            outer_angle,
+        },
+        color,
+        intensity: intensity.max(0.0),
+        shadow,
+    }));
+    command_list
+}
+
+#[inline]
+fn normalize_or(vector: Vector3<f32>, fallback: Vector3<f32>) -> Vector3<f32> {
+    if vector.magnitude2() > f32::EPSILON {
+        vector.normalize()
+    } else {
+        fallback
+    }
+}
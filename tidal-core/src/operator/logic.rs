@@ -0,0 +1,171 @@
+use cgmath::Vector3;
+
+use tidal_core_derive::evaluator;
+
+use crate::graph::OperatorEnum;
+use crate::interpreter::{EvaluateContext, EvaluateError, Lazy, LazyMultiple};
+use crate::renderer::CommandList;
+
+/// Relation checked by [`Compare`].
+#[derive(OperatorEnum, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+/// Compares `a` with `b`. Values closer than `tolerance` are equal.
+#[evaluator(CompareEvaluator for Compare)]
+#[category("Logic")]
+#[output(name = "result")]
+fn evaluate_compare(
+    #[default(0.0)] a: f32,
+    #[default(0.0)] b: f32,
+    #[default(Equal)] operation: Comparison,
+    #[default(0.0001)] tolerance: f32,
+) -> bool {
+    let equal = (a - b).abs() <= tolerance;
+
+    match operation {
+        Comparison::Equal => equal,
+        Comparison::NotEqual => !equal,
+        Comparison::Less => a < b && !equal,
+        Comparison::LessOrEqual => a < b || equal,
+        Comparison::Greater => a > b && !equal,
+        Comparison::GreaterOrEqual => a > b || equal,
+    }
+}
+
+#[evaluator(AndEvaluator for And)]
+#[category("Logic")]
+#[output(name = "result")]
+fn evaluate_and(#[default(false)] a: bool, #[default(false)] b: bool) -> bool {
+    a && b
+}
+
+#[evaluator(OrEvaluator for Or)]
+#[category("Logic")]
+#[output(name = "result")]
+fn evaluate_or(#[default(false)] a: bool, #[default(false)] b: bool) -> bool {
+    a || b
+}
+
+#[evaluator(XorEvaluator for Xor)]
+#[category("Logic")]
+#[output(name = "result")]
+fn evaluate_xor(#[default(false)] a: bool, #[default(false)] b: bool) -> bool {
+    a != b
+}
+
+#[evaluator(NotEvaluator for Not)]
+#[category("Logic")]
+#[output(name = "result")]
+fn evaluate_not(#[default(false)] value: bool) -> bool {
+    !value
+}
+
+/// Passes through the commands connected at `index`, evaluating only them. Other indices
+/// output nothing, so a negative index turns everything off.
+#[evaluator(SwitchEvaluator for Switch)]
+#[category("Logic")]
+#[output(name = "commands")]
+#[fallible]
+fn evaluate_switch(
+    #[context] ctx: &mut EvaluateContext,
+    #[default(0)] index: i32,
+    inputs: LazyMultiple<CommandList>,
+) -> Result<CommandList, EvaluateError> {
+    match usize::try_from(index) {
+        Ok(index) if index < inputs.len() => inputs.evaluate(ctx, index),
+        _ => Ok(CommandList::new()),
+    }
+}
+
+/// Passes through `if_true` or `if_false` depending on `condition`, evaluating only that
+/// branch. An unconnected branch outputs nothing.
+#[evaluator(SelectEvaluator for Select)]
+#[category("Logic")]
+#[output(name = "commands")]
+#[fallible]
+fn evaluate_select(
+    #[context] ctx: &mut EvaluateContext,
+    #[default(true)] condition: bool,
+    if_true: Lazy<CommandList>,
+    if_false: Lazy<CommandList>,
+) -> Result<CommandList, EvaluateError> {
+    let branch = if condition { if_true } else { if_false };
+
+    if branch.is_connected() {
+        branch.evaluate(ctx)
+    } else {
+        Ok(CommandList::new())
+    }
+}
+
+/// Outputs the value connected at `index`, clamped to the connected values.
+#[evaluator(ScalarSwitchEvaluator for ScalarSwitch)]
+#[category("Logic")]
+#[output(name = "value")]
+#[fallible]
+fn evaluate_scalar_switch(
+    #[context] ctx: &mut EvaluateContext,
+    #[default(0)] index: i32,
+    inputs: LazyMultiple<f32>,
+) -> Result<f32, EvaluateError> {
+    match inputs.len() {
+        0 => Ok(0.0),
+        len => inputs.evaluate(ctx, index.clamp(0, len as i32 - 1) as usize),
+    }
+}
+
+#[evaluator(ScalarSelectEvaluator for ScalarSelect)]
+#[category("Logic")]
+#[output(name = "value")]
+#[fallible]
+fn evaluate_scalar_select(
+    #[context] ctx: &mut EvaluateContext,
+    #[default(true)] condition: bool,
+    #[default(1.0)] if_true: Lazy<f32>,
+    #[default(0.0)] if_false: Lazy<f32>,
+) -> Result<f32, EvaluateError> {
+    if condition {
+        if_true.evaluate(ctx)
+    } else {
+        if_false.evaluate(ctx)
+    }
+}
+
+#[evaluator(VectorSwitchEvaluator for VectorSwitch)]
+#[category("Logic")]
+#[output(name = "vector")]
+#[fallible]
+fn evaluate_vector_switch(
+    #[context] ctx: &mut EvaluateContext,
+    #[default(0)] index: i32,
+    inputs: LazyMultiple<Vector3<f32>>,
+) -> Result<Vector3<f32>, EvaluateError> {
+    match inputs.len() {
+        0 => Ok(Vector3::new(0.0, 0.0, 0.0)),
+        len => inputs.evaluate(ctx, index.clamp(0, len as i32 - 1) as usize),
+    }
+}
+
+#[evaluator(VectorSelectEvaluator for VectorSelect)]
+#[category("Logic")]
+#[output(name = "vector")]
+#[fallible]
+fn evaluate_vector_select(
+    #[context] ctx: &mut EvaluateContext,
+    #[default(true)] condition: bool,
+    #[default(1.0, 1.0, 1.0)] if_true: Lazy<Vector3<f32>>,
+    #[default(0.0, 0.0, 0.0)] if_false: Lazy<Vector3<f32>>,
+) -> Result<Vector3<f32>, EvaluateError> {
+    if condition {
+        if_true.evaluate(ctx)
+    } else {
+        if_false.evaluate(ctx)
+    }
+}
@@ -1,7 +1,4 @@
-use std::time::{Duration, SystemTime};
-
 use cgmath::Vector3;
-use lazy_static::lazy_static;
 
 use tidal_core_derive::evaluator;
 
@@ -10,37 +7,35 @@ use crate::interpreter::evaluator::EvaluateError;
 use crate::renderer::CommandList;
 
 #[evaluator(CosEvaluator for Cos)]
+#[category("Math/Trig")]
 #[output(name = "cosine")]
 pub(crate) fn evaluate_cos(value: f32) -> f32 {
     value.cos()
 }
 
 #[evaluator(SinEvaluator for Sin)]
+#[category("Math/Trig")]
 #[output(name = "sine")]
 pub(crate) fn evaluate_sin(value: f32) -> f32 {
     value.sin()
 }
 
 #[evaluator(ComposeVectorEvaluator for ComposeVector)]
+#[category("Vector")]
 #[output(name = "vector")]
 pub(crate) fn evaluate_compose_vector(x: f32, y: f32, z: f32) -> Vector3<f32> {
     Vector3::new(x, y, z)
 }
 
 #[evaluator(TimeEvaluator for Time)]
+#[category("Animation")]
 #[output(name = "time")]
-pub(crate) fn evaluate_time() -> f32 {
-    lazy_static! {
-        static ref START: SystemTime = SystemTime::now() - Duration::from_secs(1);
-    }
-
-    SystemTime::now()
-        .duration_since(*START)
-        .unwrap()
-        .as_secs_f32()
+pub(crate) fn evaluate_time(#[context] ctx: &mut EvaluateContext) -> f32 {
+    ctx.time()
 }
 
 #[evaluator(RemapEvaluator for Remap)]
+#[category("Math")]
 #[output(name = "value")]
 pub(crate) fn evaluate_remap(
     value: f32,
@@ -53,3 +48,132 @@ pub(crate) fn evaluate_remap(
 
     to_min + c * (to_max - to_min)
 }
+
+#[evaluator(AddEvaluator for Add)]
+#[category("Math")]
+#[output(name = "value")]
+pub(crate) fn evaluate_add(a: f32, b: f32) -> f32 {
+    a + b
+}
+
+#[evaluator(SubtractEvaluator for Subtract)]
+#[category("Math")]
+#[output(name = "value")]
+pub(crate) fn evaluate_subtract(a: f32, b: f32) -> f32 {
+    a - b
+}
+
+#[evaluator(MultiplyEvaluator for Multiply)]
+#[category("Math")]
+#[output(name = "value")]
+pub(crate) fn evaluate_multiply(a: f32, #[default(1.0)] b: f32) -> f32 {
+    a * b
+}
+
+#[evaluator(DivideEvaluator for Divide)]
+#[category("Math")]
+#[output(name = "value")]
+pub(crate) fn evaluate_divide(a: f32, #[default(1.0)] b: f32) -> f32 {
+    a / b
+}
+
+#[evaluator(PowerEvaluator for Power)]
+#[category("Math")]
+#[output(name = "value")]
+pub(crate) fn evaluate_power(base: f32, #[default(1.0)] exponent: f32) -> f32 {
+    base.powf(exponent)
+}
+
+/// Remainder of `a / b` with the sign of `b`, like GLSL's `mod`.
+#[evaluator(ModuloEvaluator for Modulo)]
+#[category("Math")]
+#[output(name = "value")]
+pub(crate) fn evaluate_modulo(a: f32, #[default(1.0)] b: f32) -> f32 {
+    a - b * (a / b).floor()
+}
+
+#[evaluator(MinEvaluator for Min)]
+#[category("Math")]
+#[output(name = "value")]
+pub(crate) fn evaluate_min(a: f32, b: f32) -> f32 {
+    a.min(b)
+}
+
+#[evaluator(MaxEvaluator for Max)]
+#[category("Math")]
+#[output(name = "value")]
+pub(crate) fn evaluate_max(a: f32, b: f32) -> f32 {
+    a.max(b)
+}
+
+#[evaluator(ClampEvaluator for Clamp)]
+#[category("Math")]
+#[output(name = "value")]
+pub(crate) fn evaluate_clamp(
+    value: f32,
+    #[default(0.0)] min: f32,
+    #[default(1.0)] max: f32,
+) -> f32 {
+    value.max(min).min(max)
+}
+
+#[evaluator(AbsEvaluator for Abs)]
+#[category("Math")]
+#[output(name = "value")]
+pub(crate) fn evaluate_abs(value: f32) -> f32 {
+    value.abs()
+}
+
+#[evaluator(FloorEvaluator for Floor)]
+#[category("Math")]
+#[output(name = "value")]
+pub(crate) fn evaluate_floor(value: f32) -> f32 {
+    value.floor()
+}
+
+#[evaluator(FractEvaluator for Fract)]
+#[category("Math")]
+#[output(name = "value")]
+pub(crate) fn evaluate_fract(value: f32) -> f32 {
+    value - value.floor()
+}
+
+/// Linear interpolation from `a` to `b`. `t` isn't clamped, so it also extrapolates.
+#[evaluator(MixEvaluator for Mix)]
+#[category("Math")]
+#[output(name = "value")]
+pub(crate) fn evaluate_mix(
+    a: f32,
+    #[default(1.0)] b: f32,
+    /// Interpolation factor, `a` at 0 and `b` at 1
+    #[default(0.5)]
+    t: f32,
+) -> f32 {
+    a + (b - a) * t
+}
+
+#[evaluator(StepEvaluator for Step)]
+#[category("Math")]
+#[output(name = "value")]
+pub(crate) fn evaluate_step(#[default(0.5)] edge: f32, value: f32) -> f32 {
+    (value >= edge) as u32 as f32
+}
+
+#[evaluator(SmoothstepEvaluator for Smoothstep)]
+#[category("Math")]
+#[output(name = "value")]
+pub(crate) fn evaluate_smoothstep(
+    #[default(0.0)] edge_start: f32,
+    #[default(1.0)] edge_end: f32,
+    value: f32,
+) -> f32 {
+    smoothstep(edge_start, edge_end, value)
+}
+
+/// Hermite interpolation between 0 and 1 as `value` goes from `edge_start` to `edge_end`.
+#[inline]
+pub(crate) fn smoothstep(edge_start: f32, edge_end: f32, value: f32) -> f32 {
+    let t = ((value - edge_start) / (edge_end - edge_start)).clamp(0.0, 1.0);
+
+    t * t * (3.0 - 2.0 * t)
+}
@@ -1,7 +1,29 @@
+pub use animation::*;
+pub use beat::*;
+pub use instance::*;
+pub use light::*;
+pub use logic::*;
 pub use math::*;
+pub use noise::*;
 pub use registry::*;
 pub use render::*;
+pub use repeat::*;
+pub use sequencer::*;
+pub use vector::*;
 
+pub mod animation;
+pub mod audio;
+pub mod beat;
+pub mod expression;
+pub mod group;
+pub mod instance;
+pub mod light;
+pub mod logic;
 pub mod math;
+pub mod noise;
+pub mod particles;
 pub mod registry;
 pub mod render;
+pub mod repeat;
+pub mod sequencer;
+pub mod vector;
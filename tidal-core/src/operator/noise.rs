@@ -0,0 +1,328 @@
+use std::rc::Rc;
+
+use cgmath::{Vector2, Vector3, Vector4};
+
+use tidal_core_derive::evaluator;
+
+use crate::interpreter::EvaluateContext;
+use crate::noise::{Fbm, NoiseKind, MAX_OCTAVES};
+use crate::renderer::{NoiseTexturePass, RenderGraphContext, Texture};
+
+/// Largest side of a noise texture, in pixels.
+const MAX_RESOLUTION: f32 = 8192.0;
+
+/// Position types noise can be sampled at.
+trait NoisePoint<const N: usize> {
+    fn to_point(self) -> [f32; N];
+}
+
+impl NoisePoint<1> for f32 {
+    fn to_point(self) -> [f32; 1] {
+        [self]
+    }
+}
+
+impl NoisePoint<2> for Vector2<f32> {
+    fn to_point(self) -> [f32; 2] {
+        self.into()
+    }
+}
+
+impl NoisePoint<3> for Vector3<f32> {
+    fn to_point(self) -> [f32; 3] {
+        self.into()
+    }
+}
+
+impl NoisePoint<4> for Vector4<f32> {
+    fn to_point(self) -> [f32; 4] {
+        self.into()
+    }
+}
+
+fn fbm(
+    kind: NoiseKind,
+    seed: f32,
+    frequency: f32,
+    octaves: f32,
+    lacunarity: f32,
+    gain: f32,
+) -> Fbm {
+    Fbm {
+        kind,
+        seed: seed.to_bits(),
+        frequency,
+        octaves: octaves.round().clamp(1.0, MAX_OCTAVES as f32) as u32,
+        lacunarity,
+        gain,
+    }
+}
+
+/// Noise sampled at a position. `value` is a single channel, `vector` three decorrelated
+/// channels whose first equals `value`. More than one octave sums finer noise on top (fBm).
+macro_rules! noise_operator {
+    ($evaluator:ident for $name:ident, $function:ident, $kind:expr, $position:ty, $($default:literal),+) => {
+        #[evaluator($evaluator for $name)]
+        #[category("Noise")]
+        #[output(name = "value")]
+        #[output(name = "vector")]
+        fn $function(
+            #[default($($default),+)] position: $position,
+            #[default(0.0)] seed: f32,
+            #[default(1.0)] frequency: f32,
+            /// Number of noise layers summed, each finer than the previous
+            #[input(min = 1, max = 16, step = 1)]
+            #[default(1.0)] octaves: f32,
+            #[default(2.0)] lacunarity: f32,
+            #[default(0.5)] gain: f32,
+        ) -> (f32, Vector3<f32>) {
+            let fbm = fbm($kind, seed, frequency, octaves, lacunarity, gain);
+            let point = position.to_point();
+
+            let vector = fbm.sample_vector(point);
+
+            (vector[0], vector.into())
+        }
+    };
+}
+
+noise_operator!(
+    PerlinNoise1DEvaluator for PerlinNoise1D,
+    evaluate_perlin_noise_1d,
+    NoiseKind::Perlin,
+    f32,
+    0.0
+);
+noise_operator!(
+    PerlinNoise2DEvaluator for PerlinNoise2D,
+    evaluate_perlin_noise_2d,
+    NoiseKind::Perlin,
+    Vector2<f32>,
+    0.0,
+    0.0
+);
+noise_operator!(
+    PerlinNoise3DEvaluator for PerlinNoise3D,
+    evaluate_perlin_noise_3d,
+    NoiseKind::Perlin,
+    Vector3<f32>,
+    0.0,
+    0.0,
+    0.0
+);
+noise_operator!(
+    PerlinNoise4DEvaluator for PerlinNoise4D,
+    evaluate_perlin_noise_4d,
+    NoiseKind::Perlin,
+    Vector4<f32>,
+    0.0,
+    0.0,
+    0.0,
+    0.0
+);
+noise_operator!(
+    SimplexNoise1DEvaluator for SimplexNoise1D,
+    evaluate_simplex_noise_1d,
+    NoiseKind::Simplex,
+    f32,
+    0.0
+);
+noise_operator!(
+    SimplexNoise2DEvaluator for SimplexNoise2D,
+    evaluate_simplex_noise_2d,
+    NoiseKind::Simplex,
+    Vector2<f32>,
+    0.0,
+    0.0
+);
+noise_operator!(
+    SimplexNoise3DEvaluator for SimplexNoise3D,
+    evaluate_simplex_noise_3d,
+    NoiseKind::Simplex,
+    Vector3<f32>,
+    0.0,
+    0.0,
+    0.0
+);
+noise_operator!(
+    SimplexNoise4DEvaluator for SimplexNoise4D,
+    evaluate_simplex_noise_4d,
+    NoiseKind::Simplex,
+    Vector4<f32>,
+    0.0,
+    0.0,
+    0.0,
+    0.0
+);
+noise_operator!(
+    ValueNoise1DEvaluator for ValueNoise1D,
+    evaluate_value_noise_1d,
+    NoiseKind::Value,
+    f32,
+    0.0
+);
+noise_operator!(
+    ValueNoise2DEvaluator for ValueNoise2D,
+    evaluate_value_noise_2d,
+    NoiseKind::Value,
+    Vector2<f32>,
+    0.0,
+    0.0
+);
+noise_operator!(
+    ValueNoise3DEvaluator for ValueNoise3D,
+    evaluate_value_noise_3d,
+    NoiseKind::Value,
+    Vector3<f32>,
+    0.0,
+    0.0,
+    0.0
+);
+noise_operator!(
+    ValueNoise4DEvaluator for ValueNoise4D,
+    evaluate_value_noise_4d,
+    NoiseKind::Value,
+    Vector4<f32>,
+    0.0,
+    0.0,
+    0.0,
+    0.0
+);
+noise_operator!(
+    WorleyNoise1DEvaluator for WorleyNoise1D,
+    evaluate_worley_noise_1d,
+    NoiseKind::Worley,
+    f32,
+    0.0
+);
+noise_operator!(
+    WorleyNoise2DEvaluator for WorleyNoise2D,
+    evaluate_worley_noise_2d,
+    NoiseKind::Worley,
+    Vector2<f32>,
+    0.0,
+    0.0
+);
+noise_operator!(
+    WorleyNoise3DEvaluator for WorleyNoise3D,
+    evaluate_worley_noise_3d,
+    NoiseKind::Worley,
+    Vector3<f32>,
+    0.0,
+    0.0,
+    0.0
+);
+noise_operator!(
+    WorleyNoise4DEvaluator for WorleyNoise4D,
+    evaluate_worley_noise_4d,
+    NoiseKind::Worley,
+    Vector4<f32>,
+    0.0,
+    0.0,
+    0.0,
+    0.0
+);
+
+#[derive(Debug, Default)]
+struct NoiseTexture {
+    pass: Option<Rc<NoiseTexturePass>>,
+    texture: Option<Rc<Texture>>,
+    /// Parameters the texture was last rendered with
+    rendered: Option<(Fbm, Vector3<f32>)>,
+}
+
+impl NoiseTexture {
+    fn evaluate(
+        &mut self,
+        ctx: &mut EvaluateContext,
+        fbm: Fbm,
+        resolution: Vector2<f32>,
+        offset: Vector3<f32>,
+    ) -> Rc<Texture> {
+        let size = resolution
+            .map(|x| x.round().clamp(1.0, MAX_RESOLUTION) as u32)
+            .into();
+
+        if self.texture.as_ref().is_some_and(|t| t.size() != size) {
+            self.texture = None;
+        }
+
+        let texture = match &self.texture {
+            Some(texture) if self.rendered == Some((fbm, offset)) => return texture.clone(),
+            Some(texture) => texture.clone(),
+            None => Rc::new(ctx.renderer().create_noise_texture(size)),
+        };
+
+        let pass = self
+            .pass
+            .get_or_insert_with(|| Rc::new(ctx.renderer().create_noise_texture_pass()))
+            .clone();
+
+        // Only rendered again when a parameter changes, the noise doesn't depend on the time.
+        let target = texture.clone();
+        ctx.render_graph()
+            .add_pass("NoiseTexture", move |pass_ctx: &mut RenderGraphContext| {
+                pass.record(pass_ctx, &fbm, offset, &target);
+
+                Ok(())
+            });
+
+        self.texture = Some(texture.clone());
+        self.rendered = Some((fbm, offset));
+
+        texture
+    }
+}
+
+/// Noise rendered into a texture on the GPU. The red channel holds the noise, green and blue
+/// decorrelated copies. `offset` moves the sampled area, in texture widths; animating its z
+/// component evolves the noise in place.
+macro_rules! noise_texture_operator {
+    ($evaluator:ident for $name:ident, $function:ident, $kind:expr) => {
+        #[derive(Debug, Default)]
+        pub(crate) struct $evaluator(NoiseTexture);
+
+        #[evaluator(impl $evaluator for $name)]
+        #[category("Noise/Texture")]
+        #[output(name = "texture")]
+        fn $function(
+            #[state] state: &mut $evaluator,
+            #[context] ctx: &mut EvaluateContext,
+            #[default(512.0, 512.0)] resolution: Vector2<f32>,
+            #[default(0.0, 0.0, 0.0)] offset: Vector3<f32>,
+            #[default(0.0)] seed: f32,
+            #[default(4.0)] frequency: f32,
+            /// Number of noise layers summed, each finer than the previous
+            #[input(min = 1, max = 16, step = 1)]
+            #[default(1.0)]
+            octaves: f32,
+            #[default(2.0)] lacunarity: f32,
+            #[default(0.5)] gain: f32,
+        ) -> Rc<Texture> {
+            let fbm = fbm($kind, seed, frequency, octaves, lacunarity, gain);
+
+            state.0.evaluate(ctx, fbm, resolution, offset)
+        }
+    };
+}
+
+noise_texture_operator!(
+    PerlinNoiseTextureEvaluator for PerlinNoiseTexture,
+    evaluate_perlin_noise_texture,
+    NoiseKind::Perlin
+);
+noise_texture_operator!(
+    SimplexNoiseTextureEvaluator for SimplexNoiseTexture,
+    evaluate_simplex_noise_texture,
+    NoiseKind::Simplex
+);
+noise_texture_operator!(
+    ValueNoiseTextureEvaluator for ValueNoiseTexture,
+    evaluate_value_noise_texture,
+    NoiseKind::Value
+);
+noise_texture_operator!(
+    WorleyNoiseTextureEvaluator for WorleyNoiseTexture,
+    evaluate_worley_noise_texture,
+    NoiseKind::Worley
+);
@@ -0,0 +1,133 @@
+use std::rc::Rc;
+
+use cgmath::Vector3;
+
+use tidal_core_derive::evaluator;
+
+use crate::interpreter::EvaluateContext;
+use crate::renderer::{
+    Command, CommandList, EmitterShape, ParticleAppearance, ParticleSimulationStep, ParticleSystem,
+    RenderGraphContext,
+};
+
+/// Longest simulation step, so a stalled frame doesn't make particles jump.
+const MAXIMUM_DELTA_TIME: f32 = 0.1;
+
+/// Most particles of a single system, so a bad capacity doesn't exhaust the GPU memory. Their
+/// buffer stays within the default limit of a storage buffer binding.
+const MAXIMUM_CAPACITY: f32 = 1_000_000.0;
+
+#[derive(Debug, Default)]
+pub(crate) struct ParticleSystemEvaluator {
+    system: Option<Rc<ParticleSystem>>,
+    last_time: Option<f32>,
+    /// Fraction of a particle left to emit from previous frames
+    pending_emission: f32,
+    /// Next particle of the ring buffer to respawn
+    cursor: u32,
+    /// Whether the device was found to lack support for particles, which is only reported once
+    unsupported: bool,
+}
+
+#[evaluator(impl ParticleSystemEvaluator for ParticleSystem)]
+#[category("Render")]
+#[output(name = "commands")]
+fn evaluate_particle_system(
+    #[state] state: &mut ParticleSystemEvaluator,
+    #[context] ctx: &mut EvaluateContext,
+    #[default(Point)] shape: EmitterShape,
+    #[default(0.0, 0.0, 0.0)] position: Vector3<f32>,
+    #[default(1.0, 1.0, 1.0)] emitter_size: Vector3<f32>,
+    #[default(100.0)] rate: f32,
+    #[default(2.0)] lifetime: f32,
+    #[default(0.0, 1.0, 0.0)] velocity: Vector3<f32>,
+    #[default(0.2)] velocity_spread: f32,
+    #[default(0.0, -0.5, 0.0)] gravity: Vector3<f32>,
+    #[default(0.1)] drag: f32,
+    #[default(0.0)] turbulence: f32,
+    #[default(1.0)] turbulence_scale: f32,
+    #[default(1.0, 1.0, 1.0)] color_start: Vector3<f32>,
+    #[default(1.0, 0.3, 0.0)] color_end: Vector3<f32>,
+    #[default(1.0)] alpha_start: f32,
+    #[default(0.0)] alpha_end: f32,
+    #[default(0.02)] size: f32,
+    #[default(10000.0)] capacity: f32,
+    #[default(0.0)] seed: f32,
+) -> CommandList {
+    let capacity = capacity.round().clamp(1.0, MAXIMUM_CAPACITY) as u32;
+
+    // Changing the capacity restarts the simulation.
+    if state
+        .system
+        .as_ref()
+        .is_some_and(|system| system.capacity() != capacity)
+    {
+        *state = ParticleSystemEvaluator::default();
+    }
+
+    if state.system.is_none() {
+        let Some(system) = ctx.renderer().create_particle_system(capacity) else {
+            if !state.unsupported {
+                log::warn!("particles aren't supported by this device, skipping them");
+                state.unsupported = true;
+            }
+
+            return CommandList::new();
+        };
+
+        state.system = Some(Rc::new(system));
+    }
+
+    let system = state.system.clone().unwrap();
+
+    let time = ctx.time();
+    let delta_time = state
+        .last_time
+        .map(|last_time| (time - last_time).clamp(0.0, MAXIMUM_DELTA_TIME))
+        .unwrap_or(0.0);
+    state.last_time = Some(time);
+
+    state.pending_emission += rate.max(0.0) * delta_time;
+    let emit_count = (state.pending_emission.floor() as u32).min(capacity);
+    state.pending_emission -= emit_count as f32;
+
+    let emit_start = state.cursor;
+    state.cursor = ((state.cursor as u64 + emit_count as u64) % capacity as u64) as u32;
+
+    let step = ParticleSimulationStep {
+        shape,
+        emitter_position: position,
+        emitter_size,
+        emit_start,
+        emit_count,
+        velocity,
+        velocity_spread,
+        gravity,
+        drag: drag.max(0.0),
+        turbulence,
+        turbulence_scale,
+        lifetime: lifetime.max(0.0),
+        delta_time,
+        time,
+        seed: seed.to_bits(),
+    };
+
+    let appearance = ParticleAppearance {
+        color_start: color_start.extend(alpha_start),
+        color_end: color_end.extend(alpha_end),
+        size: size.max(0.0),
+    };
+
+    let simulated_system = system.clone();
+    ctx.render_graph().add_pass(
+        "ParticleSimulation",
+        move |pass: &mut RenderGraphContext| {
+            simulated_system.set_appearance(pass.queue, &appearance);
+            simulated_system.simulate(pass.queue, pass.encoder, &step);
+
+            Ok(())
+        },
+    );
+
+    CommandList::from(Command::AddParticles(system))
+}
@@ -1,7 +1,15 @@
+//! Operators are discovered through [`inventory`], so any crate linked into the application can
+//! register its own: submit an [`OperatorMetadataRegistryNode`] and an
+//! [`EvaluatorRegistryNode`] under the same operator name, or declare them with `#[evaluator]`.
+//! They then show up in the editor's picker and run in the player like the built-in ones.
+//!
+//! The linker drops crates nothing refers to, so an application linking a crate only for its
+//! operators has to refer to it, for instance with `use studio_operators as _;`.
+
 use serde::{Deserialize, Serialize};
 
-use crate::graph::Metadata;
-use crate::interpreter::Evaluate;
+use crate::graph::{InputState, Metadata};
+use crate::interpreter::{Evaluate, EvaluateContext, EvaluateError};
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -15,6 +23,16 @@ pub struct OperatorMetadataRegistryNode {
 
 inventory::collect!(OperatorMetadataRegistryNode);
 
+/// Registry node describing the ports of operators that depend on the node's inputs, such as
+/// the variables of an expression. `describe` extends the operator's metadata given the
+/// inputs of a node, which may be fewer than described while the node is being created.
+pub struct NodeMetadataRegistryNode {
+    pub operator: &'static str,
+    pub describe: fn(&mut Metadata, &[InputState]),
+}
+
+inventory::collect!(NodeMetadataRegistryNode);
+
 /// Registry node for Evaluators
 pub struct EvaluatorRegistryNode {
     pub operator: &'static str,
@@ -29,20 +47,62 @@ impl Operator {
         Operator("Scene".into())
     }
 
+    #[inline]
+    pub fn sequencer() -> Operator {
+        Operator("Sequencer".into())
+    }
+
+    #[inline]
+    pub fn expression() -> Operator {
+        Operator("Expression".into())
+    }
+
+    #[inline]
+    pub fn group() -> Operator {
+        Operator("Group".into())
+    }
+
+    #[inline]
+    pub fn group_input() -> Operator {
+        Operator("GroupInput".into())
+    }
+
+    #[inline]
+    pub fn group_output() -> Operator {
+        Operator("GroupOutput".into())
+    }
+
+    /// Whether the operator is registered. Demos can refer to operators of a library the
+    /// application doesn't link, which describe as [`missing_metadata`] and fail to evaluate.
+    pub fn is_registered(&self) -> bool {
+        inventory::iter::<OperatorMetadataRegistryNode>().any(|node| self.0 == node.operator)
+    }
+
     pub fn describe(&self) -> Metadata {
-        let node: &OperatorMetadataRegistryNode = inventory::iter::<OperatorMetadataRegistryNode>()
+        inventory::iter::<OperatorMetadataRegistryNode>()
             .find(|node| self.0 == node.operator)
-            .unwrap();
+            .map(|node| (node.create_metadata)())
+            .unwrap_or_else(missing_metadata)
+    }
+
+    /// Describes the ports of a node of this operator given its inputs.
+    pub fn describe_node(&self, inputs: &[InputState]) -> Metadata {
+        let mut metadata = self.describe();
+
+        for node in inventory::iter::<NodeMetadataRegistryNode>() {
+            if self.0 == node.operator {
+                (node.describe)(&mut metadata, inputs);
+            }
+        }
 
-        (node.create_metadata)()
+        metadata
     }
 
     pub fn to_evaluator(&self) -> Box<dyn Evaluate> {
-        let node = inventory::iter::<EvaluatorRegistryNode>()
+        inventory::iter::<EvaluatorRegistryNode>()
             .find(|node| self.0 == node.operator)
-            .unwrap();
-
-        (node.create_operator)()
+            .map(|node| (node.create_operator)())
+            .unwrap_or_else(|| Box::new(MissingEvaluator))
     }
 
     pub fn all() -> Vec<Operator> {
@@ -51,3 +111,24 @@ impl Operator {
             .collect()
     }
 }
+
+/// Metadata of an operator that isn't registered. It has no ports, so the node keeps its
+/// inputs untouched until the operator is available again.
+fn missing_metadata() -> Metadata {
+    Metadata {
+        name: "Missing",
+        description: Some("Operator not registered, the library defining it may not be linked"),
+        category: None,
+        inputs: vec![],
+        outputs: vec![],
+    }
+}
+
+/// Evaluator of an operator that isn't registered.
+struct MissingEvaluator;
+
+impl Evaluate for MissingEvaluator {
+    fn evaluate(&mut self, _ctx: &mut EvaluateContext) -> Result<(), EvaluateError> {
+        Err(EvaluateError::GenericError)
+    }
+}
@@ -1,12 +1,16 @@
 use std::rc::Rc;
 
-use cgmath::{EuclideanSpace, Point3, Vector3};
+use cgmath::{EuclideanSpace, Matrix4, Point3, Vector3, Vector4, Zero};
 
 use tidal_core_derive::evaluator;
 
 use crate::interpreter::{EvaluateContext, Multiple};
+use crate::operator::to_quaternion;
 use crate::renderer::{
-    Camera, Command, CommandList, Mesh, MeshDescriptor, ScreenRenderPass, Vertex,
+    Camera, Command, CommandList, Mesh, MeshDescriptor, RenderGraphContext, SampleCount,
+    SceneLighting, SceneTargets, ScreenRenderPass, TextureHandle, TextureSize, ToneMapping,
+    ToneMappingPass, ToneMappingSettings, TransientTextureDescriptor, Vertex, DEPTH_FORMAT,
+    HDR_FORMAT, SHADOW_ATLAS_FORMAT,
 };
 
 #[derive(Default)]
@@ -15,6 +19,7 @@ pub(crate) struct MeshEvaluator {
 }
 
 #[evaluator(impl MeshEvaluator for Mesh)]
+#[category("Render")]
 #[output(name = "mesh")]
 fn evaluate_mesh(
     #[state] state: &mut MeshEvaluator,
@@ -48,26 +53,186 @@ fn evaluate_mesh(
         .clone()
 }
 
+/// Corners of the box enclosing a mesh, with its center and size. An empty mesh is a point at
+/// the origin.
+#[evaluator(MeshBoundsEvaluator for MeshBounds)]
+#[category("Render")]
+#[output(name = "min")]
+#[output(name = "max")]
+#[output(name = "center")]
+#[output(name = "size")]
+fn evaluate_mesh_bounds(
+    mesh: Rc<Mesh>,
+) -> (Vector3<f32>, Vector3<f32>, Vector3<f32>, Vector3<f32>) {
+    let mut positions = mesh.vertices().iter().map(|v| Vector3::from(v.positions));
+
+    let Some(first) = positions.next() else {
+        return (
+            Vector3::zero(),
+            Vector3::zero(),
+            Vector3::zero(),
+            Vector3::zero(),
+        );
+    };
+
+    let (min, max) = positions.fold((first, first), |(min, max), p| {
+        (
+            Vector3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+            Vector3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+        )
+    });
+
+    (min, max, (min + max) / 2.0, max - min)
+}
+
+/// Render pipelines shared by the operators drawing scenes.
+#[derive(Debug, Default)]
+pub(crate) struct SceneRenderer {
+    render_pass: Option<Rc<ScreenRenderPass>>,
+    tone_mapping_pass: Option<Rc<ToneMappingPass>>,
+}
+
+impl SceneRenderer {
+    /// Adds the passes drawing a command list into a new HDR texture, and returns it.
+    pub(crate) fn render(
+        &mut self,
+        ctx: &mut EvaluateContext,
+        command_list: CommandList,
+        samples: SampleCount,
+    ) -> TextureHandle {
+        let sample_count = ctx.renderer().supported_sample_count(samples);
+
+        // Pipelines are tied to a sample count, so changing it recreates the pass.
+        if self
+            .render_pass
+            .as_ref()
+            .is_some_and(|render_pass| render_pass.sample_count() != sample_count)
+        {
+            self.render_pass = None;
+        }
+
+        let render_pass = self
+            .render_pass
+            .get_or_insert_with(|| Rc::new(ctx.renderer().create_screen_render_pass(samples)))
+            .clone();
+
+        let lighting = Rc::new(SceneLighting::new(&command_list));
+        let command_list = Rc::new(command_list);
+
+        let mut graph = ctx.render_graph();
+        let atlas_size = lighting.atlas_size();
+
+        let color = graph.create_texture(TransientTextureDescriptor {
+            label: "SceneColor",
+            size: TextureSize::Target,
+            format: HDR_FORMAT.into(),
+            sample_count: 1,
+        });
+        let multisampled_color = (sample_count > 1).then(|| {
+            graph.create_texture(TransientTextureDescriptor {
+                label: "SceneColorMultisampled",
+                size: TextureSize::Target,
+                format: HDR_FORMAT.into(),
+                sample_count,
+            })
+        });
+
+        let targets = SceneTargets {
+            color: multisampled_color.unwrap_or(color),
+            resolve: multisampled_color.map(|_| color),
+            depth: graph.create_texture(TransientTextureDescriptor {
+                label: "SceneDepth",
+                size: TextureSize::Target,
+                format: DEPTH_FORMAT,
+                sample_count,
+            }),
+            shadow_atlas: graph.create_texture(TransientTextureDescriptor {
+                label: "ShadowAtlas",
+                size: TextureSize::Absolute(atlas_size, atlas_size),
+                format: SHADOW_ATLAS_FORMAT,
+                sample_count: 1,
+            }),
+        };
+
+        {
+            let render_pass = render_pass.clone();
+            let command_list = command_list.clone();
+            let lighting = lighting.clone();
+
+            graph
+                .add_pass("Shadows", move |pass: &mut RenderGraphContext| {
+                    render_pass.record_shadows(pass, &command_list, &lighting, targets.shadow_atlas)
+                })
+                .write(targets.shadow_atlas);
+        }
+
+        let scene_pass = graph
+            .add_pass("Scene", move |pass: &mut RenderGraphContext| {
+                render_pass.record(pass, &command_list, &lighting, targets)
+            })
+            .read(targets.shadow_atlas)
+            .write(targets.depth)
+            .write(targets.color);
+
+        if let Some(resolve) = targets.resolve {
+            scene_pass.write(resolve);
+        }
+
+        color
+    }
+
+    /// Adds the pass tone mapping an HDR texture into the frame's render target.
+    pub(crate) fn tone_map(
+        &mut self,
+        ctx: &mut EvaluateContext,
+        source: TextureHandle,
+        tone_mapping: ToneMapping,
+        exposure: f32,
+    ) {
+        let tone_mapping_pass = self
+            .tone_mapping_pass
+            .get_or_insert_with(|| Rc::new(ctx.renderer().create_tone_mapping_pass()))
+            .clone();
+
+        let settings = ToneMappingSettings {
+            tone_mapping,
+            exposure,
+        };
+
+        let mut graph = ctx.render_graph();
+        let output = graph.target();
+
+        graph
+            .add_pass("ToneMapping", move |pass: &mut RenderGraphContext| {
+                tone_mapping_pass.record(pass, &settings, source, output)
+            })
+            .read(source)
+            .write(output);
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct SceneEvaluator {
-    render_pass: Option<ScreenRenderPass>,
+    renderer: SceneRenderer,
 }
 
 #[evaluator(impl SceneEvaluator for Scene)]
+#[category("Render")]
 fn evaluate_scene(
     #[state] state: &mut SceneEvaluator,
     #[context] ctx: &mut EvaluateContext,
     command_list: CommandList,
+    #[default(Aces)] tone_mapping: ToneMapping,
+    #[default(0.0)] exposure: f32,
+    #[default(X4)] samples: SampleCount,
 ) {
-    let render_pass = state
-        .render_pass
-        .get_or_insert_with(|| ctx.renderer().create_render_pass::<ScreenRenderPass>());
+    let color = state.renderer.render(ctx, command_list, samples);
 
-    let x = ctx.interpreter_context.render_target;
-    ctx.renderer().render(render_pass, &command_list, (x))
+    state.renderer.tone_map(ctx, color, tone_mapping, exposure);
 }
 
 #[evaluator(CameraEvaluator for Camera)]
+#[category("Render")]
 #[output(name = "commands")]
 fn camera(
     mut command_list: CommandList,
@@ -93,14 +258,21 @@ fn camera(
     command_list
 }
 
-#[evaluator(MultiplyEvaluator for Multiply)]
-#[output(name = "value")]
-fn evaluate_multiply(a: f32, b: f32) -> f32 {
-    a * b
-}
+/// Scales, rotates then translates the objects and lights of the commands. `rotation` is a
+/// quaternion.
+#[evaluator(TransformEvaluator for Transform)]
+#[category("Render")]
+#[output(name = "commands")]
+fn evaluate_transform(
+    mut command_list: CommandList,
+    #[default(0.0, 0.0, 0.0)] translation: Vector3<f32>,
+    #[default(0.0, 0.0, 0.0, 1.0)] rotation: Vector4<f32>,
+    #[default(1.0, 1.0, 1.0)] scale: Vector3<f32>,
+) -> CommandList {
+    let transform = Matrix4::from_translation(translation)
+        * Matrix4::from(to_quaternion(rotation))
+        * Matrix4::from_nonuniform_scale(scale.x, scale.y, scale.z);
 
-#[evaluator(RenderTargetEvaluator for RenderTarget)]
-#[output(name = "value")]
-fn render_target(a: f32, b: f32) -> f32 {
-    a * b
+    command_list.transform(&transform);
+    command_list
 }
@@ -0,0 +1,52 @@
+use tidal_core_derive::evaluator;
+
+use crate::interpreter::{EvaluateContext, EvaluateError, Lazy};
+use crate::renderer::CommandList;
+
+/// Most iterations of a single repeat, so a bad count doesn't stall the demo.
+const MAXIMUM_REPEAT_COUNT: i32 = 4096;
+
+/// Evaluates the commands `count` times and merges the results. Nodes upstream read the
+/// current iteration with [`Index`], and stateful ones keep a separate state per iteration.
+#[evaluator(RepeatEvaluator for Repeat)]
+#[category("Flow")]
+#[output(name = "commands")]
+#[fallible]
+fn evaluate_repeat(
+    #[context] ctx: &mut EvaluateContext,
+    #[default(4)] count: i32,
+    command_list: Lazy<CommandList>,
+) -> Result<CommandList, EvaluateError> {
+    let count = count.clamp(0, MAXIMUM_REPEAT_COUNT) as u32;
+
+    if !command_list.is_connected() {
+        return Ok(CommandList::new());
+    }
+
+    (0..count)
+        .map(|index| ctx.repeat(index, count, |ctx| command_list.evaluate(ctx)))
+        .collect()
+}
+
+/// Iteration of the enclosing [`Repeat`]. `level` picks an outer repeat when they are nested,
+/// 0 being the innermost. Outside repeats the index is 0 of a count of 1.
+#[evaluator(IndexEvaluator for Index)]
+#[category("Flow")]
+#[output(name = "index")]
+#[output(name = "count")]
+#[output(name = "ratio")]
+fn evaluate_index(
+    #[context] ctx: &mut EvaluateContext,
+    #[default(0)] level: i32,
+) -> (i32, i32, f32) {
+    let (index, count) = usize::try_from(level)
+        .ok()
+        .and_then(|level| ctx.iteration(level))
+        .map(|iteration| (iteration.index, iteration.count))
+        .unwrap_or((0, 1));
+
+    // Index over count, 0 for the first iteration and just under 1 for the last
+    let ratio = index as f32 / count as f32;
+
+    (index as i32, count as i32, ratio)
+}
@@ -0,0 +1,96 @@
+use std::rc::Rc;
+
+use tidal_core_derive::evaluator;
+
+use crate::interpreter::{EvaluateContext, EvaluateError, LazyMultiple};
+use crate::operator::SceneRenderer;
+use crate::renderer::{
+    CommandList, CompositeLayer, CompositePass, RenderGraphContext, SampleCount, TextureSize,
+    ToneMapping, TransientTextureDescriptor, HDR_FORMAT,
+};
+use crate::sequencer::Timeline;
+
+#[derive(Debug, Default)]
+pub struct SequencerEvaluator {
+    renderer: SceneRenderer,
+    composite_pass: Option<Rc<CompositePass>>,
+}
+
+#[evaluator(impl SequencerEvaluator for Sequencer)]
+#[category("Animation")]
+#[fallible]
+fn evaluate_sequencer(
+    #[state] state: &mut SequencerEvaluator,
+    #[context] ctx: &mut EvaluateContext,
+    timeline: Rc<Timeline>,
+    scenes: LazyMultiple<CommandList>,
+    #[default(Aces)] tone_mapping: ToneMapping,
+    #[default(0.0)] exposure: f32,
+    #[default(X4)] samples: SampleCount,
+) -> Result<(), EvaluateError> {
+    let mut rendered: Vec<(usize, CompositeLayer)> = vec![];
+
+    for active in timeline.active_clips(ctx.time()) {
+        if let Some((_, layer)) = rendered
+            .iter_mut()
+            .find(|(scene, _)| *scene == active.scene)
+        {
+            layer.weight += active.weight;
+            continue;
+        }
+
+        // Clips refer to scenes by connection index. Only the scenes of playing clips are
+        // evaluated.
+        if active.scene >= scenes.len() {
+            continue;
+        }
+
+        let command_list = scenes.evaluate(ctx, active.scene)?;
+        let texture = state.renderer.render(ctx, command_list, samples);
+        rendered.push((
+            active.scene,
+            CompositeLayer {
+                texture,
+                weight: active.weight,
+            },
+        ));
+    }
+
+    let layers: Vec<CompositeLayer> = rendered.into_iter().map(|(_, layer)| layer).collect();
+
+    let color = match layers.as_slice() {
+        // Nothing playing, show an empty scene
+        [] => state.renderer.render(ctx, CommandList::new(), samples),
+        [layer] if layer.weight >= 1.0 => layer.texture,
+        _ => {
+            let composite_pass = state
+                .composite_pass
+                .get_or_insert_with(|| Rc::new(ctx.renderer().create_composite_pass()))
+                .clone();
+
+            let mut graph = ctx.render_graph();
+            let color = graph.create_texture(TransientTextureDescriptor {
+                label: "SequencerColor",
+                size: TextureSize::Target,
+                format: HDR_FORMAT.into(),
+                sample_count: 1,
+            });
+
+            let reads: Vec<_> = layers.iter().map(|layer| layer.texture).collect();
+            let mut pass = graph.add_pass("Crossfade", move |pass: &mut RenderGraphContext| {
+                composite_pass.record(pass, &layers, color)
+            });
+
+            for texture in reads {
+                pass = pass.read(texture);
+            }
+
+            pass.write(color);
+            color
+        }
+    };
+
+    state.renderer.tone_map(ctx, color, tone_mapping, exposure);
+
+    Ok(())
+}
@@ -0,0 +1,227 @@
+use cgmath::{
+    Deg, ElementWise, Euler, InnerSpace, Quaternion, Rotation, Rotation3, Vector2, Vector3,
+    Vector4, VectorSpace,
+};
+
+use tidal_core_derive::evaluator;
+
+use crate::color::Color;
+
+#[evaluator(VectorAddEvaluator for VectorAdd)]
+#[category("Vector")]
+#[output(name = "vector")]
+fn evaluate_vector_add(a: Vector3<f32>, b: Vector3<f32>) -> Vector3<f32> {
+    a + b
+}
+
+#[evaluator(VectorSubtractEvaluator for VectorSubtract)]
+#[category("Vector")]
+#[output(name = "vector")]
+fn evaluate_vector_subtract(a: Vector3<f32>, b: Vector3<f32>) -> Vector3<f32> {
+    a - b
+}
+
+/// Component-wise product.
+#[evaluator(VectorMultiplyEvaluator for VectorMultiply)]
+#[category("Vector")]
+#[output(name = "vector")]
+fn evaluate_vector_multiply(
+    a: Vector3<f32>,
+    #[default(1.0, 1.0, 1.0)] b: Vector3<f32>,
+) -> Vector3<f32> {
+    a.mul_element_wise(b)
+}
+
+#[evaluator(VectorScaleEvaluator for VectorScale)]
+#[category("Vector")]
+#[output(name = "vector")]
+fn evaluate_vector_scale(vector: Vector3<f32>, #[default(1.0)] scale: f32) -> Vector3<f32> {
+    vector * scale
+}
+
+#[evaluator(VectorMixEvaluator for VectorMix)]
+#[category("Vector")]
+#[output(name = "vector")]
+fn evaluate_vector_mix(
+    a: Vector3<f32>,
+    #[default(1.0, 1.0, 1.0)] b: Vector3<f32>,
+    #[default(0.5)] t: f32,
+) -> Vector3<f32> {
+    a.lerp(b, t)
+}
+
+#[evaluator(DotEvaluator for Dot)]
+#[category("Vector")]
+#[output(name = "value")]
+fn evaluate_dot(a: Vector3<f32>, b: Vector3<f32>) -> f32 {
+    a.dot(b)
+}
+
+#[evaluator(CrossEvaluator for Cross)]
+#[category("Vector")]
+#[output(name = "vector")]
+fn evaluate_cross(a: Vector3<f32>, b: Vector3<f32>) -> Vector3<f32> {
+    a.cross(b)
+}
+
+/// Scales a vector to a length of 1. The zero vector stays zero.
+#[evaluator(NormalizeEvaluator for Normalize)]
+#[category("Vector")]
+#[output(name = "vector")]
+fn evaluate_normalize(vector: Vector3<f32>) -> Vector3<f32> {
+    let length = vector.magnitude();
+
+    if length > 0.0 {
+        vector / length
+    } else {
+        vector
+    }
+}
+
+#[evaluator(LengthEvaluator for Length)]
+#[category("Vector")]
+#[output(name = "length")]
+fn evaluate_length(vector: Vector3<f32>) -> f32 {
+    vector.magnitude()
+}
+
+#[evaluator(DistanceEvaluator for Distance)]
+#[category("Vector")]
+#[output(name = "distance")]
+fn evaluate_distance(a: Vector3<f32>, b: Vector3<f32>) -> f32 {
+    (b - a).magnitude()
+}
+
+#[evaluator(DecomposeVectorEvaluator for DecomposeVector)]
+#[category("Vector")]
+#[output(name = "x")]
+#[output(name = "y")]
+#[output(name = "z")]
+fn evaluate_decompose_vector(vector: Vector3<f32>) -> (f32, f32, f32) {
+    (vector.x, vector.y, vector.z)
+}
+
+#[evaluator(ComposeVector2Evaluator for ComposeVector2)]
+#[category("Vector")]
+#[output(name = "vector")]
+fn evaluate_compose_vector2(x: f32, y: f32) -> Vector2<f32> {
+    Vector2::new(x, y)
+}
+
+#[evaluator(DecomposeVector2Evaluator for DecomposeVector2)]
+#[category("Vector")]
+#[output(name = "x")]
+#[output(name = "y")]
+fn evaluate_decompose_vector2(vector: Vector2<f32>) -> (f32, f32) {
+    (vector.x, vector.y)
+}
+
+#[evaluator(ComposeVector4Evaluator for ComposeVector4)]
+#[category("Vector")]
+#[output(name = "vector")]
+fn evaluate_compose_vector4(x: f32, y: f32, z: f32, w: f32) -> Vector4<f32> {
+    Vector4::new(x, y, z, w)
+}
+
+#[evaluator(DecomposeVector4Evaluator for DecomposeVector4)]
+#[category("Vector")]
+#[output(name = "x")]
+#[output(name = "y")]
+#[output(name = "z")]
+#[output(name = "w")]
+fn evaluate_decompose_vector4(vector: Vector4<f32>) -> (f32, f32, f32, f32) {
+    (vector.x, vector.y, vector.z, vector.w)
+}
+
+#[evaluator(ComposeColorEvaluator for ComposeColor)]
+#[category("Color")]
+#[output(name = "color")]
+fn evaluate_compose_color(
+    #[default(1.0)] r: f32,
+    #[default(1.0)] g: f32,
+    #[default(1.0)] b: f32,
+    #[default(1.0)] a: f32,
+) -> Color {
+    Color::new(r, g, b, a)
+}
+
+#[evaluator(DecomposeColorEvaluator for DecomposeColor)]
+#[category("Color")]
+#[output(name = "r")]
+#[output(name = "g")]
+#[output(name = "b")]
+#[output(name = "a")]
+#[output(name = "rgb")]
+fn evaluate_decompose_color(color: Color) -> (f32, f32, f32, f32, Vector3<f32>) {
+    (color.r, color.g, color.b, color.a, color.to_rgb())
+}
+
+/// Quaternions travel through the graph as `Vector4`s, with the scalar part in `w`.
+#[inline]
+pub(crate) fn to_quaternion(v: Vector4<f32>) -> Quaternion<f32> {
+    Quaternion::new(v.w, v.x, v.y, v.z)
+}
+
+#[inline]
+fn from_quaternion(q: Quaternion<f32>) -> Vector4<f32> {
+    Vector4::new(q.v.x, q.v.y, q.v.z, q.s)
+}
+
+/// Rotation from Euler angles in degrees, applied in X, Y, Z order.
+#[evaluator(QuaternionFromEulerEvaluator for QuaternionFromEuler)]
+#[category("Vector/Rotation")]
+#[output(name = "rotation")]
+fn evaluate_quaternion_from_euler(angles: Vector3<f32>) -> Vector4<f32> {
+    from_quaternion(Quaternion::from(Euler::new(
+        Deg(angles.x),
+        Deg(angles.y),
+        Deg(angles.z),
+    )))
+}
+
+/// Rotation of `angle` degrees around `axis`.
+#[evaluator(QuaternionFromAxisAngleEvaluator for QuaternionFromAxisAngle)]
+#[category("Vector/Rotation")]
+#[output(name = "rotation")]
+fn evaluate_quaternion_from_axis_angle(
+    #[default(0.0, 1.0, 0.0)] axis: Vector3<f32>,
+    angle: f32,
+) -> Vector4<f32> {
+    let axis = if axis.magnitude2() > 0.0 {
+        axis.normalize()
+    } else {
+        Vector3::unit_y()
+    };
+
+    from_quaternion(Quaternion::from_axis_angle(axis, Deg(angle)))
+}
+
+/// Spherical interpolation between two rotations, taking the shortest path.
+#[evaluator(SlerpEvaluator for Slerp)]
+#[category("Vector/Rotation")]
+#[output(name = "rotation")]
+fn evaluate_slerp(
+    #[default(0.0, 0.0, 0.0, 1.0)] from: Vector4<f32>,
+    #[default(0.0, 0.0, 0.0, 1.0)] to: Vector4<f32>,
+    #[default(0.5)] t: f32,
+) -> Vector4<f32> {
+    let from = to_quaternion(from).normalize();
+    let mut to = to_quaternion(to).normalize();
+
+    // q and -q are the same rotation; pick the one closest to `from`.
+    if from.dot(to) < 0.0 {
+        to = -to;
+    }
+
+    from_quaternion(from.slerp(to, t))
+}
+
+#[evaluator(RotateVectorEvaluator for RotateVector)]
+#[category("Vector/Rotation")]
+#[output(name = "vector")]
+fn evaluate_rotate_vector(
+    vector: Vector3<f32>,
+    #[default(0.0, 0.0, 0.0, 1.0)] rotation: Vector4<f32>,
+) -> Vector3<f32> {
+    to_quaternion(rotation).normalize().rotate_vector(vector)
+}
@@ -0,0 +1,43 @@
+/// Small deterministic pseudo-random generator (SplitMix64).
+///
+/// Operators use it instead of a thread-local generator so results only depend on
+/// their seed and renders are reproducible.
+#[derive(Debug, Copy, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    #[inline]
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Creates a generator from a seed coming from a graph input.
+    #[inline]
+    pub fn from_f32(seed: f32) -> Self {
+        Self::new(seed.to_bits() as u64)
+    }
+
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a value in `[0, 1)`.
+    #[inline]
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Returns a value in `[min, max)`.
+    #[inline]
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+}
@@ -8,7 +8,7 @@ use cgmath::Vector4;
 use wgpu::util::DeviceExt;
 use wgpu::{PipelineLayout, PipelineLayoutDescriptor, RenderPipelineDescriptor, VertexState};
 
-use crate::renderer::{CommandList, Context, Object, RenderPass, Texture};
+use crate::renderer::{CommandList, Context, Object, Texture};
 
 /// Constant Buffer is a buffer that contain render-constants.
 /// It only holds one value.
@@ -1,26 +1,24 @@
-use wgpu::util::DeviceExt;
-
 use crate::renderer::buffers::ConstantBuffer;
 use crate::renderer::Camera;
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
-struct CameraBufferContents([[f32; 4]; 4]);
+struct CameraBufferContents {
+    view_projection: [[f32; 4]; 4],
+    view: [[f32; 4]; 4],
+}
 
+/// Layout of the bind group holding a camera's view and view projection matrices.
+///
+/// Every pass recorded in a frame gets its own buffer, as all queue writes are flushed
+/// before the frame's single submission and a shared buffer would only keep the last one.
 #[derive(Debug)]
 pub struct CameraUniform {
-    buffer: ConstantBuffer<CameraBufferContents>,
     bind_group_layout: wgpu::BindGroupLayout,
-    bind_group: wgpu::BindGroup,
 }
 
 impl CameraUniform {
     pub fn new(device: &wgpu::Device) -> Self {
-        let matrix = Camera::default().to_view_projection_matrix();
-        let contents = CameraBufferContents(matrix.into());
-
-        let buffer = ConstantBuffer::new(device, &contents);
-
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("[CameraUniform] bind_group_layout"),
             entries: &[wgpu::BindGroupLayoutEntry {
@@ -35,36 +33,29 @@ impl CameraUniform {
             }],
         });
 
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        Self { bind_group_layout }
+    }
+
+    pub fn create_bind_group(&self, device: &wgpu::Device, camera: &Camera) -> wgpu::BindGroup {
+        let contents = CameraBufferContents {
+            view_projection: camera.to_view_projection_matrix().into(),
+            view: camera.to_view_matrix().into(),
+        };
+
+        let buffer = ConstantBuffer::new(device, &contents);
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("[CameraUniform] bind_group"),
-            layout: &bind_group_layout,
+            layout: &self.bind_group_layout,
             entries: &[wgpu::BindGroupEntry {
                 binding: 0,
-                resource: wgpu::BindingResource::Buffer(buffer.buffer().as_entire_buffer_binding()),
+                resource: buffer.buffer().as_entire_binding(),
             }],
-        });
-
-        Self {
-            buffer,
-            bind_group,
-            bind_group_layout,
-        }
-    }
-
-    pub fn set(&self, queue: &wgpu::Queue, camera: &Camera) {
-        let matrix = camera.to_view_projection_matrix();
-        let contents = CameraBufferContents(matrix.into());
-
-        self.buffer.update(queue, &contents);
+        })
     }
 
     #[inline]
     pub(crate) fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
         &self.bind_group_layout
     }
-
-    #[inline]
-    pub(crate) fn bind_group(&self) -> &wgpu::BindGroup {
-        &self.bind_group
-    }
 }
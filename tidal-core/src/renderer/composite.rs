@@ -0,0 +1,133 @@
+use crate::renderer::{RenderGraphContext, RenderGraphError, TextureHandle};
+
+/// A texture blended by a [`CompositePass`], and its contribution to the result.
+#[derive(Debug, Copy, Clone)]
+pub struct CompositeLayer {
+    pub texture: TextureHandle,
+    pub weight: f32,
+}
+
+/// Adds up weighted textures of the same size into a target texture.
+#[derive(Debug)]
+pub struct CompositePass {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl CompositePass {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader_module = device.create_shader_module(wgpu::include_wgsl!("composite.wgsl"));
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("[CompositePass] bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("[CompositePass] pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        // result = layer * weight + result
+        let weighted_add = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::Constant,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("[CompositePass] pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: Default::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState {
+                        color: weighted_add,
+                        alpha: weighted_add,
+                    }),
+                    write_mask: Default::default(),
+                })],
+            }),
+            multiview: None,
+        });
+
+        Self {
+            bind_group_layout,
+            pipeline,
+        }
+    }
+
+    /// Records the weighted sum of the layers into `target`.
+    pub fn record(
+        &self,
+        ctx: &mut RenderGraphContext,
+        layers: &[CompositeLayer],
+        target: TextureHandle,
+    ) -> Result<(), RenderGraphError> {
+        let bind_groups = layers
+            .iter()
+            .map(|layer| {
+                Ok(ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("[CompositePass] bind group"),
+                    layout: &self.bind_group_layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(
+                            ctx.texture_view(layer.texture)?,
+                        ),
+                    }],
+                }))
+            })
+            .collect::<Result<Vec<_>, RenderGraphError>>()?;
+
+        let mut render_pass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Composite"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: ctx.texture_view(target)?,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+
+        for (layer, bind_group) in layers.iter().zip(&bind_groups) {
+            let weight = layer.weight as f64;
+
+            render_pass.set_blend_constant(wgpu::Color {
+                r: weight,
+                g: weight,
+                b: weight,
+                a: weight,
+            });
+            render_pass.set_bind_group(0, bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use std::borrow::Cow;
+
+#[derive(Debug, Clone)]
+pub struct ComputePipelineDescriptor<'a> {
+    pub label: &'a str,
+    pub source: &'a str,
+    pub entry_point: &'a str,
+}
+
+/// A compute shader and the layout of its bind group 0, derived from the shader.
+#[derive(Debug)]
+pub struct ComputePipeline {
+    pipeline: wgpu::ComputePipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl ComputePipeline {
+    pub fn new(device: &wgpu::Device, desc: &ComputePipelineDescriptor) -> Self {
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(desc.label),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(desc.source)),
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some(desc.label),
+            layout: None,
+            module: &module,
+            entry_point: desc.entry_point,
+        });
+
+        let bind_group_layout = pipeline.get_bind_group_layout(0);
+
+        Self {
+            pipeline,
+            bind_group_layout,
+        }
+    }
+
+    #[inline]
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    /// Records a dispatch of the pipeline with the given bind group.
+    pub fn dispatch(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        bind_group: &wgpu::BindGroup,
+        workgroups: u32,
+    ) {
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("[ComputePipeline] dispatch"),
+        });
+
+        compute_pass.set_pipeline(&self.pipeline);
+        compute_pass.set_bind_group(0, bind_group, &[]);
+        compute_pass.dispatch_workgroups(workgroups, 1, 1);
+    }
+}
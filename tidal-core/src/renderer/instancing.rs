@@ -0,0 +1,96 @@
+use std::rc::Rc;
+
+use crate::renderer::{Instance, Mesh, Object};
+
+/// Per-instance data as laid out in the instance vertex buffer.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct InstanceData {
+    pub transform: [[f32; 4]; 4],
+    pub color: [f32; 4],
+}
+
+impl From<Instance> for InstanceData {
+    fn from(instance: Instance) -> Self {
+        Self {
+            transform: instance.transform.into(),
+            color: instance.color.into(),
+        }
+    }
+}
+
+impl InstanceData {
+    #[inline]
+    pub fn buffer_layout() -> wgpu::VertexBufferLayout<'static> {
+        static ATTR_ARRAY: [wgpu::VertexAttribute; 5] = wgpu::vertex_attr_array![
+            3 => Float32x4,
+            4 => Float32x4,
+            5 => Float32x4,
+            6 => Float32x4,
+            7 => Float32x4
+        ];
+
+        wgpu::VertexBufferLayout {
+            array_stride: core::mem::size_of::<InstanceData>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &ATTR_ARRAY,
+        }
+    }
+}
+
+/// A range of the instance buffer drawn with a single mesh.
+#[derive(Debug, Clone)]
+pub struct InstanceBatch {
+    pub mesh: Rc<Mesh>,
+    pub instances: std::ops::Range<u32>,
+}
+
+/// Groups objects sharing the same mesh so each mesh is drawn with a single instanced
+/// draw call, regardless of how many objects reference it.
+#[derive(Debug, Default)]
+pub struct InstanceBatches {
+    pub batches: Vec<InstanceBatch>,
+    pub data: Vec<InstanceData>,
+}
+
+impl InstanceBatches {
+    pub fn new<'a>(objects: impl IntoIterator<Item = &'a Object>) -> Self {
+        // Meshes keep the order in which they first appear.
+        let mut groups: Vec<(Rc<Mesh>, Vec<InstanceData>)> = vec![];
+
+        for object in objects {
+            let position = groups
+                .iter()
+                .position(|(mesh, _)| Rc::ptr_eq(mesh, &object.mesh));
+
+            let index = position.unwrap_or_else(|| {
+                groups.push((object.mesh.clone(), vec![]));
+                groups.len() - 1
+            });
+
+            groups[index]
+                .1
+                .extend(object.iter_instances().map(InstanceData::from));
+        }
+
+        let mut batches = Self::default();
+
+        for (mesh, data) in groups {
+            let start = batches.data.len() as u32;
+            batches.data.extend(data);
+            let end = batches.data.len() as u32;
+
+            batches.batches.push(InstanceBatch {
+                mesh,
+                instances: start..end,
+            });
+        }
+
+        batches
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
@@ -29,6 +29,8 @@ impl Vertex {
 pub struct Mesh {
     buffer: wgpu::Buffer,
     num_vertices: u32,
+    /// CPU-side copy of the vertices, used by operators sampling the mesh surface
+    vertices: Box<[Vertex]>,
 }
 
 #[derive(Debug)]
@@ -47,9 +49,15 @@ impl Mesh {
         Mesh {
             buffer,
             num_vertices: desc.vertices.len() as u32,
+            vertices: desc.vertices.into(),
         }
     }
 
+    #[inline]
+    pub fn vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
     #[inline]
     pub(crate) fn buffer(&self) -> &wgpu::Buffer {
         &self.buffer
@@ -1,18 +1,34 @@
 pub use camera_uniform::*;
+pub use composite::*;
+pub use compute::*;
+pub use instancing::*;
 pub use mesh::*;
+pub use noise_texture::*;
+pub use particles::*;
+pub use render_graph::*;
 pub use renderer::*;
 pub use sampler_cache::*;
 pub use scene::*;
 pub use screen_render_pass::*;
 pub use shader::*;
+pub use shadows::*;
 pub use texture::*;
+pub use tone_mapping::*;
 
 pub mod buffers;
 pub mod camera_uniform;
+pub mod composite;
+pub mod compute;
+pub mod instancing;
 pub mod mesh;
+pub mod noise_texture;
+pub mod particles;
+pub mod render_graph;
 pub mod renderer;
 pub mod sampler_cache;
 pub mod scene;
 pub mod screen_render_pass;
 pub mod shader;
+pub mod shadows;
 pub mod texture;
+pub mod tone_mapping;
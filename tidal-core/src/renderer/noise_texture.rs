@@ -0,0 +1,152 @@
+use cgmath::{Vector2, Vector3};
+use wgpu::util::DeviceExt;
+
+use crate::noise::{Fbm, MAX_OCTAVES};
+use crate::renderer::{RenderGraphContext, Texture, TextureDescriptor, HDR_FORMAT};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct NoiseParams {
+    offset: [f32; 4],
+    kind: u32,
+    seed: u32,
+    octaves: u32,
+    frequency: f32,
+    lacunarity: f32,
+    gain: f32,
+    resolution: [f32; 2],
+}
+
+/// Renders noise into HDR textures, one decorrelated channel in each of red, green and blue.
+///
+/// Texel `(x, y)` holds [`Fbm::sample_vector`] at `((x + 0.5) / width, (y + 0.5) / height, 0)`
+/// plus the offset.
+#[derive(Debug)]
+pub struct NoiseTexturePass {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl NoiseTexturePass {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let shader_module = device.create_shader_module(wgpu::include_wgsl!("noise_texture.wgsl"));
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("[NoiseTexturePass] bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("[NoiseTexturePass] pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("[NoiseTexturePass] pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: Default::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: HDR_FORMAT.into(),
+                    blend: None,
+                    write_mask: Default::default(),
+                })],
+            }),
+            multiview: None,
+        });
+
+        Self {
+            bind_group_layout,
+            pipeline,
+        }
+    }
+
+    /// Creates a texture this pass can render into.
+    pub fn create_texture(device: &wgpu::Device, size: Vector2<u32>) -> Texture {
+        Texture::new(
+            device,
+            &TextureDescriptor {
+                format: HDR_FORMAT.into(),
+                dimensions: size,
+                data: None,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            },
+        )
+    }
+
+    /// Records the rendering of `fbm` offset by `offset` into `target`, which must come from
+    /// [`NoiseTexturePass::create_texture`].
+    pub fn record(
+        &self,
+        ctx: &mut RenderGraphContext,
+        fbm: &Fbm,
+        offset: Vector3<f32>,
+        target: &Texture,
+    ) {
+        let size = target.size();
+
+        let params = NoiseParams {
+            offset: offset.extend(0.0).into(),
+            kind: fbm.kind.index(),
+            seed: fbm.seed,
+            octaves: fbm.octaves.clamp(1, MAX_OCTAVES),
+            frequency: fbm.frequency,
+            lacunarity: fbm.lacunarity,
+            gain: fbm.gain,
+            resolution: [size.x as f32, size.y as f32],
+        };
+
+        let buffer = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("[NoiseTexturePass] params"),
+                contents: bytemuck::bytes_of(&params),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+
+        let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("[NoiseTexturePass] bind group"),
+            layout: &self.bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        let mut render_pass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("NoiseTexture"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target.view(),
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,206 @@
+// GPU port of `crate::noise`, restricted to three dimensions. Both sides hash lattice points the
+// same way so a seed gives the same noise on the CPU and the GPU.
+
+struct NoiseParams {
+    // Domain offset, in texture widths
+    offset: vec4<f32>,
+    kind: u32,
+    seed: u32,
+    octaves: u32,
+    frequency: f32,
+    lacunarity: f32,
+    gain: f32,
+    resolution: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> params: NoiseParams;
+
+const CHANNEL_SEED_STEP: u32 = 0x9E3779B9u;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    // Single triangle covering the whole texture
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+fn hash(x: u32) -> u32 {
+    let state = x * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+
+    return (word >> 22u) ^ word;
+}
+
+fn hash_cell(cell: vec3<i32>, seed: u32) -> u32 {
+    var h = hash(seed);
+    h = hash(h ^ bitcast<u32>(cell.x));
+    h = hash(h ^ bitcast<u32>(cell.y));
+    h = hash(h ^ bitcast<u32>(cell.z));
+
+    return h;
+}
+
+fn unit(h: u32) -> f32 {
+    return f32(h >> 8u) / 16777216.0;
+}
+
+fn random_point(h: u32) -> vec3<f32> {
+    return vec3<f32>(
+        unit(hash(h)),
+        unit(hash(h + 1u)),
+        unit(hash(h + 2u)),
+    ) * 2.0 - 1.0;
+}
+
+fn gradient(cell: vec3<i32>, seed: u32) -> vec3<f32> {
+    let g = random_point(hash_cell(cell, seed));
+    let length_squared = dot(g, g);
+
+    if length_squared > 0.0 {
+        return g / sqrt(length_squared);
+    }
+
+    return g;
+}
+
+fn fade(t: vec3<f32>) -> vec3<f32> {
+    return t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+}
+
+fn perlin(point: vec3<f32>, seed: u32) -> f32 {
+    let base = floor(point);
+    let local = point - base;
+    let weights = fade(local);
+
+    var sum = 0.0;
+    for (var bits = 0u; bits < 8u; bits++) {
+        let corner = vec3<u32>(bits, bits >> 1u, bits >> 2u) & vec3<u32>(1u);
+        let cell = vec3<i32>(base) + vec3<i32>(corner);
+        let offset = local - vec3<f32>(corner);
+        let w = select(1.0 - weights, weights, corner == vec3<u32>(1u));
+
+        sum += w.x * w.y * w.z * dot(gradient(cell, seed), offset);
+    }
+
+    return clamp(sum * 1.55, -1.0, 1.0);
+}
+
+fn value(point: vec3<f32>, seed: u32) -> f32 {
+    let base = floor(point);
+    let weights = fade(point - base);
+
+    var sum = 0.0;
+    for (var bits = 0u; bits < 8u; bits++) {
+        let corner = vec3<u32>(bits, bits >> 1u, bits >> 2u) & vec3<u32>(1u);
+        let cell = vec3<i32>(base) + vec3<i32>(corner);
+        let w = select(1.0 - weights, weights, corner == vec3<u32>(1u));
+
+        sum += w.x * w.y * w.z * (unit(hash_cell(cell, seed)) * 2.0 - 1.0);
+    }
+
+    return sum;
+}
+
+fn simplex(point: vec3<f32>, seed: u32) -> f32 {
+    let skew = 1.0 / 3.0;
+    let unskew = 1.0 / 6.0;
+
+    let s = (point.x + point.y + point.z) * skew;
+    let cell = vec3<i32>(floor(point + s));
+    let t = f32(cell.x + cell.y + cell.z) * unskew;
+    let origin = point - (vec3<f32>(cell) - t);
+
+    // Position of each axis when sorted by decreasing offset, ties keeping the axis order
+    let rank = vec3<u32>(
+        u32(origin.y > origin.x) + u32(origin.z > origin.x),
+        u32(origin.x >= origin.y) + u32(origin.z > origin.y),
+        u32(origin.x >= origin.z) + u32(origin.y >= origin.z),
+    );
+
+    var sum = 0.0;
+    for (var k = 0u; k <= 3u; k++) {
+        let corner = vec3<i32>(rank < vec3<u32>(k));
+        let offset = origin - vec3<f32>(corner) + f32(k) * unskew;
+        let falloff = 0.6 - dot(offset, offset);
+
+        if falloff > 0.0 {
+            let falloff_squared = falloff * falloff;
+
+            sum += falloff_squared * falloff_squared * dot(gradient(cell + corner, seed), offset);
+        }
+    }
+
+    return clamp(sum * 42.0, -1.0, 1.0);
+}
+
+fn worley(point: vec3<f32>, seed: u32) -> f32 {
+    let base = vec3<i32>(floor(point));
+    var closest = 3.40282347e+38;
+
+    for (var z = -1; z <= 1; z++) {
+        for (var y = -1; y <= 1; y++) {
+            for (var x = -1; x <= 1; x++) {
+                let cell = base + vec3<i32>(x, y, z);
+                let feature = random_point(hash_cell(cell, seed));
+                let d = vec3<f32>(cell) + feature * 0.5 + 0.5 - point;
+
+                closest = min(closest, dot(d, d));
+            }
+        }
+    }
+
+    return min(sqrt(closest), 1.0);
+}
+
+fn sample_noise(point: vec3<f32>, seed: u32) -> f32 {
+    switch params.kind {
+        case 1u: {
+            return simplex(point, seed);
+        }
+        case 2u: {
+            return value(point, seed);
+        }
+        case 3u: {
+            return worley(point, seed);
+        }
+        default: {
+            return perlin(point, seed);
+        }
+    }
+}
+
+fn fbm(point: vec3<f32>, seed: u32) -> f32 {
+    var sum = 0.0;
+    var total = 0.0;
+    var amplitude = 1.0;
+    var frequency = params.frequency;
+
+    for (var octave = 0u; octave < params.octaves; octave++) {
+        sum += amplitude * sample_noise(point * frequency, seed + octave);
+        total += amplitude;
+
+        amplitude *= params.gain;
+        frequency *= params.lacunarity;
+    }
+
+    if total > 0.0 {
+        return sum / total;
+    }
+
+    return 0.0;
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let uv = position.xy / params.resolution;
+    let point = vec3<f32>(uv, 0.0) + params.offset.xyz;
+
+    return vec4<f32>(
+        fbm(point, params.seed),
+        fbm(point, params.seed + CHANNEL_SEED_STEP),
+        fbm(point, params.seed + 2u * CHANNEL_SEED_STEP),
+        1.0,
+    );
+}
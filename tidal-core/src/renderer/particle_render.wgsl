@@ -0,0 +1,79 @@
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+    view: mat4x4<f32>,
+};
+
+struct Particle {
+    position: vec3<f32>,
+    age: f32,
+    velocity: vec3<f32>,
+    lifetime: f32,
+}
+
+struct RenderParams {
+    color_start: vec4<f32>,
+    color_end: vec4<f32>,
+    size: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0)
+var<storage, read> particles: array<Particle>;
+
+@group(1) @binding(1)
+var<uniform> params: RenderParams;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) vertex_index: u32,
+    @builtin(instance_index) instance_index: u32,
+) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+    );
+
+    let particle = particles[instance_index];
+    let corner = corners[vertex_index];
+
+    var out: VertexOutput;
+    out.uv = corner;
+
+    // Dead particles are moved out of the clip volume.
+    if particle.age >= particle.lifetime {
+        out.clip_position = vec4<f32>(0.0, 0.0, 2.0, 1.0);
+        out.color = vec4<f32>(0.0);
+        return out;
+    }
+
+    // Camera-facing quad, built from the view matrix' right and up vectors.
+    let right = vec3<f32>(camera.view[0].x, camera.view[1].x, camera.view[2].x);
+    let up = vec3<f32>(camera.view[0].y, camera.view[1].y, camera.view[2].y);
+    let position = particle.position + (right * corner.x + up * corner.y) * params.size.x;
+
+    let life = clamp(particle.age / particle.lifetime, 0.0, 1.0);
+
+    out.clip_position = camera.view_proj * vec4<f32>(position, 1.0);
+    out.color = mix(params.color_start, params.color_end, life);
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let falloff = 1.0 - smoothstep(0.5, 1.0, length(in.uv));
+
+    return vec4<f32>(in.color.rgb, in.color.a * falloff);
+}
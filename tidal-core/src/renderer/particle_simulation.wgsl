@@ -0,0 +1,156 @@
+struct Particle {
+    position: vec3<f32>,
+    age: f32,
+    velocity: vec3<f32>,
+    lifetime: f32,
+}
+
+struct SimulationParams {
+    emitter_position: vec3<f32>,
+    shape: u32,
+    emitter_size: vec3<f32>,
+    emit_start: u32,
+    velocity: vec3<f32>,
+    emit_count: u32,
+    gravity: vec3<f32>,
+    velocity_spread: f32,
+    delta_time: f32,
+    lifetime: f32,
+    drag: f32,
+    turbulence: f32,
+    turbulence_scale: f32,
+    time: f32,
+    seed: u32,
+    capacity: u32,
+}
+
+@group(0) @binding(0)
+var<storage, read_write> particles: array<Particle>;
+
+@group(0) @binding(1)
+var<uniform> params: SimulationParams;
+
+const TAU: f32 = 6.283185307;
+
+fn pcg(v: u32) -> u32 {
+    let state = v * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn random(state: ptr<function, u32>) -> f32 {
+    *state = pcg(*state);
+    return f32(*state) / 4294967295.0;
+}
+
+fn random_unit_vector(state: ptr<function, u32>) -> vec3<f32> {
+    let z = random(state) * 2.0 - 1.0;
+    let angle = random(state) * TAU;
+    let r = sqrt(max(1.0 - z * z, 0.0));
+
+    return vec3<f32>(r * cos(angle), r * sin(angle), z);
+}
+
+fn hash3(p: vec3<i32>) -> f32 {
+    let h = pcg(u32(p.x) ^ pcg(u32(p.y) ^ pcg(u32(p.z) ^ params.seed)));
+    return f32(h) / 4294967295.0 * 2.0 - 1.0;
+}
+
+fn value_noise(p: vec3<f32>) -> f32 {
+    let cell = vec3<i32>(floor(p));
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+
+    let x00 = mix(hash3(cell), hash3(cell + vec3<i32>(1, 0, 0)), u.x);
+    let x10 = mix(hash3(cell + vec3<i32>(0, 1, 0)), hash3(cell + vec3<i32>(1, 1, 0)), u.x);
+    let x01 = mix(hash3(cell + vec3<i32>(0, 0, 1)), hash3(cell + vec3<i32>(1, 0, 1)), u.x);
+    let x11 = mix(hash3(cell + vec3<i32>(0, 1, 1)), hash3(cell + vec3<i32>(1, 1, 1)), u.x);
+
+    return mix(mix(x00, x10, u.y), mix(x01, x11, u.y), u.z);
+}
+
+fn potential(p: vec3<f32>) -> vec3<f32> {
+    return vec3<f32>(
+        value_noise(p),
+        value_noise(p + vec3<f32>(31.416, -47.853, 12.793)),
+        value_noise(p + vec3<f32>(-233.145, -113.408, -185.31)),
+    );
+}
+
+// Divergence-free noise field, computed as the curl of a noise potential.
+fn curl_noise(p: vec3<f32>) -> vec3<f32> {
+    let e = 0.01;
+    let dx = vec3<f32>(e, 0.0, 0.0);
+    let dy = vec3<f32>(0.0, e, 0.0);
+    let dz = vec3<f32>(0.0, 0.0, e);
+
+    let px = (potential(p + dx) - potential(p - dx)) / (2.0 * e);
+    let py = (potential(p + dy) - potential(p - dy)) / (2.0 * e);
+    let pz = (potential(p + dz) - potential(p - dz)) / (2.0 * e);
+
+    return vec3<f32>(py.z - pz.y, pz.x - px.z, px.y - py.x);
+}
+
+fn emit_position(state: ptr<function, u32>) -> vec3<f32> {
+    // Shapes: 0 = point, 1 = sphere, 2 = box, 3 = ring
+    switch params.shape {
+        case 1u: {
+            let radius = pow(random(state), 1.0 / 3.0);
+            return params.emitter_position + random_unit_vector(state) * radius * params.emitter_size;
+        }
+        case 2u: {
+            let r = vec3<f32>(random(state), random(state), random(state)) - 0.5;
+            return params.emitter_position + r * params.emitter_size;
+        }
+        case 3u: {
+            let angle = random(state) * TAU;
+            return params.emitter_position + vec3<f32>(cos(angle), sin(angle), 0.0) * params.emitter_size;
+        }
+        default: {
+            return params.emitter_position;
+        }
+    }
+}
+
+fn spawn(index: u32) -> Particle {
+    var state = pcg(index ^ pcg(params.seed ^ bitcast<u32>(params.time)));
+
+    var particle: Particle;
+    particle.position = emit_position(&state);
+    particle.velocity = params.velocity + random_unit_vector(&state) * params.velocity_spread;
+    particle.age = 0.0;
+    particle.lifetime = params.lifetime * mix(0.75, 1.0, random(&state));
+
+    return particle;
+}
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if index >= params.capacity {
+        return;
+    }
+
+    // Particles are emitted in a ring buffer: the ones in [emit_start, emit_start + emit_count)
+    // are respawned this frame, replacing the oldest ones.
+    let offset = (index + params.capacity - params.emit_start) % params.capacity;
+    if offset < params.emit_count {
+        particles[index] = spawn(index);
+        return;
+    }
+
+    var particle = particles[index];
+    if particle.age >= particle.lifetime {
+        return;
+    }
+
+    let dt = params.delta_time;
+    let turbulence = curl_noise(particle.position * params.turbulence_scale + vec3<f32>(params.time * 0.1)) * params.turbulence;
+
+    particle.velocity += (params.gravity + turbulence) * dt;
+    particle.velocity *= exp(-params.drag * dt);
+    particle.position += particle.velocity * dt;
+    particle.age += dt;
+
+    particles[index] = particle;
+}
@@ -0,0 +1,355 @@
+use crate::graph::OperatorEnum;
+use crate::renderer::{CameraUniform, ComputePipeline, ComputePipelineDescriptor};
+
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Shape particles are emitted from.
+#[derive(OperatorEnum, Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum EmitterShape {
+    #[default]
+    Point,
+    Sphere,
+    Box,
+    Ring,
+}
+
+impl EmitterShape {
+    #[inline]
+    fn index(&self) -> u32 {
+        match self {
+            EmitterShape::Point => 0,
+            EmitterShape::Sphere => 1,
+            EmitterShape::Box => 2,
+            EmitterShape::Ring => 3,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct ParticleData {
+    position: [f32; 3],
+    age: f32,
+    velocity: [f32; 3],
+    lifetime: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct SimulationParams {
+    emitter_position: [f32; 3],
+    shape: u32,
+    emitter_size: [f32; 3],
+    emit_start: u32,
+    velocity: [f32; 3],
+    emit_count: u32,
+    gravity: [f32; 3],
+    velocity_spread: f32,
+    delta_time: f32,
+    lifetime: f32,
+    drag: f32,
+    turbulence: f32,
+    turbulence_scale: f32,
+    time: f32,
+    seed: u32,
+    capacity: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct RenderParams {
+    color_start: [f32; 4],
+    color_end: [f32; 4],
+    size: [f32; 4],
+}
+
+/// Parameters of a single simulation step.
+#[derive(Debug, Copy, Clone)]
+pub struct ParticleSimulationStep {
+    pub shape: EmitterShape,
+    pub emitter_position: cgmath::Vector3<f32>,
+    pub emitter_size: cgmath::Vector3<f32>,
+    /// First particle of the ring buffer respawned during this step
+    pub emit_start: u32,
+    /// Amount of particles respawned during this step
+    pub emit_count: u32,
+    pub velocity: cgmath::Vector3<f32>,
+    pub velocity_spread: f32,
+    pub gravity: cgmath::Vector3<f32>,
+    pub drag: f32,
+    pub turbulence: f32,
+    pub turbulence_scale: f32,
+    pub lifetime: f32,
+    pub delta_time: f32,
+    pub time: f32,
+    pub seed: u32,
+}
+
+/// How live particles are drawn.
+#[derive(Debug, Copy, Clone)]
+pub struct ParticleAppearance {
+    pub color_start: cgmath::Vector4<f32>,
+    pub color_end: cgmath::Vector4<f32>,
+    pub size: f32,
+}
+
+/// GPU state of a particle system. Particles live in a storage buffer simulated by a
+/// compute shader and are drawn as camera-facing billboards by the scene pass.
+#[derive(Debug)]
+pub struct ParticleSystem {
+    capacity: u32,
+    simulation: ComputePipeline,
+    simulation_params: wgpu::Buffer,
+    simulation_bind_group: wgpu::BindGroup,
+    particles: wgpu::Buffer,
+    render_params: wgpu::Buffer,
+}
+
+impl ParticleSystem {
+    /// Whether the device can simulate and draw particles, which needs compute shaders and
+    /// storage buffers. WebGL2 level devices have neither.
+    pub fn is_supported(device: &wgpu::Device) -> bool {
+        let limits = device.limits();
+
+        limits.max_storage_buffers_per_shader_stage > 0
+            && limits.max_compute_workgroup_size_x >= WORKGROUP_SIZE
+            && limits.max_compute_invocations_per_workgroup >= WORKGROUP_SIZE
+            && limits.max_compute_workgroups_per_dimension > 0
+    }
+
+    pub fn new(device: &wgpu::Device, capacity: u32) -> Self {
+        let capacity = capacity.max(1);
+
+        let simulation = ComputePipeline::new(
+            device,
+            &ComputePipelineDescriptor {
+                label: "[ParticleSystem] simulation",
+                source: include_str!("particle_simulation.wgsl"),
+                entry_point: "cs_main",
+            },
+        );
+
+        // Buffers are zero-initialized, which leaves every particle dead.
+        let particles = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("[ParticleSystem] particles"),
+            size: (std::mem::size_of::<ParticleData>() * capacity as usize) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+
+        let simulation_params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("[ParticleSystem] simulation params"),
+            size: std::mem::size_of::<SimulationParams>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let render_params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("[ParticleSystem] render params"),
+            size: std::mem::size_of::<RenderParams>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let simulation_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("[ParticleSystem] simulation bind group"),
+            layout: simulation.bind_group_layout(),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: particles.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: simulation_params.as_entire_binding(),
+                },
+            ],
+        });
+
+        Self {
+            capacity,
+            simulation,
+            simulation_params,
+            simulation_bind_group,
+            particles,
+            render_params,
+        }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Records a simulation step, respawning particles and integrating the live ones.
+    pub fn simulate(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        step: &ParticleSimulationStep,
+    ) {
+        let params = SimulationParams {
+            emitter_position: step.emitter_position.into(),
+            shape: step.shape.index(),
+            emitter_size: step.emitter_size.into(),
+            emit_start: step.emit_start % self.capacity,
+            velocity: step.velocity.into(),
+            emit_count: step.emit_count.min(self.capacity),
+            gravity: step.gravity.into(),
+            velocity_spread: step.velocity_spread,
+            delta_time: step.delta_time,
+            lifetime: step.lifetime,
+            drag: step.drag,
+            turbulence: step.turbulence,
+            turbulence_scale: step.turbulence_scale,
+            time: step.time,
+            seed: step.seed,
+            capacity: self.capacity,
+        };
+
+        queue.write_buffer(&self.simulation_params, 0, bytemuck::bytes_of(&params));
+
+        let workgroups = self.capacity.div_ceil(WORKGROUP_SIZE);
+        self.simulation
+            .dispatch(encoder, &self.simulation_bind_group, workgroups);
+    }
+
+    pub fn set_appearance(&self, queue: &wgpu::Queue, appearance: &ParticleAppearance) {
+        let params = RenderParams {
+            color_start: appearance.color_start.into(),
+            color_end: appearance.color_end.into(),
+            size: [appearance.size, 0.0, 0.0, 0.0],
+        };
+
+        queue.write_buffer(&self.render_params, 0, bytemuck::bytes_of(&params));
+    }
+}
+
+/// Draws particle systems as additive camera-facing billboards.
+#[derive(Debug)]
+pub struct ParticleRenderer {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl ParticleRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        camera_uniform: &CameraUniform,
+        format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        multisample: wgpu::MultisampleState,
+    ) -> Self {
+        let shader_module =
+            device.create_shader_module(wgpu::include_wgsl!("particle_render.wgsl"));
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("[ParticleRenderer] bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("[ParticleRenderer] pipeline layout"),
+            bind_group_layouts: &[camera_uniform.bind_group_layout(), &bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let additive = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::SrcAlpha,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("[ParticleRenderer] pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: Default::default(),
+            // Particles are hidden by opaque geometry but don't occlude each other.
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: Default::default(),
+                bias: Default::default(),
+            }),
+            multisample,
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState {
+                        color: additive,
+                        alpha: additive,
+                    }),
+                    write_mask: Default::default(),
+                })],
+            }),
+            multiview: None,
+        });
+
+        Self {
+            bind_group_layout,
+            pipeline,
+        }
+    }
+
+    pub fn create_bind_group(
+        &self,
+        device: &wgpu::Device,
+        system: &ParticleSystem,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("[ParticleRenderer] bind group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: system.particles.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: system.render_params.as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    /// Draws a particle system. The camera must already be bound to group 0.
+    pub fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        system: &ParticleSystem,
+        bind_group: &'a wgpu::BindGroup,
+    ) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(1, bind_group, &[]);
+        render_pass.draw(0..6, 0..system.capacity());
+    }
+}
@@ -0,0 +1,557 @@
+use std::collections::HashMap;
+
+use cgmath::Vector2;
+
+use crate::renderer::{CameraUniform, SamplerCache};
+
+/// Handle to a texture declared in a [`RenderGraph`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct TextureHandle(usize);
+
+/// Size of a transient texture.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum TextureSize {
+    /// Same size as the frame's render target
+    Target,
+    /// Fixed size in pixels
+    Absolute(u32, u32),
+}
+
+impl TextureSize {
+    #[inline]
+    fn resolve(&self, target_size: Vector2<u32>) -> Vector2<u32> {
+        match *self {
+            TextureSize::Target => target_size,
+            TextureSize::Absolute(width, height) => Vector2::new(width, height),
+        }
+    }
+}
+
+/// Describes a texture that only lives during a single frame. Its contents are
+/// undefined until the first pass writing to it runs.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct TransientTextureDescriptor {
+    pub label: &'static str,
+    pub size: TextureSize,
+    pub format: wgpu::TextureFormat,
+    pub sample_count: u32,
+}
+
+#[derive(thiserror::Error, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RenderGraphError {
+    #[error("render graph contains a dependency cycle")]
+    Cycle,
+    #[error("texture handle {0:?} is not declared in this render graph")]
+    InvalidHandle(TextureHandle),
+    #[error("texture handle {0:?} is not alive during this pass")]
+    NotAlive(TextureHandle),
+}
+
+/// Everything a pass may use while recording its commands.
+pub struct RenderGraphContext<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    pub encoder: &'a mut wgpu::CommandEncoder,
+    pub sampler_cache: &'a mut SamplerCache,
+    pub camera_uniform: &'a CameraUniform,
+    pub surface_format: wgpu::TextureFormat,
+    pub target_size: Vector2<u32>,
+    views: Vec<Option<&'a wgpu::TextureView>>,
+}
+
+impl<'a> RenderGraphContext<'a> {
+    /// Returns the view of a texture declared as read or written by the pass.
+    ///
+    /// Fails if the texture isn't declared in the graph or isn't alive while the pass is
+    /// executing.
+    pub fn texture_view(
+        &self,
+        handle: TextureHandle,
+    ) -> Result<&'a wgpu::TextureView, RenderGraphError> {
+        self.views
+            .get(handle.0)
+            .ok_or(RenderGraphError::InvalidHandle(handle))?
+            .ok_or(RenderGraphError::NotAlive(handle))
+    }
+}
+
+/// A unit of GPU work scheduled by a [`RenderGraph`].
+pub trait RenderGraphPass {
+    fn execute(&self, ctx: &mut RenderGraphContext) -> Result<(), RenderGraphError>;
+}
+
+impl<F> RenderGraphPass for F
+where
+    F: Fn(&mut RenderGraphContext) -> Result<(), RenderGraphError>,
+{
+    #[inline]
+    fn execute(&self, ctx: &mut RenderGraphContext) -> Result<(), RenderGraphError> {
+        self(ctx)
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+enum VirtualTexture {
+    Target,
+    Transient(TransientTextureDescriptor),
+}
+
+struct PassNode {
+    name: &'static str,
+    reads: Vec<TextureHandle>,
+    writes: Vec<TextureHandle>,
+    pass: Box<dyn RenderGraphPass>,
+}
+
+/// Declares the reads and writes of a pass added to a [`RenderGraph`].
+pub struct PassBuilder<'a> {
+    node: &'a mut PassNode,
+}
+
+impl<'a> PassBuilder<'a> {
+    /// The pass samples or otherwise reads from the texture.
+    pub fn read(self, handle: TextureHandle) -> Self {
+        self.node.reads.push(handle);
+        self
+    }
+
+    /// The pass renders into the texture.
+    pub fn write(self, handle: TextureHandle) -> Self {
+        self.node.writes.push(handle);
+        self
+    }
+}
+
+/// A frame-level graph of render passes.
+///
+/// Passes declare the textures they read and write. Once every pass has been added,
+/// the graph is ordered so every writer of a texture runs before its readers,
+/// transient textures are allocated (reusing the same GPU texture for textures whose
+/// lifetimes don't overlap) and everything is recorded into a single command encoder.
+pub struct RenderGraph {
+    textures: Vec<VirtualTexture>,
+    passes: Vec<PassNode>,
+}
+
+impl Default for RenderGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Result of ordering a [`RenderGraph`]: the order in which passes run and, for each
+/// transient texture, the interval of scheduled passes during which it's alive.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RenderGraphSchedule {
+    pub order: Vec<usize>,
+    lifetimes: Vec<Option<(usize, usize)>>,
+}
+
+/// Physical textures backing the transient textures of a [`RenderGraphSchedule`]. Transient
+/// textures with the same description whose lifetimes don't overlap share a texture.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TextureAllocation {
+    /// Physical texture of each declared texture, `None` for the target and unused textures
+    assignments: Vec<Option<usize>>,
+    textures: Vec<(&'static str, TransientTextureKey)>,
+}
+
+impl TextureAllocation {
+    /// Index of the physical texture backing `handle`.
+    #[inline]
+    pub fn texture(&self, handle: TextureHandle) -> Option<usize> {
+        self.assignments.get(handle.0).copied().flatten()
+    }
+
+    /// Number of physical textures needed.
+    #[inline]
+    pub fn texture_count(&self) -> usize {
+        self.textures.len()
+    }
+}
+
+impl RenderGraph {
+    pub fn new() -> Self {
+        Self {
+            textures: vec![VirtualTexture::Target],
+            passes: vec![],
+        }
+    }
+
+    /// The frame's render target.
+    #[inline]
+    pub fn target(&self) -> TextureHandle {
+        TextureHandle(0)
+    }
+
+    pub fn create_texture(&mut self, desc: TransientTextureDescriptor) -> TextureHandle {
+        self.textures.push(VirtualTexture::Transient(desc));
+        TextureHandle(self.textures.len() - 1)
+    }
+
+    pub fn add_pass(
+        &mut self,
+        name: &'static str,
+        pass: impl RenderGraphPass + 'static,
+    ) -> PassBuilder<'_> {
+        self.passes.push(PassNode {
+            name,
+            reads: vec![],
+            writes: vec![],
+            pass: Box::new(pass),
+        });
+
+        PassBuilder {
+            node: self.passes.last_mut().unwrap(),
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.passes.is_empty()
+    }
+
+    /// Orders the passes and computes the lifetime of every transient texture.
+    ///
+    /// A pass reading a texture depends on every pass writing it, so passes may be added
+    /// in any order. Passes writing the same texture keep the order in which they were
+    /// added, as do independent passes.
+    pub fn schedule(&self) -> Result<RenderGraphSchedule, RenderGraphError> {
+        let mut writers: Vec<Vec<usize>> = vec![vec![]; self.textures.len()];
+
+        for (index, pass) in self.passes.iter().enumerate() {
+            for handle in pass.reads.iter().chain(pass.writes.iter()) {
+                if handle.0 >= self.textures.len() {
+                    return Err(RenderGraphError::InvalidHandle(*handle));
+                }
+            }
+
+            for handle in pass.writes.iter() {
+                writers[handle.0].push(index);
+            }
+        }
+
+        let mut dependents: Vec<Vec<usize>> = vec![vec![]; self.passes.len()];
+        let mut pending: Vec<usize> = vec![0; self.passes.len()];
+
+        let mut add_edge = |from: usize, to: usize| {
+            if from != to && !dependents[from].contains(&to) {
+                dependents[from].push(to);
+                pending[to] += 1;
+            }
+        };
+
+        for texture_writers in writers.iter() {
+            for pair in texture_writers.windows(2) {
+                add_edge(pair[0], pair[1]);
+            }
+        }
+
+        for (index, pass) in self.passes.iter().enumerate() {
+            for handle in pass.reads.iter() {
+                // Passes that read and write the same texture only see what was written
+                // before them; their own write already orders them against later writers.
+                let read_modify_write = pass.writes.contains(handle);
+
+                for writer in writers[handle.0].iter() {
+                    if !read_modify_write || *writer < index {
+                        add_edge(*writer, index);
+                    }
+                }
+            }
+        }
+
+        // Kahn's algorithm, always picking the earliest added pass that's ready.
+        let mut order = Vec::with_capacity(self.passes.len());
+        let mut ready: Vec<usize> = (0..self.passes.len())
+            .filter(|index| pending[*index] == 0)
+            .collect();
+
+        while let Some(position) = ready
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, index)| **index)
+            .map(|(position, _)| position)
+        {
+            let index = ready.swap_remove(position);
+            order.push(index);
+
+            for dependent in dependents[index].iter() {
+                pending[*dependent] -= 1;
+                if pending[*dependent] == 0 {
+                    ready.push(*dependent);
+                }
+            }
+        }
+
+        if order.len() != self.passes.len() {
+            return Err(RenderGraphError::Cycle);
+        }
+
+        let mut lifetimes: Vec<Option<(usize, usize)>> = vec![None; self.textures.len()];
+        for (step, index) in order.iter().enumerate() {
+            let pass = &self.passes[*index];
+
+            for handle in pass.reads.iter().chain(pass.writes.iter()) {
+                let lifetime = lifetimes[handle.0].get_or_insert((step, step));
+                lifetime.1 = step;
+            }
+        }
+
+        Ok(RenderGraphSchedule { order, lifetimes })
+    }
+
+    /// Assigns a physical texture to every transient texture of `schedule`. Textures are
+    /// taken when their lifetime starts and given back once it ends, so later textures with
+    /// the same description alias the same GPU memory.
+    pub fn allocate(
+        &self,
+        schedule: &RenderGraphSchedule,
+        target_size: Vector2<u32>,
+    ) -> TextureAllocation {
+        let mut allocation = TextureAllocation {
+            assignments: vec![None; self.textures.len()],
+            textures: vec![],
+        };
+        let mut in_use: Vec<bool> = vec![];
+
+        for step in 0..schedule.order.len() {
+            for (handle, texture) in self.textures.iter().enumerate() {
+                let VirtualTexture::Transient(desc) = texture else {
+                    continue;
+                };
+
+                if schedule.lifetimes[handle].map(|(first, _)| first) != Some(step) {
+                    continue;
+                }
+
+                let key = TransientTextureKey::new(desc, target_size);
+                let free = allocation
+                    .textures
+                    .iter()
+                    .zip(&in_use)
+                    .position(|((_, texture_key), in_use)| *texture_key == key && !in_use);
+
+                let slot = free.unwrap_or_else(|| {
+                    allocation.textures.push((desc.label, key));
+                    in_use.push(false);
+                    in_use.len() - 1
+                });
+
+                in_use[slot] = true;
+                allocation.assignments[handle] = Some(slot);
+            }
+
+            for (handle, lifetime) in schedule.lifetimes.iter().enumerate() {
+                if let (Some((_, last)), Some(slot)) = (lifetime, allocation.assignments[handle]) {
+                    if *last == step {
+                        in_use[slot] = false;
+                    }
+                }
+            }
+        }
+
+        allocation
+    }
+
+    /// Executes every pass in a single command encoder and submits it.
+    pub(crate) fn execute(
+        self,
+        context: RenderGraphExecutionContext,
+    ) -> Result<(), RenderGraphError> {
+        let schedule = self.schedule()?;
+
+        let RenderGraphExecutionContext {
+            device,
+            queue,
+            sampler_cache,
+            camera_uniform,
+            surface_format,
+            texture_pool,
+            target,
+            target_size,
+        } = context;
+
+        let allocation = self.allocate(&schedule, target_size);
+
+        texture_pool.begin_frame();
+        let slots: Vec<usize> = allocation
+            .textures
+            .iter()
+            .map(|(label, key)| texture_pool.acquire(device, label, *key))
+            .collect();
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("[RenderGraph] encoder"),
+        });
+
+        for (step, index) in schedule.order.iter().enumerate() {
+            let node = &self.passes[*index];
+
+            let views = self
+                .textures
+                .iter()
+                .enumerate()
+                .map(|(handle, texture)| match texture {
+                    VirtualTexture::Target => Some(target),
+                    VirtualTexture::Transient(_) => {
+                        let (first, last) = schedule.lifetimes[handle]?;
+                        if step < first || step > last {
+                            return None;
+                        }
+
+                        texture_pool.view(slots[allocation.assignments[handle]?])
+                    }
+                })
+                .collect();
+
+            encoder.push_debug_group(node.name);
+
+            node.pass.execute(&mut RenderGraphContext {
+                device,
+                queue,
+                encoder: &mut encoder,
+                sampler_cache,
+                camera_uniform,
+                surface_format,
+                target_size,
+                views,
+            })?;
+
+            encoder.pop_debug_group();
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+        texture_pool.end_frame();
+
+        Ok(())
+    }
+}
+
+pub(crate) struct RenderGraphExecutionContext<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    pub sampler_cache: &'a mut SamplerCache,
+    pub camera_uniform: &'a CameraUniform,
+    pub surface_format: wgpu::TextureFormat,
+    pub texture_pool: &'a mut TransientTexturePool,
+    pub target: &'a wgpu::TextureView,
+    pub target_size: Vector2<u32>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+struct TransientTextureKey {
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    sample_count: u32,
+}
+
+impl TransientTextureKey {
+    fn new(desc: &TransientTextureDescriptor, target_size: Vector2<u32>) -> Self {
+        let size = desc.size.resolve(target_size);
+
+        Self {
+            format: desc.format,
+            width: size.x.max(1),
+            height: size.y.max(1),
+            sample_count: desc.sample_count.max(1),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PooledTexture {
+    key: TransientTextureKey,
+    view: wgpu::TextureView,
+    in_use: bool,
+    used_this_frame: bool,
+}
+
+/// Physical textures backing the transient textures of a [`RenderGraph`].
+///
+/// Textures are kept across frames and only dropped after a frame that didn't use them.
+#[derive(Debug, Default)]
+pub(crate) struct TransientTexturePool {
+    textures: Vec<PooledTexture>,
+    by_key: HashMap<TransientTextureKey, Vec<usize>>,
+}
+
+impl TransientTexturePool {
+    fn begin_frame(&mut self) {
+        for texture in self.textures.iter_mut() {
+            texture.in_use = false;
+            texture.used_this_frame = false;
+        }
+    }
+
+    fn acquire(
+        &mut self,
+        device: &wgpu::Device,
+        label: &'static str,
+        key: TransientTextureKey,
+    ) -> usize {
+        let slots = self.by_key.entry(key).or_default();
+
+        let free = slots
+            .iter()
+            .copied()
+            .find(|slot| !self.textures[*slot].in_use);
+
+        let slot = free.unwrap_or_else(|| {
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: key.width,
+                    height: key.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: key.sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format: key.format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            });
+
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+            self.textures.push(PooledTexture {
+                key,
+                view,
+                in_use: false,
+                used_this_frame: false,
+            });
+
+            let slot = self.textures.len() - 1;
+            slots.push(slot);
+            slot
+        });
+
+        let texture = &mut self.textures[slot];
+        texture.in_use = true;
+        texture.used_this_frame = true;
+
+        slot
+    }
+
+    #[inline]
+    fn view(&self, slot: usize) -> Option<&wgpu::TextureView> {
+        self.textures.get(slot).map(|texture| &texture.view)
+    }
+
+    /// Drops the textures the last frame didn't need.
+    fn end_frame(&mut self) {
+        if self.textures.iter().all(|texture| texture.used_this_frame) {
+            return;
+        }
+
+        self.textures.retain(|texture| texture.used_this_frame);
+        self.by_key.clear();
+
+        for (slot, texture) in self.textures.iter().enumerate() {
+            self.by_key.entry(texture.key).or_default().push(slot);
+        }
+    }
+}
@@ -1,11 +1,14 @@
 use std::borrow::Cow;
 
+use cgmath::Vector2;
 use wgpu::TextureFormat;
 
 use crate::renderer::{
-    CameraUniform, CommandList, Mesh, MeshDescriptor, RenderPass, RenderPassContext,
-    RenderPassFactory, SamplerCache, SamplerDescriptor, Shader, ShaderModuleDescriptor, Texture,
-    TextureDescriptor,
+    CameraUniform, CompositePass, ComputePipeline, ComputePipelineDescriptor, Mesh, MeshDescriptor,
+    NoiseTexturePass, ParticleSystem, RenderGraph, RenderGraphError, RenderGraphExecutionContext,
+    RenderPassContext, RenderPassFactory, SampleCount, SamplerCache, SamplerDescriptor,
+    ScreenRenderPass, Shader, ShaderModuleDescriptor, Texture, TextureDescriptor, ToneMappingPass,
+    TransientTexturePool, HDR_FORMAT,
 };
 
 pub trait Context {
@@ -18,6 +21,7 @@ pub struct Renderer {
     context: Box<dyn Context + Send + Sync>,
     sampler_cache: SamplerCache,
     camera_uniform: CameraUniform,
+    texture_pool: TransientTexturePool,
 }
 
 impl Renderer {
@@ -30,6 +34,7 @@ impl Renderer {
             context,
             sampler_cache: Default::default(),
             camera_uniform,
+            texture_pool: Default::default(),
         }
     }
 
@@ -56,19 +61,100 @@ impl Renderer {
         Shader::new(device, Cow::Borrowed(desc.source))
     }
 
+    pub fn create_compute_pipeline(&mut self, desc: &ComputePipelineDescriptor) -> ComputePipeline {
+        let device = self.context.device();
+
+        ComputePipeline::new(device, desc)
+    }
+
+    /// Creates a particle system, or returns `None` when the device doesn't support them.
+    pub fn create_particle_system(&mut self, capacity: u32) -> Option<ParticleSystem> {
+        let device = self.context.device();
+
+        ParticleSystem::is_supported(device).then(|| ParticleSystem::new(device, capacity))
+    }
+
+    pub fn create_noise_texture_pass(&mut self) -> NoiseTexturePass {
+        let device = self.context.device();
+
+        NoiseTexturePass::new(device)
+    }
+
+    /// Creates a texture the noise texture pass can render into.
+    pub fn create_noise_texture(&mut self, size: Vector2<u32>) -> Texture {
+        let device = self.context.device();
+
+        NoiseTexturePass::create_texture(device, size)
+    }
+
+    /// Creates a scene pass using the closest sample count the device supports, logging when
+    /// it differs from `sample_count`.
+    pub fn create_screen_render_pass(&mut self, sample_count: SampleCount) -> ScreenRenderPass {
+        let supported = self.supported_sample_count(sample_count);
+
+        if supported != sample_count.count() {
+            log::warn!(
+                "{} samples aren't supported by this device, rendering scenes with {supported}",
+                sample_count.count()
+            );
+        }
+
+        ScreenRenderPass::with_sample_count(self.render_pass_context(), supported)
+    }
+
+    /// Number of samples used for `sample_count` on this device.
+    ///
+    /// Only 1 and 4 samples are guaranteed for every format, so 2 and 8 samples fall back to
+    /// 4 samples unless the device exposes adapter specific format features.
+    pub fn supported_sample_count(&self, sample_count: SampleCount) -> u32 {
+        let features = self.context.device().features();
+
+        match sample_count {
+            SampleCount::X2 | SampleCount::X8
+                if !features.contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES) =>
+            {
+                4
+            }
+            _ => sample_count.count(),
+        }
+    }
+
+    /// Creates a composite pass blending HDR scene textures.
+    pub fn create_composite_pass(&mut self) -> CompositePass {
+        let device = self.context.device();
+
+        CompositePass::new(device, HDR_FORMAT.into())
+    }
+
+    /// Creates a tone mapping pass writing into textures of the surface format.
+    pub fn create_tone_mapping_pass(&mut self) -> ToneMappingPass {
+        let device = self.context.device();
+
+        ToneMappingPass::new(device, self.context.surface_format())
+    }
+
     #[deprecated]
     pub fn create_render_pass<R: RenderPassFactory>(&mut self) -> R::Output {
         R::new(self.render_pass_context())
     }
 
-    #[deprecated]
-    pub fn render<R: RenderPass>(
+    /// Records every pass of the render graph and submits them at once.
+    pub fn execute(
         &mut self,
-        render_pass: &mut R,
-        command_list: &CommandList,
-        attributes: R::Attributes<'_>,
-    ) {
-        render_pass.render(command_list, self.render_pass_context(), attributes);
+        render_graph: RenderGraph,
+        target: &wgpu::TextureView,
+        target_size: Vector2<u32>,
+    ) -> Result<(), RenderGraphError> {
+        render_graph.execute(RenderGraphExecutionContext {
+            device: self.context.device(),
+            queue: self.context.queue(),
+            sampler_cache: &mut self.sampler_cache,
+            camera_uniform: &self.camera_uniform,
+            surface_format: self.context.surface_format(),
+            texture_pool: &mut self.texture_pool,
+            target,
+            target_size,
+        })
     }
 
     #[inline(always)]
@@ -1,11 +1,11 @@
 use std::rc::Rc;
 
-use cgmath::{Matrix4, Point3, SquareMatrix, Vector3};
+use cgmath::{InnerSpace, Matrix4, Point3, SquareMatrix, Transform, Vector3, Vector4};
 
-use crate::renderer::Mesh;
+use crate::renderer::{Mesh, ParticleSystem};
 
 #[rustfmt::skip]
-const OPENGL_TO_WGPU_MATRIX: Matrix4<f32> = Matrix4::new(
+pub(crate) const OPENGL_TO_WGPU_MATRIX: Matrix4<f32> = Matrix4::new(
     1.0, 0.0, 0.0, 0.0,
     0.0, 1.0, 0.0, 0.0,
     0.0, 0.0, 0.5, 0.5,
@@ -38,8 +38,13 @@ impl Default for Camera {
 }
 
 impl Camera {
+    #[inline]
+    pub fn to_view_matrix(&self) -> Matrix4<f32> {
+        Matrix4::look_at_rh(self.eye, self.target, self.up)
+    }
+
     pub fn to_view_projection_matrix(&self) -> Matrix4<f32> {
-        let view = Matrix4::look_at_rh(self.eye, self.target, self.up);
+        let view = self.to_view_matrix();
         let proj = cgmath::perspective(
             cgmath::Deg(self.fov_y),
             self.aspect,
@@ -60,6 +65,10 @@ pub enum Command {
     // Entities
     SetCamera(Camera),
     AddObject(Rc<Mesh>),
+    /// Adds several copies of a mesh drawn with a single instanced draw call
+    AddInstancedObject(Rc<Mesh>, Rc<[Instance]>),
+    AddParticles(Rc<ParticleSystem>),
+    AddLight(Light),
     ResetTransform,
 }
 
@@ -71,22 +80,129 @@ impl From<Command> for CommandList {
     }
 }
 
+/// A single copy of an instanced mesh, relative to the object's transform.
+#[derive(Debug, Copy, Clone)]
+pub struct Instance {
+    pub transform: Matrix4<f32>,
+    pub color: Vector4<f32>,
+}
+
+impl Default for Instance {
+    fn default() -> Self {
+        Self {
+            transform: Matrix4::identity(),
+            color: Vector4::new(1.0, 1.0, 1.0, 1.0),
+        }
+    }
+}
+
+/// Shadow casting settings of a light.
+#[derive(Debug, Copy, Clone)]
+pub struct ShadowSettings {
+    /// Depth offset applied when comparing against the shadow map, to avoid shadow acne
+    pub bias: f32,
+    /// Size in pixels of each of the light's shadow maps
+    pub resolution: u32,
+    /// Amount of shadow maps the view frustum is split into. Only used by directional lights.
+    pub cascades: u32,
+    /// Distance from the camera covered by the cascades. Only used by directional lights.
+    pub distance: f32,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum LightKind {
+    Directional {
+        direction: Vector3<f32>,
+    },
+    Spot {
+        position: Point3<f32>,
+        direction: Vector3<f32>,
+        range: f32,
+        /// Half angle, in radians, where the cone starts fading out
+        inner_angle: f32,
+        /// Half angle, in radians, of the cone
+        outer_angle: f32,
+    },
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Light {
+    pub kind: LightKind,
+    pub color: Vector3<f32>,
+    pub intensity: f32,
+    /// Shadow settings. Lights without them don't cast shadows.
+    pub shadow: Option<ShadowSettings>,
+}
+
+impl Light {
+    /// Moves the light to the space described by the transform.
+    pub fn transformed(&self, transform: &Matrix4<f32>) -> Self {
+        let kind = match self.kind {
+            LightKind::Directional { direction } => LightKind::Directional {
+                direction: transform.transform_vector(direction).normalize(),
+            },
+            LightKind::Spot {
+                position,
+                direction,
+                range,
+                inner_angle,
+                outer_angle,
+            } => LightKind::Spot {
+                position: transform.transform_point(position),
+                direction: transform.transform_vector(direction).normalize(),
+                range,
+                inner_angle,
+                outer_angle,
+            },
+        };
+
+        Self { kind, ..*self }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Object {
     pub mesh: Rc<Mesh>,
     pub transform: Matrix4<f32>,
+    /// Instances to draw. Objects without instances are drawn once.
+    pub instances: Option<Rc<[Instance]>>,
+}
+
+impl Object {
+    /// Iterates over the world transform and color of every copy of this object.
+    pub fn iter_instances(&self) -> impl Iterator<Item = Instance> + '_ {
+        let single = self.instances.is_none().then(Instance::default);
+        let instances = self.instances.iter().flat_map(|instances| instances.iter());
+
+        single
+            .into_iter()
+            .chain(instances.copied())
+            .map(|instance| Instance {
+                transform: self.transform * instance.transform,
+                color: instance.color,
+            })
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct CommandList {
     transform_stack: Vec<Matrix4<f32>>,
     pub objects: Vec<Object>,
+    pub particles: Vec<Rc<ParticleSystem>>,
+    pub lights: Vec<Light>,
     pub camera: Camera,
 }
 
+/// Merges the lists, keeping the camera of the first one.
 impl FromIterator<CommandList> for CommandList {
     fn from_iter<T: IntoIterator<Item = CommandList>>(iter: T) -> Self {
-        let mut command_list = CommandList::new();
+        let mut iter = iter.into_iter();
+        let mut command_list = iter.next().unwrap_or_else(CommandList::new);
+
+        for other in iter {
+            command_list.merge(other);
+        }
+
         command_list
     }
 }
@@ -96,10 +212,31 @@ impl CommandList {
         Self {
             transform_stack: vec![Matrix4::identity()],
             objects: vec![],
+            particles: vec![],
+            lights: vec![],
             camera: Default::default(),
         }
     }
 
+    /// Moves the objects and lights of the list. Particles are simulated in world space and
+    /// stay in place.
+    pub fn transform(&mut self, transform: &Matrix4<f32>) {
+        for object in self.objects.iter_mut() {
+            object.transform = transform * object.transform;
+        }
+
+        for light in self.lights.iter_mut() {
+            *light = light.transformed(transform);
+        }
+    }
+
+    /// Adds the objects, particles and lights of `other`, already placed by its transforms.
+    pub fn merge(&mut self, other: CommandList) {
+        self.objects.extend(other.objects);
+        self.particles.extend(other.particles);
+        self.lights.extend(other.lights);
+    }
+
     pub fn add(&mut self, c: Command) {
         match c {
             Command::Translate(translation) => self
@@ -113,7 +250,26 @@ impl CommandList {
             Command::AddObject(mesh) => {
                 let transform = *self.transform_stack.last().unwrap();
 
-                self.objects.push(Object { mesh, transform });
+                self.objects.push(Object {
+                    mesh,
+                    transform,
+                    instances: None,
+                });
+            }
+            Command::AddInstancedObject(mesh, instances) => {
+                let transform = *self.transform_stack.last().unwrap();
+
+                self.objects.push(Object {
+                    mesh,
+                    transform,
+                    instances: Some(instances),
+                });
+            }
+            Command::AddParticles(system) => self.particles.push(system),
+            Command::AddLight(light) => {
+                let transform = self.transform_stack.last().unwrap();
+
+                self.lights.push(light.transformed(transform));
             }
             Command::ResetTransform => {
                 self.transform_stack.clear();
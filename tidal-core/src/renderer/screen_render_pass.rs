@@ -1,6 +1,41 @@
-use wgpu::{include_wgsl, RenderPassDescriptor};
+use std::cell::OnceCell;
 
-use crate::renderer::{CameraUniform, CommandList, SamplerCache, Vertex};
+use wgpu::include_wgsl;
+use wgpu::util::DeviceExt;
+
+use crate::graph::OperatorEnum;
+use crate::renderer::{
+    CameraUniform, CommandList, InstanceBatches, InstanceData, ParticleRenderer,
+    RenderGraphContext, RenderGraphError, SamplerCache, SceneLighting, ShadowRenderer,
+    TextureFormat, TextureHandle, Vertex,
+};
+
+pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+/// Format scenes are rendered in, before being tone mapped into the render target.
+pub const HDR_FORMAT: TextureFormat = TextureFormat::RGBA16F;
+
+/// Samples per pixel of the color and depth targets of a scene. Every device supports 1 and
+/// 4 samples, 2 and 8 need adapter specific format features.
+#[derive(OperatorEnum, Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum SampleCount {
+    X1,
+    X2,
+    #[default]
+    X4,
+    X8,
+}
+
+impl SampleCount {
+    #[inline]
+    pub fn count(&self) -> u32 {
+        match self {
+            SampleCount::X1 => 1,
+            SampleCount::X2 => 2,
+            SampleCount::X4 => 4,
+            SampleCount::X8 => 8,
+        }
+    }
+}
 
 pub struct RenderPassContext<'a> {
     pub device: &'a wgpu::Device,
@@ -13,17 +48,6 @@ pub struct RenderPassContext<'a> {
     pub surface_format: wgpu::TextureFormat,
 }
 
-pub trait RenderPass {
-    type Attributes<'a>;
-
-    fn render(
-        &mut self,
-        command_list: &CommandList,
-        context: RenderPassContext,
-        attributes: Self::Attributes<'_>,
-    );
-}
-
 pub trait RenderPassFactory {
     type Output;
 
@@ -35,22 +59,55 @@ pub struct ScreenRenderPass {
     pipeline_layout: wgpu::PipelineLayout,
     shader_module: wgpu::ShaderModule,
     pipeline: wgpu::RenderPipeline,
+    /// Created by the first scene with particles, as it needs storage buffers in vertex shaders
+    particle_renderer: OnceCell<ParticleRenderer>,
+    shadow_renderer: ShadowRenderer,
+    sample_count: u32,
+}
+
+/// Textures a [`ScreenRenderPass`] renders the scene with.
+#[derive(Debug, Copy, Clone)]
+pub struct SceneTargets {
+    /// Color attachment. Multisampled when the pass uses more than one sample.
+    pub color: TextureHandle,
+    /// Single sampled texture multisampled colors are resolved into
+    pub resolve: Option<TextureHandle>,
+    pub depth: TextureHandle,
+    /// Shadow maps rendered by [`ScreenRenderPass::record_shadows`]
+    pub shadow_atlas: TextureHandle,
 }
 
 impl RenderPassFactory for ScreenRenderPass {
     type Output = ScreenRenderPass;
 
     fn new(context: RenderPassContext) -> Self {
+        ScreenRenderPass::with_sample_count(context, 1)
+    }
+}
+
+impl ScreenRenderPass {
+    /// Creates a pass rendering into color and depth targets with `sample_count` samples.
+    pub fn with_sample_count(context: RenderPassContext, sample_count: u32) -> Self {
+        let multisample = wgpu::MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        };
+
         let shader_module = context
             .device
             .create_shader_module(include_wgsl!("screen_render_pass.wgsl"));
 
+        let shadow_renderer = ShadowRenderer::new(context.device);
+
         let pipeline_layout =
             context
                 .device
                 .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                     label: Some("[ScreenRenderPass] pipeline layout"),
-                    bind_group_layouts: &[context.camera_uniform.bind_group_layout()],
+                    bind_group_layouts: &[
+                        context.camera_uniform.bind_group_layout(),
+                        shadow_renderer.lighting_bind_group_layout(),
+                    ],
                     push_constant_ranges: &[],
                 });
 
@@ -62,16 +119,22 @@ impl RenderPassFactory for ScreenRenderPass {
                 vertex: wgpu::VertexState {
                     module: &shader_module,
                     entry_point: "vs_main",
-                    buffers: &[Vertex::buffer_layout()],
+                    buffers: &[Vertex::buffer_layout(), InstanceData::buffer_layout()],
                 },
                 primitive: Default::default(),
-                depth_stencil: None,
-                multisample: Default::default(),
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: DEPTH_FORMAT,
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::Less,
+                    stencil: Default::default(),
+                    bias: Default::default(),
+                }),
+                multisample,
                 fragment: Some(wgpu::FragmentState {
                     module: &shader_module,
                     entry_point: "fs_main",
                     targets: &[Some(wgpu::ColorTargetState {
-                        format: context.surface_format,
+                        format: HDR_FORMAT.into(),
                         blend: None,
                         write_mask: Default::default(),
                     })],
@@ -83,54 +146,134 @@ impl RenderPassFactory for ScreenRenderPass {
             shader_module,
             pipeline_layout,
             pipeline,
+            particle_renderer: OnceCell::new(),
+            shadow_renderer,
+            sample_count,
         }
     }
-}
 
-impl RenderPass for ScreenRenderPass {
-    type Attributes<'a> = (&'a wgpu::TextureView);
+    #[inline]
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Records the shadow maps of the scene's lights into the shadow atlas.
+    pub fn record_shadows(
+        &self,
+        ctx: &mut RenderGraphContext,
+        command_list: &CommandList,
+        lighting: &SceneLighting,
+        shadow_atlas: TextureHandle,
+    ) -> Result<(), RenderGraphError> {
+        self.shadow_renderer
+            .record(ctx, command_list, lighting, shadow_atlas)
+    }
 
-    fn render(
-        &mut self,
+    /// Records the command list into the target textures.
+    pub fn record(
+        &self,
+        ctx: &mut RenderGraphContext,
         command_list: &CommandList,
-        context: RenderPassContext,
-        (screen_texture_view): Self::Attributes<'_>,
-    ) {
-        context
+        lighting: &SceneLighting,
+        targets: SceneTargets,
+    ) -> Result<(), RenderGraphError> {
+        let resolve = targets
+            .resolve
+            .map(|resolve| ctx.texture_view(resolve))
+            .transpose()?;
+
+        let camera_bind_group = ctx
             .camera_uniform
-            .set(context.queue, &command_list.camera);
+            .create_bind_group(ctx.device, &command_list.camera);
 
-        let mut encoder = context
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("x") });
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("ScreenRenderPass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: screen_texture_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.2,
-                            b: 0.3,
-                            a: 1.0,
-                        }),
-                        store: true,
+        let lighting_bind_group = self.shadow_renderer.create_lighting_bind_group(
+            ctx.device,
+            lighting,
+            ctx.texture_view(targets.shadow_atlas)?,
+        );
+
+        let batches = InstanceBatches::new(command_list.objects.iter());
+        let instance_buffer = (!batches.is_empty()).then(|| {
+            ctx.device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("[ScreenRenderPass] instances"),
+                    contents: bytemuck::cast_slice(&batches.data),
+                    usage: wgpu::BufferUsages::VERTEX,
+                })
+        });
+
+        // Particle systems only exist on devices supporting them, see
+        // `ParticleSystem::is_supported`.
+        let particle_renderer = (!command_list.particles.is_empty()).then(|| {
+            self.particle_renderer.get_or_init(|| {
+                ParticleRenderer::new(
+                    ctx.device,
+                    ctx.camera_uniform,
+                    HDR_FORMAT.into(),
+                    DEPTH_FORMAT,
+                    wgpu::MultisampleState {
+                        count: self.sample_count,
+                        ..Default::default()
                     },
-                })],
-                depth_stencil_attachment: None,
-            });
+                )
+            })
+        });
+
+        let particle_bind_groups: Vec<_> = particle_renderer
+            .iter()
+            .flat_map(|renderer| {
+                command_list
+                    .particles
+                    .iter()
+                    .map(|system| renderer.create_bind_group(ctx.device, system))
+            })
+            .collect();
+
+        let mut render_pass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("ScreenRenderPass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: ctx.texture_view(targets.color)?,
+                resolve_target: resolve,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: 0.1,
+                        g: 0.2,
+                        b: 0.3,
+                        a: 1.0,
+                    }),
+                    // Multisampled colors are only needed until they are resolved.
+                    store: targets.resolve.is_none(),
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: ctx.texture_view(targets.depth)?,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: false,
+                }),
+                stencil_ops: None,
+            }),
+        });
 
-            render_pass.set_pipeline(&self.pipeline);
-            render_pass.set_bind_group(0, context.camera_uniform.bind_group(), &[]);
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &lighting_bind_group, &[]);
+
+        if let Some(instance_buffer) = &instance_buffer {
+            render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+
+            for batch in batches.batches.iter() {
+                render_pass.set_vertex_buffer(0, batch.mesh.buffer().slice(..));
+                render_pass.draw(0..batch.mesh.num_vertices(), batch.instances.clone());
+            }
+        }
 
-            for object in command_list.objects.iter() {
-                render_pass.set_vertex_buffer(0, object.mesh.buffer().slice(..));
-                render_pass.draw(0..object.mesh.num_vertices(), 0..1);
+        if let Some(particle_renderer) = particle_renderer {
+            for (system, bind_group) in command_list.particles.iter().zip(&particle_bind_groups) {
+                particle_renderer.draw(&mut render_pass, system, bind_group);
             }
         }
 
-        context.queue.submit(std::iter::once(encoder.finish()));
+        Ok(())
     }
 }
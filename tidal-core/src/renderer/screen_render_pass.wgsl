@@ -1,35 +1,190 @@
 struct CameraUniform {
     view_proj: mat4x4<f32>,
+    view: mat4x4<f32>,
 };
 
 @group(0) @binding(0) // 1.
 var<uniform> camera: CameraUniform;
 
+const MAX_LIGHTS: u32 = 16u;
+const MAX_SHADOW_VIEWS: u32 = 32u;
+const NO_SHADOW: u32 = 0xffffffffu;
+const AMBIENT: f32 = 0.1;
+
+struct Light {
+    position: vec3<f32>,
+    kind: u32,
+    direction: vec3<f32>,
+    range: f32,
+    color: vec3<f32>,
+    cos_outer: f32,
+    cos_inner: f32,
+    bias: f32,
+    first_view: u32,
+    view_count: u32,
+}
+
+struct ShadowView {
+    view_proj: mat4x4<f32>,
+    // Offset and scale of the view's tile, in atlas texture coordinates
+    atlas_rect: vec4<f32>,
+    // x: furthest view depth covered by the view, y: texel size in atlas coordinates
+    params: vec4<f32>,
+}
+
+struct Lighting {
+    counts: vec4<u32>,
+    lights: array<Light, MAX_LIGHTS>,
+    views: array<ShadowView, MAX_SHADOW_VIEWS>,
+}
+
+@group(1) @binding(0)
+var<uniform> lighting: Lighting;
+@group(1) @binding(1)
+var shadow_atlas: texture_depth_2d;
+@group(1) @binding(2)
+var shadow_sampler: sampler_comparison;
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) normal: vec3<f32>,
     @location(2) tex_coords: vec2<f32>,
 }
 
+struct InstanceInput {
+    @location(3) transform_0: vec4<f32>,
+    @location(4) transform_1: vec4<f32>,
+    @location(5) transform_2: vec4<f32>,
+    @location(6) transform_3: vec4<f32>,
+    @location(7) color: vec4<f32>,
+}
+
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) normal: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) world_position: vec3<f32>,
 }
 
 @vertex
 fn vs_main(
     model: VertexInput,
+    instance: InstanceInput,
 ) -> VertexOutput {
+    let transform = mat4x4<f32>(
+        instance.transform_0,
+        instance.transform_1,
+        instance.transform_2,
+        instance.transform_3,
+    );
+
+    let world_position = transform * vec4<f32>(model.position, 1.0);
+
     var out: VertexOutput;
     out.tex_coords = model.tex_coords;
-    out.normal = model.normal;
-    out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
+    out.normal = (transform * vec4<f32>(model.normal, 0.0)).xyz;
+    out.color = instance.color;
+    out.world_position = world_position.xyz;
+    out.clip_position = camera.view_proj * world_position;
 
     return out;
 }
 
+// 3x3 percentage-closer filtering. Each tap is already bilinearly filtered by the
+// comparison sampler.
+fn sample_shadow(view: ShadowView, world_position: vec3<f32>, bias: f32) -> f32 {
+    let clip = view.view_proj * vec4<f32>(world_position, 1.0);
+    let ndc = clip.xyz / clip.w;
+    let uv = ndc.xy * vec2<f32>(0.5, -0.5) + 0.5;
+
+    // Outside of the shadow map
+    if any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) || ndc.z > 1.0 {
+        return 1.0;
+    }
+
+    let texel = view.params.y;
+    let tile_min = view.atlas_rect.xy + vec2<f32>(texel * 1.5);
+    let tile_max = view.atlas_rect.xy + view.atlas_rect.zw - vec2<f32>(texel * 1.5);
+    let atlas_uv = clamp(view.atlas_rect.xy + uv * view.atlas_rect.zw, tile_min, tile_max);
+    let depth = ndc.z - bias;
+
+    var visibility = 0.0;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * texel;
+            visibility += textureSampleCompareLevel(shadow_atlas, shadow_sampler, atlas_uv + offset, depth);
+        }
+    }
+
+    return visibility / 9.0;
+}
+
+fn shadow_factor(light: Light, world_position: vec3<f32>) -> f32 {
+    if light.first_view == NO_SHADOW {
+        return 1.0;
+    }
+
+    let view_depth = -(camera.view * vec4<f32>(world_position, 1.0)).z;
+
+    // Picks the first cascade covering the fragment
+    for (var i = 0u; i < light.view_count; i++) {
+        let view = lighting.views[light.first_view + i];
+
+        if view_depth <= view.params.x {
+            return sample_shadow(view, world_position, light.bias);
+        }
+    }
+
+    return 1.0;
+}
+
+fn light_contribution(light: Light, world_position: vec3<f32>, normal: vec3<f32>, has_normal: bool) -> vec3<f32> {
+    var to_light: vec3<f32>;
+    var attenuation = 1.0;
+
+    if light.kind == 0u {
+        to_light = -light.direction;
+    } else {
+        let offset = light.position - world_position;
+        let distance = length(offset);
+        to_light = offset / max(distance, 0.0001);
+
+        let cos_angle = dot(-to_light, light.direction);
+        let cone = smoothstep(light.cos_outer, max(light.cos_inner, light.cos_outer + 0.0001), cos_angle);
+        let falloff = clamp(1.0 - distance / light.range, 0.0, 1.0);
+        attenuation = cone * falloff * falloff;
+    }
+
+    // Meshes without normals are lit as if facing the light
+    var diffuse = 1.0;
+    if has_normal {
+        diffuse = max(dot(normal, to_light), 0.0);
+    }
+
+    if diffuse * attenuation <= 0.0 {
+        return vec3<f32>(0.0);
+    }
+
+    return light.color * diffuse * attenuation * shadow_factor(light, world_position);
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    let light_count = min(lighting.counts.x, MAX_LIGHTS);
+
+    // Scenes without lights are unlit
+    if light_count == 0u {
+        return in.color;
+    }
+
+    let has_normal = length(in.normal) > 0.0001;
+    let normal = select(vec3<f32>(0.0, 1.0, 0.0), normalize(in.normal), has_normal);
+
+    var light = vec3<f32>(AMBIENT);
+    for (var i = 0u; i < light_count; i++) {
+        light += light_contribution(lighting.lights[i], in.world_position, normal, has_normal);
+    }
+
+    return vec4<f32>(in.color.rgb * light, in.color.a);
 }
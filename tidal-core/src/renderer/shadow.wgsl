@@ -0,0 +1,32 @@
+struct ShadowView {
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> view: ShadowView;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+}
+
+struct InstanceInput {
+    @location(3) transform_0: vec4<f32>,
+    @location(4) transform_1: vec4<f32>,
+    @location(5) transform_2: vec4<f32>,
+    @location(6) transform_3: vec4<f32>,
+}
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    instance: InstanceInput,
+) -> @builtin(position) vec4<f32> {
+    let transform = mat4x4<f32>(
+        instance.transform_0,
+        instance.transform_1,
+        instance.transform_2,
+        instance.transform_3,
+    );
+
+    return view.view_proj * transform * vec4<f32>(model.position, 1.0);
+}
@@ -0,0 +1,651 @@
+use std::cmp::Reverse;
+
+use bytemuck::Zeroable;
+use cgmath::{EuclideanSpace, InnerSpace, Matrix4, Point3, Rad, Transform, Vector3};
+use wgpu::util::DeviceExt;
+
+use crate::renderer::scene::OPENGL_TO_WGPU_MATRIX;
+use crate::renderer::{
+    Camera, CommandList, InstanceBatches, InstanceData, Light, LightKind, RenderGraphContext,
+    RenderGraphError, ShadowSettings, TextureHandle, Vertex,
+};
+
+pub const SHADOW_ATLAS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+/// Size in pixels of the texture every shadow map is packed into.
+pub const SHADOW_ATLAS_SIZE: u32 = 4096;
+
+pub const MAX_LIGHTS: usize = 16;
+pub const MAX_SHADOW_VIEWS: usize = 32;
+pub const MAX_CASCADES: u32 = 4;
+
+const MIN_SHADOW_RESOLUTION: u32 = 128;
+const MAX_SHADOW_RESOLUTION: u32 = SHADOW_ATLAS_SIZE / 2;
+
+/// Blend between uniform (0) and logarithmic (1) cascade splits.
+const CASCADE_SPLIT_LAMBDA: f32 = 0.75;
+/// Distance behind each cascade where shadow casters are still rendered.
+const CASCADE_CASTER_MARGIN: f32 = 100.0;
+const SPOT_SHADOW_NEAR: f32 = 0.05;
+
+/// Marks a light without shadow maps.
+const NO_SHADOW: u32 = u32::MAX;
+
+/// Stride between shadow view matrices, matching the default uniform offset alignment.
+const VIEW_UNIFORM_STRIDE: wgpu::BufferAddress = 256;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct LightData {
+    position: [f32; 3],
+    kind: u32,
+    direction: [f32; 3],
+    range: f32,
+    color: [f32; 3],
+    cos_outer: f32,
+    cos_inner: f32,
+    bias: f32,
+    first_view: u32,
+    view_count: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShadowViewData {
+    view_projection: [[f32; 4]; 4],
+    /// Offset and scale of the view's tile, in atlas texture coordinates
+    atlas_rect: [f32; 4],
+    /// x: furthest view depth covered by the view, y: texel size in atlas coordinates
+    params: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct LightingBufferContents {
+    /// x: amount of lights
+    counts: [u32; 4],
+    lights: [LightData; MAX_LIGHTS],
+    views: [ShadowViewData; MAX_SHADOW_VIEWS],
+}
+
+/// A shadow map rendered into a tile of the shadow atlas.
+#[derive(Debug, Copy, Clone)]
+pub struct ShadowView {
+    pub view_projection: Matrix4<f32>,
+    /// Top-left corner of the tile, in pixels
+    pub offset: (u32, u32),
+    /// Width and height of the tile, in pixels
+    pub size: u32,
+    /// Furthest view depth covered by this view. Used to select cascades.
+    pub split_distance: f32,
+}
+
+/// Lights of a frame and the shadow maps they need, laid out as the scene shader expects.
+#[derive(Debug)]
+pub struct SceneLighting {
+    contents: LightingBufferContents,
+    views: Vec<ShadowView>,
+}
+
+impl SceneLighting {
+    pub fn new(command_list: &CommandList) -> Self {
+        let lights = &command_list.lights[..command_list.lights.len().min(MAX_LIGHTS)];
+        let camera = &command_list.camera;
+
+        let mut contents = LightingBufferContents::zeroed();
+        contents.counts[0] = lights.len() as u32;
+
+        for (data, light) in contents.lights.iter_mut().zip(lights) {
+            *data = LightData::from(light);
+        }
+
+        // Larger tiles go first, which keeps the shelf packing free of gaps as every
+        // tile size is a power of two.
+        let mut shadowed: Vec<_> = lights
+            .iter()
+            .enumerate()
+            .filter_map(|(index, light)| Some((index, light, light.shadow?)))
+            .collect();
+        shadowed.sort_by_key(|(_, _, shadow)| Reverse(shadow_resolution(shadow)));
+
+        let mut packer = ShelfPacker::new(SHADOW_ATLAS_SIZE);
+        let mut views: Vec<ShadowView> = vec![];
+
+        for (index, light, shadow) in shadowed {
+            let size = shadow_resolution(&shadow);
+            let matrices = light_view_projections(light, &shadow, camera, size);
+
+            if views.len() + matrices.len() > MAX_SHADOW_VIEWS {
+                continue;
+            }
+
+            // A light either gets all of its tiles or casts no shadows.
+            let mut candidate = packer;
+            let offsets: Option<Vec<_>> =
+                matrices.iter().map(|_| candidate.allocate(size)).collect();
+
+            let Some(offsets) = offsets else {
+                continue;
+            };
+
+            packer = candidate;
+
+            let data = &mut contents.lights[index];
+            data.first_view = views.len() as u32;
+            data.view_count = matrices.len() as u32;
+
+            views.extend(matrices.into_iter().zip(offsets).map(
+                |((view_projection, split_distance), offset)| ShadowView {
+                    view_projection,
+                    offset,
+                    size,
+                    split_distance,
+                },
+            ));
+        }
+
+        for (data, view) in contents.views.iter_mut().zip(&views) {
+            *data = ShadowViewData::from(view);
+        }
+
+        Self { contents, views }
+    }
+
+    #[inline]
+    pub fn views(&self) -> &[ShadowView] {
+        &self.views
+    }
+
+    #[inline]
+    pub fn has_shadows(&self) -> bool {
+        !self.views.is_empty()
+    }
+
+    /// Size of the shadow atlas this frame needs.
+    #[inline]
+    pub fn atlas_size(&self) -> u32 {
+        if self.has_shadows() {
+            SHADOW_ATLAS_SIZE
+        } else {
+            1
+        }
+    }
+}
+
+impl From<&Light> for LightData {
+    fn from(light: &Light) -> Self {
+        let color = (light.color * light.intensity).into();
+        let bias = light.shadow.map(|shadow| shadow.bias).unwrap_or(0.0);
+
+        match light.kind {
+            LightKind::Directional { direction } => Self {
+                position: [0.0; 3],
+                kind: 0,
+                direction: direction.normalize().into(),
+                range: 0.0,
+                color,
+                cos_outer: 0.0,
+                cos_inner: 0.0,
+                bias,
+                first_view: NO_SHADOW,
+                view_count: 0,
+            },
+            LightKind::Spot {
+                position,
+                direction,
+                range,
+                inner_angle,
+                outer_angle,
+            } => Self {
+                position: position.into(),
+                kind: 1,
+                direction: direction.normalize().into(),
+                range,
+                color,
+                cos_outer: outer_angle.cos(),
+                cos_inner: inner_angle.min(outer_angle).cos(),
+                bias,
+                first_view: NO_SHADOW,
+                view_count: 0,
+            },
+        }
+    }
+}
+
+impl From<&ShadowView> for ShadowViewData {
+    fn from(view: &ShadowView) -> Self {
+        let scale = 1.0 / SHADOW_ATLAS_SIZE as f32;
+
+        Self {
+            view_projection: view.view_projection.into(),
+            atlas_rect: [
+                view.offset.0 as f32 * scale,
+                view.offset.1 as f32 * scale,
+                view.size as f32 * scale,
+                view.size as f32 * scale,
+            ],
+            params: [view.split_distance, scale, 0.0, 0.0],
+        }
+    }
+}
+
+#[inline]
+fn shadow_resolution(shadow: &ShadowSettings) -> u32 {
+    shadow
+        .resolution
+        .clamp(MIN_SHADOW_RESOLUTION, MAX_SHADOW_RESOLUTION)
+        .next_power_of_two()
+}
+
+/// Returns the view projection and split distance of every shadow map of a light.
+fn light_view_projections(
+    light: &Light,
+    shadow: &ShadowSettings,
+    camera: &Camera,
+    size: u32,
+) -> Vec<(Matrix4<f32>, f32)> {
+    match light.kind {
+        LightKind::Directional { direction } => {
+            let direction = direction.normalize();
+            let splits = cascade_splits(camera, shadow);
+
+            let mut near = camera.z_near;
+            splits
+                .into_iter()
+                .map(|far| {
+                    let corners = frustum_corners(camera, near, far);
+                    near = far;
+
+                    (cascade_view_projection(direction, &corners, size), far)
+                })
+                .collect()
+        }
+        LightKind::Spot {
+            position,
+            direction,
+            range,
+            outer_angle,
+            ..
+        } => {
+            let direction = direction.normalize();
+            let view = Matrix4::look_to_rh(position, direction, up_vector(direction));
+            let fov_y = (outer_angle * 2.0).clamp(0.01, std::f32::consts::PI - 0.01);
+            let projection = cgmath::perspective(
+                Rad(fov_y),
+                1.0,
+                SPOT_SHADOW_NEAR,
+                range.max(SPOT_SHADOW_NEAR * 2.0),
+            );
+
+            vec![(OPENGL_TO_WGPU_MATRIX * projection * view, f32::MAX)]
+        }
+    }
+}
+
+/// Splits the shadowed part of the view frustum, blending uniform and logarithmic splits.
+fn cascade_splits(camera: &Camera, shadow: &ShadowSettings) -> Vec<f32> {
+    let count = shadow.cascades.clamp(1, MAX_CASCADES);
+    let near = camera.z_near;
+    let far = shadow.distance.min(camera.z_far).max(near * 2.0);
+
+    (1..=count)
+        .map(|index| {
+            let p = index as f32 / count as f32;
+            let uniform = near + (far - near) * p;
+            let logarithmic = near * (far / near).powf(p);
+
+            uniform + (logarithmic - uniform) * CASCADE_SPLIT_LAMBDA
+        })
+        .collect()
+}
+
+fn frustum_corners(camera: &Camera, near: f32, far: f32) -> [Point3<f32>; 8] {
+    let forward = (camera.target - camera.eye).normalize();
+    let right = forward.cross(camera.up).normalize();
+    let up = right.cross(forward);
+
+    let tan_y = (camera.fov_y.to_radians() * 0.5).tan();
+    let tan_x = tan_y * camera.aspect;
+
+    let mut corners = [camera.eye; 8];
+    for (index, distance) in [near, far].into_iter().enumerate() {
+        let center = camera.eye + forward * distance;
+        let x = right * distance * tan_x;
+        let y = up * distance * tan_y;
+
+        corners[index * 4] = center - x - y;
+        corners[index * 4 + 1] = center + x - y;
+        corners[index * 4 + 2] = center + x + y;
+        corners[index * 4 + 3] = center - x + y;
+    }
+
+    corners
+}
+
+/// Fits an orthographic projection around the bounding sphere of a frustum slice.
+///
+/// The sphere keeps the projection size constant as the camera rotates, and its center
+/// is snapped to shadow map texels so shadows don't shimmer as the camera moves.
+fn cascade_view_projection(
+    direction: Vector3<f32>,
+    corners: &[Point3<f32>; 8],
+    size: u32,
+) -> Matrix4<f32> {
+    let center = Point3::centroid(corners);
+    let radius = corners
+        .iter()
+        .map(|corner| (corner - center).magnitude())
+        .fold(0.0f32, f32::max)
+        .ceil()
+        .max(1.0);
+
+    let up = up_vector(direction);
+    let rotation = Matrix4::look_to_rh(Point3::origin(), direction, up);
+    let inverse_rotation = rotation.inverse_transform().unwrap();
+
+    let texel = radius * 2.0 / size as f32;
+    let local = rotation.transform_point(center);
+    let snapped = Point3::new(
+        (local.x / texel).floor() * texel,
+        (local.y / texel).floor() * texel,
+        local.z,
+    );
+    let center = inverse_rotation.transform_point(snapped);
+
+    let distance = radius + CASCADE_CASTER_MARGIN;
+    let view = Matrix4::look_to_rh(center - direction * distance, direction, up);
+    let projection = cgmath::ortho(-radius, radius, -radius, radius, 0.0, distance + radius);
+
+    OPENGL_TO_WGPU_MATRIX * projection * view
+}
+
+#[inline]
+fn up_vector(direction: Vector3<f32>) -> Vector3<f32> {
+    if direction.y.abs() > 0.99 {
+        Vector3::unit_z()
+    } else {
+        Vector3::unit_y()
+    }
+}
+
+/// Packs square tiles into rows of a square texture.
+#[derive(Debug, Copy, Clone)]
+struct ShelfPacker {
+    size: u32,
+    x: u32,
+    y: u32,
+    row_height: u32,
+}
+
+impl ShelfPacker {
+    fn new(size: u32) -> Self {
+        Self {
+            size,
+            x: 0,
+            y: 0,
+            row_height: 0,
+        }
+    }
+
+    fn allocate(&mut self, size: u32) -> Option<(u32, u32)> {
+        if self.x + size > self.size {
+            self.x = 0;
+            self.y += self.row_height;
+            self.row_height = 0;
+        }
+
+        if self.x + size > self.size || self.y + size > self.size {
+            return None;
+        }
+
+        let offset = (self.x, self.y);
+        self.x += size;
+        self.row_height = self.row_height.max(size);
+
+        Some(offset)
+    }
+}
+
+/// Renders shadow maps and owns the bindings the scene shader reads them through.
+#[derive(Debug)]
+pub struct ShadowRenderer {
+    view_bind_group_layout: wgpu::BindGroupLayout,
+    lighting_bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    comparison_sampler: wgpu::Sampler,
+}
+
+impl ShadowRenderer {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let shader_module = device.create_shader_module(wgpu::include_wgsl!("shadow.wgsl"));
+
+        let view_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("[ShadowRenderer] view bind_group_layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: wgpu::BufferSize::new(
+                            std::mem::size_of::<[[f32; 4]; 4]>() as u64,
+                        ),
+                    },
+                    count: None,
+                }],
+            });
+
+        let lighting_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("[ShadowRenderer] lighting bind_group_layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Depth,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                        count: None,
+                    },
+                ],
+            });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("[ShadowRenderer] pipeline layout"),
+            bind_group_layouts: &[&view_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("[ShadowRenderer] pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: "vs_main",
+                buffers: &[Vertex::buffer_layout(), InstanceData::buffer_layout()],
+            },
+            primitive: Default::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: SHADOW_ATLAS_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: Default::default(),
+                bias: wgpu::DepthBiasState {
+                    constant: 2,
+                    slope_scale: 2.0,
+                    clamp: 0.0,
+                },
+            }),
+            multisample: Default::default(),
+            fragment: None,
+            multiview: None,
+        });
+
+        let comparison_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("[ShadowRenderer] comparison sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+
+        Self {
+            view_bind_group_layout,
+            lighting_bind_group_layout,
+            pipeline,
+            comparison_sampler,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn lighting_bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.lighting_bind_group_layout
+    }
+
+    /// Creates the bind group the scene shader reads lights and shadow maps from.
+    pub fn create_lighting_bind_group(
+        &self,
+        device: &wgpu::Device,
+        lighting: &SceneLighting,
+        atlas: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("[ShadowRenderer] lighting"),
+            contents: bytemuck::bytes_of(&lighting.contents),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("[ShadowRenderer] lighting bind group"),
+            layout: &self.lighting_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(atlas),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&self.comparison_sampler),
+                },
+            ],
+        })
+    }
+
+    /// Renders the depth of every shadow view into its tile of the atlas.
+    pub fn record(
+        &self,
+        ctx: &mut RenderGraphContext,
+        command_list: &CommandList,
+        lighting: &SceneLighting,
+        atlas: TextureHandle,
+    ) -> Result<(), RenderGraphError> {
+        let views = lighting.views();
+
+        let mut view_data = vec![0u8; views.len() * VIEW_UNIFORM_STRIDE as usize];
+        for (chunk, view) in view_data
+            .chunks_exact_mut(VIEW_UNIFORM_STRIDE as usize)
+            .zip(views)
+        {
+            let matrix: [[f32; 4]; 4] = view.view_projection.into();
+            let bytes = bytemuck::bytes_of(&matrix);
+            chunk[..bytes.len()].copy_from_slice(bytes);
+        }
+
+        let batches = InstanceBatches::new(command_list.objects.iter());
+        let buffers = (!views.is_empty() && !batches.is_empty()).then(|| {
+            let view_buffer = ctx
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("[ShadowRenderer] views"),
+                    contents: &view_data,
+                    usage: wgpu::BufferUsages::UNIFORM,
+                });
+
+            let view_bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("[ShadowRenderer] view bind group"),
+                layout: &self.view_bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &view_buffer,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(std::mem::size_of::<[[f32; 4]; 4]>() as u64),
+                    }),
+                }],
+            });
+
+            let instance_buffer =
+                ctx.device
+                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("[ShadowRenderer] instances"),
+                        contents: bytemuck::cast_slice(&batches.data),
+                        usage: wgpu::BufferUsages::VERTEX,
+                    });
+
+            (view_bind_group, instance_buffer)
+        });
+
+        let mut render_pass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Shadows"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: ctx.texture_view(atlas)?,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+        });
+
+        let Some((view_bind_group, instance_buffer)) = &buffers else {
+            return Ok(());
+        };
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+
+        for (index, view) in views.iter().enumerate() {
+            let offset = (index as wgpu::BufferAddress * VIEW_UNIFORM_STRIDE) as u32;
+
+            render_pass.set_viewport(
+                view.offset.0 as f32,
+                view.offset.1 as f32,
+                view.size as f32,
+                view.size as f32,
+                0.0,
+                1.0,
+            );
+            render_pass.set_bind_group(0, view_bind_group, &[offset]);
+
+            for batch in batches.batches.iter() {
+                render_pass.set_vertex_buffer(0, batch.mesh.buffer().slice(..));
+                render_pass.draw(0..batch.mesh.num_vertices(), batch.instances.clone());
+            }
+        }
+
+        Ok(())
+    }
+}
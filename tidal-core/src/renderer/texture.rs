@@ -13,11 +13,23 @@ pub enum TextureFormat {
     SRGBA8U,
 }
 
+impl From<TextureFormat> for wgpu::TextureFormat {
+    fn from(format: TextureFormat) -> Self {
+        match format {
+            TextureFormat::RGBA16F => wgpu::TextureFormat::Rgba16Float,
+            TextureFormat::RGBA8U => wgpu::TextureFormat::Rgba8Unorm,
+            TextureFormat::SRGBA8U => wgpu::TextureFormat::Rgba8UnormSrgb,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct TextureDescriptor<'a> {
     pub format: wgpu::TextureFormat,
     pub dimensions: cgmath::Vector2<u32>,
     pub data: Option<&'a [u8]>,
+    /// Usages on top of sampling the texture and copying into it
+    pub usage: wgpu::TextureUsages,
 }
 
 impl Texture {
@@ -33,7 +45,9 @@ impl Texture {
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: desc.format,
-            usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+            usage: wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | desc.usage,
             view_formats: &[],
         });
 
@@ -52,6 +66,11 @@ impl Texture {
         &self.view
     }
 
+    #[inline]
+    pub fn size(&self) -> cgmath::Vector2<u32> {
+        cgmath::Vector2::new(self.inner.width(), self.inner.height())
+    }
+
     #[inline]
     pub(crate) fn format(&self) -> wgpu::TextureFormat {
         self.inner.format()
@@ -0,0 +1,174 @@
+use wgpu::util::DeviceExt;
+
+use crate::graph::OperatorEnum;
+use crate::renderer::{RenderGraphContext, RenderGraphError, TextureHandle};
+
+/// Curve mapping HDR colors into the displayable range.
+#[derive(OperatorEnum, Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum ToneMapping {
+    #[default]
+    Aces,
+    Reinhard,
+    AgX,
+}
+
+impl ToneMapping {
+    #[inline]
+    fn index(&self) -> u32 {
+        match self {
+            ToneMapping::Aces => 0,
+            ToneMapping::Reinhard => 1,
+            ToneMapping::AgX => 2,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ToneMappingSettings {
+    pub tone_mapping: ToneMapping,
+    /// Exposure compensation, in stops
+    pub exposure: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct ToneMappingParams {
+    exposure: f32,
+    curve: u32,
+    encode_srgb: u32,
+    _padding: u32,
+}
+
+/// Tone maps an HDR texture into a texture of the output format.
+#[derive(Debug)]
+pub struct ToneMappingPass {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    output_format: wgpu::TextureFormat,
+}
+
+impl ToneMappingPass {
+    pub fn new(device: &wgpu::Device, output_format: wgpu::TextureFormat) -> Self {
+        let shader_module = device.create_shader_module(wgpu::include_wgsl!("tone_mapping.wgsl"));
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("[ToneMappingPass] bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("[ToneMappingPass] pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("[ToneMappingPass] pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: Default::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: output_format,
+                    blend: None,
+                    write_mask: Default::default(),
+                })],
+            }),
+            multiview: None,
+        });
+
+        Self {
+            bind_group_layout,
+            pipeline,
+            output_format,
+        }
+    }
+
+    /// Records the tone mapping of `source` into `target`, which must have the same size.
+    pub fn record(
+        &self,
+        ctx: &mut RenderGraphContext,
+        settings: &ToneMappingSettings,
+        source: TextureHandle,
+        target: TextureHandle,
+    ) -> Result<(), RenderGraphError> {
+        let params = ToneMappingParams {
+            exposure: settings.exposure.exp2(),
+            curve: settings.tone_mapping.index(),
+            // sRGB targets are encoded by the hardware when written.
+            encode_srgb: !self.output_format.is_srgb() as u32,
+            _padding: 0,
+        };
+
+        let buffer = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("[ToneMappingPass] params"),
+                contents: bytemuck::bytes_of(&params),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+
+        let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("[ToneMappingPass] bind group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(ctx.texture_view(source)?),
+                },
+            ],
+        });
+
+        let mut render_pass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("ToneMapping"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: ctx.texture_view(target)?,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
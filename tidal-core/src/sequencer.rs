@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+/// How a clip replaces the clips playing before it.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(tag = "type")]
+pub enum Transition {
+    /// The clip replaces the previous ones as soon as it starts
+    #[default]
+    Cut,
+    /// The clip fades in over the previous ones during `duration` seconds
+    Crossfade { duration: f32 },
+}
+
+/// A named range of the timeline showing one of the sequencer's scenes.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Clip {
+    pub name: String,
+    /// Start time, in seconds
+    pub start: f32,
+    /// End time, in seconds
+    pub end: f32,
+    /// Index of the scene connected to the sequencer that this clip shows
+    pub scene: usize,
+    /// Transition into this clip
+    pub transition: Transition,
+}
+
+impl Clip {
+    #[inline]
+    pub fn is_active(&self, time: f32) -> bool {
+        self.start <= time && time < self.end
+    }
+
+    /// How much of the transition into this clip is completed at `time`, from 0 to 1.
+    pub fn transition_progress(&self, time: f32) -> f32 {
+        match self.transition {
+            Transition::Cut => 1.0,
+            Transition::Crossfade { duration } if duration > 0.0 => {
+                ((time - self.start) / duration).clamp(0.0, 1.0)
+            }
+            Transition::Crossfade { .. } => 1.0,
+        }
+    }
+}
+
+/// A clip playing at a given time, and how much it contributes to the final image.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ActiveClip {
+    pub clip: usize,
+    pub scene: usize,
+    pub weight: f32,
+}
+
+/// Clips of a demo laid over time.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Timeline {
+    clips: Vec<Clip>,
+}
+
+impl Timeline {
+    pub fn new(mut clips: Vec<Clip>) -> Self {
+        clips.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+        Self { clips }
+    }
+
+    #[inline]
+    pub fn clips(&self) -> &[Clip] {
+        &self.clips
+    }
+
+    /// Inserts a clip, keeping clips sorted by start time.
+    pub fn insert(&mut self, clip: Clip) -> usize {
+        let index = self
+            .clips
+            .partition_point(|other| other.start <= clip.start);
+
+        self.clips.insert(index, clip);
+        index
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Clip> {
+        (index < self.clips.len()).then(|| self.clips.remove(index))
+    }
+
+    /// Replaces a clip, moving it if its start time changed.
+    pub fn update(&mut self, index: usize, clip: Clip) -> Option<usize> {
+        self.remove(index)?;
+
+        Some(self.insert(clip))
+    }
+
+    /// End of the last clip, in seconds.
+    pub fn duration(&self) -> f32 {
+        self.clips.iter().map(|clip| clip.end).fold(0.0, f32::max)
+    }
+
+    /// Returns the clips playing at `time`, with weights adding up to 1.
+    ///
+    /// Clips are layered by start time: each clip fades in over the ones that started before
+    /// it, scaling their weights down by its own transition progress.
+    pub fn active_clips(&self, time: f32) -> Vec<ActiveClip> {
+        let mut active: Vec<ActiveClip> = vec![];
+
+        for (index, clip) in self.clips.iter().enumerate() {
+            if !clip.is_active(time) {
+                continue;
+            }
+
+            let progress = if active.is_empty() {
+                1.0
+            } else {
+                clip.transition_progress(time)
+            };
+
+            for previous in active.iter_mut() {
+                previous.weight *= 1.0 - progress;
+            }
+
+            active.push(ActiveClip {
+                clip: index,
+                scene: clip.scene,
+                weight: progress,
+            });
+        }
+
+        active.retain(|clip| clip.weight > 0.0);
+        active
+    }
+}
@@ -0,0 +1,41 @@
+use tidal_core::animation::{Curve, Interpolation, Keyframe, ScalarCurve};
+
+fn curve(interpolation: Interpolation) -> ScalarCurve {
+    Curve::new(vec![
+        Keyframe::new(1.0, 2.0, interpolation),
+        Keyframe::new(2.0, 4.0, interpolation),
+        Keyframe::new(3.0, 0.0, interpolation),
+    ])
+}
+
+#[test]
+fn samples_between_keyframes() {
+    let linear = curve(Interpolation::Linear);
+
+    assert_eq!(linear.sample(1.5), 3.0);
+    assert_eq!(linear.sample(2.5), 2.0);
+    assert_eq!(curve(Interpolation::Step).sample(2.5), 4.0);
+}
+
+#[test]
+fn holds_the_ends_outside_the_keyframes() {
+    let linear = curve(Interpolation::Linear);
+
+    assert_eq!(linear.sample(0.0), 2.0);
+    assert_eq!(linear.sample(f32::NEG_INFINITY), 2.0);
+    assert_eq!(linear.sample(10.0), 0.0);
+    assert_eq!(linear.sample(f32::INFINITY), 0.0);
+    assert_eq!(ScalarCurve::default().sample(1.0), 0.0);
+}
+
+#[test]
+fn samples_the_first_keyframe_at_nan() {
+    for interpolation in [
+        Interpolation::Step,
+        Interpolation::Linear,
+        Interpolation::Bezier,
+        Interpolation::Hermite,
+    ] {
+        assert_eq!(curve(interpolation).sample(f32::NAN), 2.0);
+    }
+}
@@ -0,0 +1,243 @@
+use std::f32::consts::{PI, SQRT_2};
+use std::io::Cursor;
+use std::path::Path;
+
+use tidal_core::audio::{
+    AudioBuffer, AudioError, AudioPlayer, NullOutput, SpectrumAnalyzer, Tempo, SPECTRUM_WINDOW_SIZE,
+};
+
+const SAMPLE_RATE: u32 = 1000;
+
+/// Mono buffer whose sample at each frame is the frame's time in seconds, so the position of
+/// the playback can be read from the samples it outputs.
+fn ramp(seconds: u32) -> AudioBuffer {
+    let samples = (0..seconds * SAMPLE_RATE)
+        .map(|frame| frame as f32 / SAMPLE_RATE as f32)
+        .collect();
+
+    AudioBuffer::new(SAMPLE_RATE, 1, samples)
+}
+
+/// Mono buffer at 48kHz playing a sine of `frequency` Hz and `amplitude` from `start` seconds
+/// on, silent before.
+fn sine(frequency: f32, amplitude: f32, start: f32, seconds: f32) -> AudioBuffer {
+    let sample_rate = 48000;
+
+    let samples = (0..(seconds * sample_rate as f32) as usize)
+        .map(|frame| {
+            let time = frame as f32 / sample_rate as f32;
+
+            if time < start {
+                0.0
+            } else {
+                amplitude * (2.0 * PI * frequency * time).sin()
+            }
+        })
+        .collect();
+
+    AudioBuffer::new(sample_rate, 1, samples)
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() < 1e-4,
+        "expected {expected}, got {actual}"
+    );
+}
+
+#[test]
+fn decodes_wav() {
+    let mut wav = Cursor::new(vec![]);
+    let spec = hound::WavSpec {
+        channels: 2,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut writer = hound::WavWriter::new(&mut wav, spec).unwrap();
+    for sample in [0, 16384, -16384, i16::MIN] {
+        writer.write_sample(sample).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    wav.set_position(0);
+    let buffer = AudioBuffer::decode_wav(wav).unwrap();
+
+    assert_eq!(buffer.sample_rate(), 8000);
+    assert_eq!(buffer.channels(), 2);
+    assert_eq!(buffer.frames(), 2);
+    assert_close(buffer.duration(), 2.0 / 8000.0);
+
+    assert_close(buffer.sample(0.0, 0), 0.0);
+    assert_close(buffer.sample(0.0, 1), 0.5);
+    assert_close(buffer.sample(1.0, 0), -0.5);
+    assert_close(buffer.sample(1.0, 1), -1.0);
+    assert_close(buffer.sample(0.5, 1), -0.25);
+    assert_close(buffer.mixed_sample(0), 0.25);
+
+    // Frames past the end are silent.
+    assert_close(buffer.sample(2.0, 0), 0.0);
+    assert_close(buffer.mixed_sample(2), 0.0);
+}
+
+#[test]
+fn rejects_unsupported_formats() {
+    assert!(matches!(
+        AudioBuffer::load(Path::new("soundtrack.mp3")),
+        Err(AudioError::UnsupportedFormat(extension)) if extension == "mp3"
+    ));
+}
+
+#[test]
+fn computes_beat_positions() {
+    let tempo = Tempo {
+        bpm: 120.0,
+        offset: 1.0,
+        beats_per_bar: 4,
+    };
+
+    assert_close(tempo.beat_duration(), 0.5);
+
+    let position = tempo.position(3.25);
+
+    assert_close(position.beats, 4.5);
+    assert_eq!(position.beat, 4);
+    assert_eq!(position.bar, 1);
+    assert_eq!(position.beat_in_bar, 0);
+    assert_close(position.beat_phase, 0.5);
+    assert_close(position.bar_phase, 0.125);
+
+    // Times before the first beat stay at its start.
+    assert_eq!(tempo.position(0.0), Default::default());
+}
+
+#[test]
+fn plays_at_the_output_rate() {
+    let mut player = AudioPlayer::new(NullOutput::new(SAMPLE_RATE, 2), ramp(10)).unwrap();
+
+    // Paused players output silence and don't advance.
+    assert!(player.output_mut().advance(0.5).iter().all(|s| *s == 0.0));
+    assert_close(player.time(), 0.0);
+
+    player.play();
+
+    let samples = player.output_mut().render(2).to_vec();
+
+    // Mono audio plays on every channel.
+    assert_eq!(samples.len(), 4);
+    assert_close(samples[2], 0.001);
+    assert_close(samples[3], 0.001);
+
+    player.output_mut().advance(1.0);
+    assert_close(player.time(), 1.002);
+}
+
+#[test]
+fn syncs_to_the_demo_clock() {
+    let mut player = AudioPlayer::new(NullOutput::new(SAMPLE_RATE, 1), ramp(10))
+        .unwrap()
+        .with_sync_tolerance(0.05);
+
+    // Syncing starts the playback.
+    player.sync(0.0);
+    assert!(player.is_playing());
+
+    player.output_mut().advance(1.0);
+    assert_close(player.time(), 1.0);
+
+    // Small drifts are left to the output, seeking would be audible.
+    player.sync(1.03);
+    assert_close(player.time(), 1.0);
+    assert_close(player.output_mut().render(1)[0], 1.0);
+
+    // Larger ones seek to the demo clock.
+    player.sync(4.0);
+    assert_close(player.time(), 4.0);
+    assert_close(player.output_mut().render(1)[0], 4.0);
+
+    player.sync(2.0);
+    assert_close(player.time(), 2.0);
+    assert_close(player.output_mut().render(1)[0], 2.0);
+}
+
+#[test]
+fn resamples_to_the_output_rate() {
+    let mut player = AudioPlayer::new(NullOutput::new(SAMPLE_RATE * 2, 1), ramp(10)).unwrap();
+    player.play();
+
+    let samples = player.output_mut().render(3).to_vec();
+
+    assert_close(samples[0], 0.0);
+    assert_close(samples[1], 0.0005);
+    assert_close(samples[2], 0.001);
+    assert_close(player.time(), 0.0015);
+}
+
+#[test]
+fn finds_sines_in_their_band() {
+    let buffer = sine(1000.0, 1.0, 0.0, 1.0);
+    let spectrum = SpectrumAnalyzer::default().analyze(&buffer, 0.5);
+
+    let peak = spectrum.magnitudes().iter().copied().fold(0.0, f32::max);
+    assert!((peak - 1.0).abs() < 0.2, "full scale sine peaked at {peak}");
+
+    let band = spectrum.band_energy(900.0, 1100.0);
+    let below = spectrum.band_energy(100.0, 500.0);
+    let above = spectrum.band_energy(4000.0, 8000.0);
+
+    assert!(band > 0.2, "band energy {band}");
+    assert!(below < band * 0.01, "energy below the sine {below}");
+    assert!(above < band * 0.01, "energy above the sine {above}");
+}
+
+#[test]
+fn measures_rms() {
+    let spectrum = SpectrumAnalyzer::default().analyze(&sine(440.0, 0.5, 0.0, 1.0), 0.5);
+    assert!((spectrum.rms() - 0.5 / SQRT_2).abs() < 0.01);
+
+    // Channels are mixed down before the analysis.
+    let square = AudioBuffer::new(48000, 2, [0.25, 0.25, -0.25, -0.25].repeat(24000));
+    let spectrum = SpectrumAnalyzer::default().analyze(&square, 0.5);
+    assert_close(spectrum.rms(), 0.25);
+
+    // Windows before the start of the audio are silent.
+    let spectrum = SpectrumAnalyzer::default().analyze(&square, 0.0);
+    assert_close(spectrum.rms(), 0.0);
+}
+
+#[test]
+fn detects_the_flux_of_onsets() {
+    let buffer = sine(1000.0, 1.0, 0.5, 1.0);
+    let mut analyzer = SpectrumAnalyzer::default();
+    let hop = SPECTRUM_WINDOW_SIZE as f32 / 4.0 / 48000.0;
+
+    let flux = |analyzer: &mut SpectrumAnalyzer, time: f32| {
+        let previous = analyzer.analyze(&buffer, time - hop);
+
+        analyzer
+            .analyze(&buffer, time)
+            .flux(&previous, 900.0, 1100.0)
+    };
+
+    let silence = flux(&mut analyzer, 0.4);
+    let onset = flux(&mut analyzer, 0.5 + hop);
+    let sustain = flux(&mut analyzer, 0.9);
+
+    assert_close(silence, 0.0);
+    assert!(onset > 0.01, "onset flux {onset}");
+    assert!(sustain < onset * 0.1, "sustain flux {sustain}");
+}
+
+#[test]
+fn analysis_only_depends_on_the_time() {
+    let buffer = sine(250.0, 0.8, 0.1, 1.0);
+
+    let mut analyzer = SpectrumAnalyzer::default();
+    let first = analyzer.analyze(&buffer, 0.3);
+    analyzer.analyze(&buffer, 0.7);
+    let second = analyzer.analyze(&buffer, 0.3);
+
+    assert_eq!(first.magnitudes(), second.magnitudes());
+    assert_eq!(first.rms(), second.rms());
+}
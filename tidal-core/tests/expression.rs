@@ -0,0 +1,112 @@
+use tidal_core::cgmath::Vector3;
+use tidal_core::expression::{Expression, ExpressionType, ExpressionValue};
+
+/// Names and types of the variables of a formula, which must compile.
+fn variables(source: &str) -> Vec<(String, ExpressionType)> {
+    let expression = Expression::new(source);
+
+    assert_eq!(expression.error(), None, "{source}");
+
+    expression
+        .variables()
+        .iter()
+        .map(|variable| (variable.name.clone(), variable.data_type))
+        .collect()
+}
+
+fn evaluate(source: &str, variables: &[ExpressionValue]) -> ExpressionValue {
+    Expression::new(source).evaluate(variables, &mut vec![])
+}
+
+#[test]
+fn infers_vectors_through_arithmetic() {
+    use ExpressionType::{Scalar, Vector};
+
+    for source in [
+        "length(v)",
+        "length(v * 2.0)",
+        "length(v + v)",
+        "length(-v / pi)",
+        "length(abs(v) - 1)",
+        "(v * 2).x",
+    ] {
+        assert_eq!(variables(source), [("v".into(), Vector)], "{source}");
+    }
+
+    assert_eq!(
+        variables("distance(a - b, c)"),
+        [
+            ("a".into(), Vector),
+            ("b".into(), Vector),
+            ("c".into(), Vector)
+        ]
+    );
+
+    // Operands mixed with a vector can stay scalars.
+    assert_eq!(
+        variables("length(vec3(1, 2, 3) * s)"),
+        [("s".into(), Scalar)]
+    );
+    assert_eq!(
+        variables("length(v.xyz * s) + length(w)"),
+        [
+            ("v".into(), Vector),
+            ("s".into(), Scalar),
+            ("w".into(), Vector)
+        ]
+    );
+    assert_eq!(variables("sin(t) * 2"), [("t".into(), Scalar)]);
+}
+
+#[test]
+fn evaluates_vector_math() {
+    let v = ExpressionValue::Vector(Vector3::new(3.0, 0.0, 4.0));
+
+    assert_eq!(
+        evaluate("length(v * 2.0)", &[v]),
+        ExpressionValue::Scalar(10.0)
+    );
+    assert_eq!(
+        evaluate("length(v + v)", &[v]),
+        ExpressionValue::Scalar(10.0)
+    );
+    assert_eq!(
+        evaluate("normalize(v - vec3(3, 0, 0))", &[v]),
+        ExpressionValue::Vector(Vector3::new(0.0, 0.0, 1.0))
+    );
+}
+
+#[test]
+fn rejects_scalars_where_vectors_are_expected() {
+    let error = Expression::new("length(2.0 * pi)")
+        .error()
+        .cloned()
+        .unwrap();
+
+    assert_eq!(error.message, "'length' expects vectors");
+    // Binary operations are reported at their operator.
+    assert_eq!(error.position, 11);
+}
+
+#[test]
+fn rejects_deeply_nested_expressions() {
+    let nested =
+        |depth: usize, value: &str| format!("{}{value}{}", "(".repeat(depth), ")".repeat(depth));
+
+    assert_eq!(
+        evaluate(&nested(100, "2"), &[]),
+        ExpressionValue::Scalar(2.0)
+    );
+
+    for source in [
+        nested(200_000, "1"),
+        "-".repeat(200_000) + "1",
+        "sin(".repeat(200_000) + "1",
+        "1".to_string() + &" + 1".repeat(200_000),
+        "v".to_string() + &".x".repeat(200_000),
+    ] {
+        let error = Expression::new(&source).error().cloned().unwrap();
+
+        assert_eq!(error.message, "expression is nested too deeply");
+    }
+}
@@ -0,0 +1,127 @@
+use tidal_core::cgmath::Vector2;
+use tidal_core::renderer::{
+    RenderGraph, RenderGraphContext, RenderGraphError, TextureHandle, TextureSize,
+    TransientTextureDescriptor,
+};
+
+/// Passes are only scheduled here, never executed.
+fn pass(_: &mut RenderGraphContext) -> Result<(), RenderGraphError> {
+    Ok(())
+}
+
+fn texture(graph: &mut RenderGraph) -> TextureHandle {
+    graph.create_texture(TransientTextureDescriptor {
+        label: "Texture",
+        size: TextureSize::Target,
+        format: wgpu::TextureFormat::Rgba16Float,
+        sample_count: 1,
+    })
+}
+
+#[test]
+fn orders_writers_before_readers() {
+    let mut graph = RenderGraph::new();
+    let target = graph.target();
+    let color = texture(&mut graph);
+    let depth = texture(&mut graph);
+
+    graph.add_pass("Resolve", pass).read(color).write(target);
+    graph.add_pass("Independent", pass).write(depth);
+    graph.add_pass("Color", pass).write(color);
+
+    assert_eq!(graph.schedule().unwrap().order, [1, 2, 0]);
+}
+
+#[test]
+fn keeps_the_order_of_writers_of_the_same_texture() {
+    let mut graph = RenderGraph::new();
+    let target = graph.target();
+    let color = texture(&mut graph);
+
+    graph.add_pass("Composite", pass).read(color).write(target);
+    graph.add_pass("Clear", pass).write(color);
+    graph.add_pass("Blend", pass).read(color).write(color);
+
+    assert_eq!(graph.schedule().unwrap().order, [1, 2, 0]);
+}
+
+#[test]
+fn rejects_dependency_cycles() {
+    let mut graph = RenderGraph::new();
+    let a = texture(&mut graph);
+    let b = texture(&mut graph);
+
+    graph.add_pass("A to B", pass).read(a).write(b);
+    graph.add_pass("B to A", pass).read(b).write(a);
+
+    assert_eq!(graph.schedule(), Err(RenderGraphError::Cycle));
+}
+
+#[test]
+fn rejects_textures_of_other_graphs() {
+    let mut other = RenderGraph::new();
+    let foreign = texture(&mut other);
+
+    let mut graph = RenderGraph::new();
+    graph.add_pass("Foreign", pass).read(foreign);
+
+    assert_eq!(
+        graph.schedule(),
+        Err(RenderGraphError::InvalidHandle(foreign))
+    );
+}
+
+#[test]
+fn aliases_textures_with_disjoint_lifetimes() {
+    let mut graph = RenderGraph::new();
+    let target = graph.target();
+    let first = texture(&mut graph);
+    let second = texture(&mut graph);
+    let third = texture(&mut graph);
+
+    graph.add_pass("First", pass).write(first);
+    graph
+        .add_pass("Present first", pass)
+        .read(first)
+        .write(target);
+    graph.add_pass("Second", pass).write(second);
+    // Reads `second` while writing `third`, so they can't share a texture.
+    graph.add_pass("Third", pass).read(second).write(third);
+    graph
+        .add_pass("Present third", pass)
+        .read(third)
+        .write(target);
+
+    let schedule = graph.schedule().unwrap();
+    assert_eq!(schedule.order, [0, 1, 2, 3, 4]);
+
+    let allocation = graph.allocate(&schedule, Vector2::new(64, 64));
+
+    assert_eq!(allocation.texture_count(), 2);
+    assert_eq!(allocation.texture(target), None);
+    assert_eq!(allocation.texture(first), allocation.texture(second));
+    assert_ne!(allocation.texture(second), allocation.texture(third));
+}
+
+#[test]
+fn only_aliases_textures_of_the_same_description() {
+    let mut graph = RenderGraph::new();
+    let target = graph.target();
+    let color = texture(&mut graph);
+    let shadows = graph.create_texture(TransientTextureDescriptor {
+        label: "Shadows",
+        size: TextureSize::Absolute(1024, 1024),
+        format: wgpu::TextureFormat::Depth32Float,
+        sample_count: 1,
+    });
+
+    graph.add_pass("Color", pass).write(color);
+    graph.add_pass("Present", pass).read(color).write(target);
+    graph.add_pass("Shadows", pass).write(shadows);
+
+    let schedule = graph.schedule().unwrap();
+    let allocation = graph.allocate(&schedule, Vector2::new(64, 64));
+
+    assert_eq!(allocation.texture_count(), 2);
+    assert_ne!(allocation.texture(color), allocation.texture(shadows));
+}
@@ -1,5 +1,6 @@
 use std::default::Default;
 use std::fmt::format;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::{cell::RefCell, collections::HashMap, f32::consts::PI};
 
@@ -16,6 +17,7 @@ use eframe::{
 use lazy_static::lazy_static;
 use uuid::{uuid, Uuid};
 
+use tidal_core::audio::AudioBuffer;
 use tidal_core::demo::Demo;
 use tidal_core::graph::{Graph, NodeId};
 use tidal_core::interpreter::interpreter::Interpreter;
@@ -45,6 +47,15 @@ pub struct App {
     viewport_widget: ViewportWidget,
 
     interpreter_holder: InterpreterHolder,
+    /// Store revision loaded into the interpreter, so evaluator state survives
+    /// between frames until the graph changes
+    loaded_revision: Option<u64>,
+    /// Directory the paths of the project are relative to. Projects aren't opened from the
+    /// editor yet, so it's the working directory.
+    project_root: PathBuf,
+    /// Soundtrack decoded from its path, kept while the path doesn't change. `None` when it
+    /// failed to decode.
+    decoded_soundtrack: Option<(PathBuf, Option<AudioBuffer>)>,
     save_dialog: ProjectSaveDialog,
     recent_projects: RecentProjects,
 }
@@ -68,15 +79,43 @@ impl App {
         App {
             focused: Focus::GraphEditor,
             // project: Arc::new(Mutex::new(Project::new(ProjectState { graph }))),
-            store: Store::new(State { graph }),
+            store: Store::new(State {
+                graph,
+                library: vec![],
+                soundtrack: None,
+            }),
             node_editor_widget: Default::default(),
             node_inspector_widget: Default::default(),
             viewport_widget,
             interpreter_holder,
+            loaded_revision: None,
+            project_root: PathBuf::from("."),
+            decoded_soundtrack: None,
             save_dialog: Default::default(),
             recent_projects,
         }
     }
+
+    /// Decodes the soundtrack of the project, unless it was already decoded from the same path.
+    fn soundtrack(&mut self) -> Option<AudioBuffer> {
+        let soundtrack = self.store.state().soundtrack.as_ref()?;
+
+        if let Some((_, buffer)) = self
+            .decoded_soundtrack
+            .as_ref()
+            .filter(|(path, _)| *path == soundtrack.path)
+        {
+            return buffer.clone();
+        }
+
+        let buffer = soundtrack
+            .load(&self.project_root)
+            .map_err(|e| log::error!("failed to decode soundtrack {:?}: {e}", soundtrack.path))
+            .ok();
+
+        self.decoded_soundtrack = Some((soundtrack.path.clone(), buffer.clone()));
+        buffer
+    }
 }
 
 impl eframe::App for App {
@@ -90,9 +129,20 @@ impl eframe::App for App {
 
         // self.save_dialog.show(ctx, &mut project);
 
-        self.interpreter_holder.load_demo(Demo {
-            graph: self.store.state().graph.clone(),
-        });
+        if self.loaded_revision != Some(self.store.revision()) {
+            let soundtrack = self.soundtrack();
+
+            self.interpreter_holder.load_demo(
+                Demo {
+                    graph: self.store.state().graph.clone(),
+                    soundtrack: self.store.state().soundtrack.clone(),
+                    library: self.store.state().library.clone(),
+                },
+                soundtrack,
+            );
+
+            self.loaded_revision = Some(self.store.revision());
+        }
 
         SidePanel::right("side panel")
             .min_width(500.0)
@@ -124,8 +174,8 @@ impl eframe::App for App {
                 let responses = self.node_editor_widget.show(ui, &self.store);
                 for response in responses {
                     match response {
-                        NodeEditorWidgetResponse::OpenInspector { node_id } => {
-                            self.node_inspector_widget = Some(NodeInspectorWidget { node_id })
+                        NodeEditorWidgetResponse::OpenInspector { path, node_id } => {
+                            self.node_inspector_widget = Some(NodeInspectorWidget { path, node_id })
                         }
                     }
                 }
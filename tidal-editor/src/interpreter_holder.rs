@@ -1,5 +1,6 @@
 use std::sync::{Arc, LockResult, Mutex, MutexGuard};
 
+use tidal_core::audio::AudioBuffer;
 use tidal_core::demo::Demo;
 use tidal_core::interpreter::Interpreter;
 
@@ -16,9 +17,12 @@ impl InterpreterHolder {
         self.0.lock().unwrap()
     }
 
-    pub fn load_demo(&mut self, demo: Demo) {
+    /// Replaces the interpreter with one evaluating `demo`, whose audio operators analyze
+    /// the decoded `soundtrack`.
+    pub fn load_demo(&mut self, demo: Demo, soundtrack: Option<AudioBuffer>) {
         let mut guard = self.0.lock().expect("mutex unlock");
 
-        *guard = Interpreter::new(demo)
+        *guard = Interpreter::new(demo);
+        guard.set_soundtrack(soundtrack);
     }
 }
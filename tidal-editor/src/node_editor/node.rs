@@ -13,6 +13,7 @@ use tidal_core::graph::PortId;
 use tidal_core::graph::{
     DataType, Graph, InputMetadata, Metadata, Node, NodeId, NodePortId, Placement,
 };
+use tidal_core::operator::Operator;
 
 use crate::drag::MinimumDrag;
 use crate::node_editor::node_editor_state::{
@@ -25,6 +26,8 @@ use crate::{color, rgb, rgba};
 #[derive(Debug, Copy, Clone)]
 pub(crate) enum NodeResponse {
     OpenInspector,
+    /// Shows the subgraph of a group node in the editor
+    EnterGroup,
     StartConnecting(PortId),
     FinishedConnecting,
     Drag(Vec2),
@@ -116,14 +119,18 @@ impl<'a> NodeWidget<'a> {
     fn draw_title(&mut self, ui: &mut Ui, node: &Node) -> Vec<NodeResponse> {
         let mut node_responses = vec![];
 
-        let metadata = node.operator.describe();
+        let metadata = node.describe();
 
         ui.centered_and_justified(|ui| {
             let label = RichText::new(&*metadata.name)
                 .text_style(TextStyle::Heading)
                 .strong();
 
-            let response = ui.label(label).interact(Sense::drag());
+            let mut response = ui.label(label).interact(Sense::drag());
+            if let Some(description) = metadata.description {
+                response = response.on_hover_text(description);
+            }
+
             if response.dragged() {
                 let delta = self.pan_zoom.descale_vector(response.drag_delta());
 
@@ -131,7 +138,11 @@ impl<'a> NodeWidget<'a> {
             }
 
             if response.double_clicked() {
-                node_responses.push(NodeResponse::OpenInspector);
+                if node.operator == Operator::group() {
+                    node_responses.push(NodeResponse::EnterGroup);
+                } else {
+                    node_responses.push(NodeResponse::OpenInspector);
+                }
             }
         });
 
@@ -162,8 +173,13 @@ impl<'a> NodeWidget<'a> {
                     }
                 }
 
+                let missing = input_meta.required
+                    && !matches!(input_state, InputState::Connection(cs) if !cs.is_empty());
+
                 let input_placements = if show_all {
                     match input_state {
+                        // A new connection replaces the one of a single value input.
+                        _ if !input_meta.multiple => vec![Placement::Replace(0)],
                         InputState::Constant(_) => vec![Placement::Replace(0)],
                         InputState::Connection(cs) => cs
                             .iter()
@@ -177,6 +193,8 @@ impl<'a> NodeWidget<'a> {
                     }
                 } else {
                     match input_state {
+                        // Keep missing required inputs visible, so they can be flagged.
+                        _ if missing => vec![Placement::Replace(0)],
                         InputState::Constant(_) => vec![],
                         InputState::Connection(cs) => cs
                             .iter()
@@ -218,7 +236,15 @@ impl<'a> NodeWidget<'a> {
                             }
                         });
 
-                        ui.label(&*input_meta.name);
+                        let label = if missing {
+                            ui.colored_label(ui.visuals().error_fg_color, &*input_meta.name)
+                                .on_hover_text("Required input, connect a node to it")
+                        } else {
+                            ui.label(&*input_meta.name)
+                        };
+                        if let Some(description) = input_meta.description {
+                            label.on_hover_text(description);
+                        }
                     });
                 }
             }
@@ -227,7 +253,7 @@ impl<'a> NodeWidget<'a> {
 
     fn draw_outputs(&mut self, ui: &mut Ui, node_id: NodeId, node: &Node) -> Vec<NodeResponse> {
         let mut node_responses = vec![];
-        let metadata = node.operator.describe();
+        let metadata = node.describe();
 
         let layout = Layout::top_down(Align::Max);
         ui.with_layout(layout, |ui| {
@@ -239,7 +265,7 @@ impl<'a> NodeWidget<'a> {
                         .draw_port_symbol(ui, output_meta.data_type)
                         .interact(Sense::click_and_drag());
 
-                    ui.label(output_meta.name);
+                    ui.label(&*output_meta.name);
 
                     if response.drag_started() {
                         node_responses.push(NodeResponse::StartConnecting(input_id));
@@ -264,10 +290,23 @@ impl<'a> NodeWidget<'a> {
 
         let fill = match data_type {
             DataType::Scalar => rgb!("709E68"),
+            DataType::Int => rgb!("4E8A5E"),
+            DataType::Bool => rgb!("A35F7E"),
+            DataType::String => rgb!("B38FB8"),
+            DataType::Vector2 => rgb!("6E9E86"),
             DataType::Vector => rgb!("6E9E9C"),
+            DataType::Vector4 => rgb!("6E889E"),
+            DataType::Color => rgb!("C9A24D"),
             DataType::Mesh => rgb!("57649E"),
             DataType::Texture => rgb!("9E4C52"),
             DataType::Command => rgb!("9E2A9E"),
+            DataType::ScalarCurve => rgb!("8CB37F"),
+            DataType::VectorCurve => rgb!("85B3B0"),
+            DataType::Timeline => rgb!("B3785B"),
+            DataType::Expression => rgb!("9E9E6E"),
+            DataType::Group => rgb!("7A7A9E"),
+            DataType::GroupPorts => rgb!("8A8A8A"),
+            DataType::Enum => rgb!("9E7AB3"),
         };
 
         let size = self.pan_zoom.scale_vector(Vec2::splat(RADIUS));
@@ -39,15 +39,24 @@ pub struct NodeEditorWidget {
 
 #[derive(Debug)]
 pub enum NodeEditorWidgetResponse {
-    OpenInspector { node_id: NodeId },
+    OpenInspector { path: Vec<NodeId>, node_id: NodeId },
 }
 
 impl NodeEditorWidget {
     pub fn show(&mut self, ui: &mut Ui, store: &Store) -> Vec<NodeEditorWidgetResponse> {
-        let graph = &store.state().graph;
+        // The group being edited may be gone after an undo.
+        let graph = match store.state().graph.group_graph(&self.state.path) {
+            Some(graph) => graph,
+            None => {
+                self.state.path.clear();
+                &store.state().graph
+            }
+        };
 
         let mut node_editor_responses = vec![];
 
+        self.draw_breadcrumb(ui, store);
+
         let mut transient_state = TransientState::default();
         let mut is_hovering_node = false;
         let mut delayed_node_responses = vec![];
@@ -119,9 +128,9 @@ impl NodeEditorWidget {
                     };
 
                     match connection_widget.show(ui) {
-                        ConnectionWidgetResponse::Disconnected { input, index } => {
-                            store.dispatch(GraphCommand::Disconnect { input, index })
-                        }
+                        ConnectionWidgetResponse::Disconnected { input, index } => store.dispatch(
+                            GraphCommand::Disconnect { input, index }.at(&self.state.path),
+                        ),
                         ConnectionWidgetResponse::Default => {}
                     };
                 });
@@ -133,7 +142,15 @@ impl NodeEditorWidget {
         for (node_id, node_response) in delayed_node_responses {
             match node_response {
                 NodeResponse::OpenInspector => {
-                    node_editor_responses.push(NodeEditorWidgetResponse::OpenInspector { node_id })
+                    node_editor_responses.push(NodeEditorWidgetResponse::OpenInspector {
+                        path: self.state.path.clone(),
+                        node_id,
+                    })
+                }
+                NodeResponse::EnterGroup => {
+                    self.state.path.push(node_id);
+                    self.state.selected_nodes.clear();
+                    self.state.interaction = Interaction::Default;
                 }
                 NodeResponse::StartConnecting(port_id) => {
                     self.state.interaction = Interaction::Connecting {
@@ -146,11 +163,14 @@ impl NodeEditorWidget {
                         &mut self.state.interaction
                     {
                         if let Some((input, placement)) = *candidate {
-                            store.dispatch(GraphCommand::ConnectNode {
-                                output: *output,
-                                input,
-                                placement,
-                            })
+                            store.dispatch(
+                                GraphCommand::ConnectNode {
+                                    output: *output,
+                                    input,
+                                    placement,
+                                }
+                                .at(&self.state.path),
+                            )
                         }
                     }
 
@@ -164,17 +184,23 @@ impl NodeEditorWidget {
                     {
                         self.state.selected_nodes.clear();
 
-                        store.dispatch(GraphCommand::MoveNodes {
-                            node_ids: HashSet::from([node_id]),
-                            delta,
-                        });
+                        store.dispatch(
+                            GraphCommand::MoveNodes {
+                                node_ids: HashSet::from([node_id]),
+                                delta,
+                            }
+                            .at(&self.state.path),
+                        );
                     }
                     // If multiple, check if this node is part of the selected nodes.
                     else {
-                        store.dispatch(GraphCommand::MoveNodes {
-                            node_ids: self.state.selected_nodes.clone(),
-                            delta,
-                        });
+                        store.dispatch(
+                            GraphCommand::MoveNodes {
+                                node_ids: self.state.selected_nodes.clone(),
+                                delta,
+                            }
+                            .at(&self.state.path),
+                        );
                     }
                 }
                 NodeResponse::Hovering => is_hovering_node = true,
@@ -192,14 +218,52 @@ impl NodeEditorWidget {
         node_editor_responses
     }
 
+    /// Shows the groups entered to reach the graph being edited, letting the user go back out.
+    fn draw_breadcrumb(&mut self, ui: &mut Ui, store: &Store) {
+        if self.state.path.is_empty() {
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("Demo").clicked() {
+                self.state.path.clear();
+                self.state.selected_nodes.clear();
+                return;
+            }
+
+            for depth in 1..=self.state.path.len() {
+                let Some(subgraph) = store.state().graph.group(&self.state.path[..depth]) else {
+                    break;
+                };
+
+                ui.label(">");
+
+                if ui.button(&subgraph.name).clicked() {
+                    self.state.path.truncate(depth);
+                    self.state.selected_nodes.clear();
+                    break;
+                }
+            }
+        });
+    }
+
     fn draw_picker_widget(&mut self, ui: &mut Ui, store: &Store) {
         if let Some(picker_widget) = &self.picker_widget {
-            match picker_widget.show(ui) {
+            match picker_widget.show(ui, &store.state().library) {
                 PickerResponse::Close => {
                     self.picker_widget = None;
                 }
                 PickerResponse::CreateNode { operator, position } => {
-                    store.dispatch(GraphCommand::CreateNode { operator, position });
+                    store.dispatch(
+                        GraphCommand::CreateNode { operator, position }.at(&self.state.path),
+                    );
+
+                    self.picker_widget = None;
+                }
+                PickerResponse::CreateGroup { subgraph, position } => {
+                    store.dispatch(
+                        GraphCommand::CreateGroup { subgraph, position }.at(&self.state.path),
+                    );
 
                     self.picker_widget = None;
                 }
@@ -33,6 +33,8 @@ pub(crate) struct NodeEditorState {
     pub pan_zoom: PanZoom,
     pub picker_opened: bool,
     pub selected_nodes: HashSet<NodeId>,
+    /// Group nodes entered from the demo's graph to reach the graph being edited
+    pub path: Vec<NodeId>,
 }
 
 #[derive(Debug, Default)]
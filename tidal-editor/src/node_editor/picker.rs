@@ -1,3 +1,5 @@
+use std::collections::BTreeSet;
+
 use derive_more::Constructor;
 use eframe::egui::{
     vec2, Align, Align2, Area, Button, InnerResponse, Layout, Pos2, Rect, Shape, Ui, Vec2,
@@ -5,6 +7,7 @@ use eframe::egui::{
 use eframe::epaint::RectShape;
 
 use tidal_core::cgmath::{Vector2, Zero};
+use tidal_core::graph::{Metadata, Subgraph};
 use tidal_core::operator::Operator;
 
 use crate::rgba;
@@ -24,12 +27,16 @@ pub enum PickerResponse {
         operator: Operator,
         position: Vector2<f32>,
     },
+    CreateGroup {
+        subgraph: Subgraph,
+        position: Vector2<f32>,
+    },
     #[default]
     None,
 }
 
 impl PickerWidget {
-    pub fn show(&self, ui: &mut Ui) -> PickerResponse {
+    pub fn show(&self, ui: &mut Ui, library: &[Subgraph]) -> PickerResponse {
         let background_idx = ui.painter().add(Shape::Noop);
 
         let InnerResponse {
@@ -38,18 +45,45 @@ impl PickerWidget {
         } = Area::new("picker")
             .fixed_pos(self.position)
             .show(ui.ctx(), |ui| {
-                for operator in Operator::all() {
-                    if operator.0 != "Scene" {
-                        if ui.button(&*operator.describe().name).clicked() {
-                            return PickerResponse::CreateNode {
-                                operator,
+                // The nodes of a subgraph's ports come with it.
+                let hidden = [
+                    Operator::scene(),
+                    Operator::group_input(),
+                    Operator::group_output(),
+                ];
+
+                let mut entries: Vec<PickerEntry> = Operator::all()
+                    .into_iter()
+                    .filter(|operator| !hidden.contains(operator))
+                    .map(PickerEntry::new)
+                    .collect();
+                entries.sort_by_key(|entry| entry.metadata.name);
+
+                let response = self.show_entries(ui, &entries, 0);
+                if !matches!(response, PickerResponse::None) {
+                    return response;
+                }
+
+                if library.is_empty() {
+                    return PickerResponse::None;
+                }
+
+                ui.menu_button("Library", |ui| {
+                    for subgraph in library {
+                        if ui.button(&subgraph.name).clicked() {
+                            ui.close_menu();
+
+                            return PickerResponse::CreateGroup {
+                                subgraph: subgraph.clone(),
                                 position: self.create_at_position,
                             };
                         }
                     }
-                }
 
-                PickerResponse::None
+                    PickerResponse::None
+                })
+                .inner
+                .unwrap_or_default()
             });
 
         ui.painter().set(
@@ -66,4 +100,72 @@ impl PickerWidget {
 
         picker_response
     }
+
+    /// Shows a menu for each category of `entries` below `depth`, followed by a button for each
+    /// entry whose category ends at `depth`.
+    fn show_entries(&self, ui: &mut Ui, entries: &[PickerEntry], depth: usize) -> PickerResponse {
+        let categories: BTreeSet<&str> = entries
+            .iter()
+            .filter_map(|entry| entry.category.get(depth).copied())
+            .collect();
+
+        for category in categories {
+            let entries: Vec<PickerEntry> = entries
+                .iter()
+                .filter(|entry| entry.category.get(depth) == Some(&category))
+                .cloned()
+                .collect();
+
+            let response = ui
+                .menu_button(category, |ui| self.show_entries(ui, &entries, depth + 1))
+                .inner
+                .unwrap_or_default();
+
+            if !matches!(response, PickerResponse::None) {
+                return response;
+            }
+        }
+
+        for entry in entries.iter().filter(|entry| entry.category.len() == depth) {
+            let mut button = ui.button(entry.metadata.name);
+            if let Some(description) = entry.metadata.description {
+                button = button.on_hover_text(description);
+            }
+
+            if button.clicked() {
+                ui.close_menu();
+
+                return PickerResponse::CreateNode {
+                    operator: entry.operator.clone(),
+                    position: self.create_at_position,
+                };
+            }
+        }
+
+        PickerResponse::None
+    }
+}
+
+/// Operator listed by the picker, under the menus of its category.
+#[derive(Debug, Clone)]
+struct PickerEntry {
+    operator: Operator,
+    metadata: Metadata,
+    category: Vec<&'static str>,
+}
+
+impl PickerEntry {
+    fn new(operator: Operator) -> Self {
+        let metadata = operator.describe();
+        let category = metadata
+            .category
+            .map(|category| category.split('/').collect())
+            .unwrap_or_default();
+
+        Self {
+            operator,
+            metadata,
+            category,
+        }
+    }
 }
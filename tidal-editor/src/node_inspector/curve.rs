@@ -0,0 +1,117 @@
+use eframe::egui::{ComboBox, DragValue, Grid, Ui, Widget};
+
+use tidal_core::animation::{Curve, Interpolate, Interpolation, Keyframe};
+use tidal_core::cgmath::Vector3;
+
+const INTERPOLATIONS: [(Interpolation, &str); 4] = [
+    (Interpolation::Step, "Step"),
+    (Interpolation::Linear, "Linear"),
+    (Interpolation::Bezier, "Bezier"),
+    (Interpolation::Hermite, "Hermite"),
+];
+
+/// Values editable by the curve editor.
+pub trait CurveValue: Interpolate {
+    fn show(ui: &mut Ui, value: &mut Self) -> bool;
+}
+
+impl CurveValue for f32 {
+    fn show(ui: &mut Ui, value: &mut Self) -> bool {
+        DragValue::new(value).speed(0.1).ui(ui).changed()
+    }
+}
+
+impl CurveValue for Vector3<f32> {
+    fn show(ui: &mut Ui, value: &mut Self) -> bool {
+        let mut changed = false;
+
+        changed |= DragValue::new(&mut value.x).speed(0.1).ui(ui).changed();
+        changed |= DragValue::new(&mut value.y).speed(0.1).ui(ui).changed();
+        changed |= DragValue::new(&mut value.z).speed(0.1).ui(ui).changed();
+
+        changed
+    }
+}
+
+/// Shows the keyframes of a curve. Returns the edited curve if anything changed.
+pub fn show_curve<V: CurveValue>(
+    ui: &mut Ui,
+    id: impl std::hash::Hash + Copy,
+    curve: &Curve<V>,
+) -> Option<Curve<V>> {
+    let mut edited: Option<Curve<V>> = None;
+
+    ui.vertical(|ui| {
+        Grid::new(("curve", id)).num_columns(4).show(ui, |ui| {
+            for (index, keyframe) in curve.keyframes().iter().enumerate() {
+                let mut keyframe = *keyframe;
+                let mut changed = false;
+                let mut removed = false;
+
+                changed |= DragValue::new(&mut keyframe.time)
+                    .speed(0.01)
+                    .suffix("s")
+                    .ui(ui)
+                    .changed();
+
+                ui.horizontal(|ui| changed |= V::show(ui, &mut keyframe.value));
+
+                ComboBox::from_id_source(("interpolation", id, index))
+                    .selected_text(interpolation_name(keyframe.interpolation))
+                    .show_ui(ui, |ui| {
+                        for (interpolation, name) in INTERPOLATIONS {
+                            changed |= ui
+                                .selectable_value(&mut keyframe.interpolation, interpolation, name)
+                                .changed();
+                        }
+                    });
+
+                removed |= ui.small_button("✖").clicked();
+                ui.end_row();
+
+                // Tangents only matter for curved interpolations.
+                if matches!(
+                    keyframe.interpolation,
+                    Interpolation::Bezier | Interpolation::Hermite
+                ) {
+                    ui.label("in / out");
+                    ui.horizontal(|ui| {
+                        changed |= V::show(ui, &mut keyframe.in_tangent);
+                        ui.separator();
+                        changed |= V::show(ui, &mut keyframe.out_tangent);
+                    });
+                    ui.end_row();
+                }
+
+                if removed {
+                    let curve = edited.get_or_insert_with(|| curve.clone());
+                    curve.remove(index);
+                } else if changed {
+                    let curve = edited.get_or_insert_with(|| curve.clone());
+                    curve.update(index, keyframe);
+                }
+            }
+        });
+
+        if ui.button("Add keyframe").clicked() {
+            let last = curve.keyframes().last();
+            let time = last.map(|k| k.time + 1.0).unwrap_or(0.0);
+            let value = last.map(|k| k.value).unwrap_or_else(V::zero);
+
+            edited
+                .get_or_insert_with(|| curve.clone())
+                .insert(Keyframe::new(time, value, Interpolation::Linear));
+        }
+    });
+
+    edited
+}
+
+#[inline]
+fn interpolation_name(interpolation: Interpolation) -> &'static str {
+    INTERPOLATIONS
+        .iter()
+        .find(|(i, _)| *i == interpolation)
+        .map(|(_, name)| *name)
+        .unwrap_or_default()
+}
@@ -0,0 +1,80 @@
+use eframe::egui::{ComboBox, Grid, TextEdit, Ui, Widget};
+
+use tidal_core::graph::{DataType, GroupPort};
+
+/// Types a group can receive or output.
+const PORT_TYPES: [DataType; 13] = [
+    DataType::Scalar,
+    DataType::Int,
+    DataType::Bool,
+    DataType::String,
+    DataType::Vector2,
+    DataType::Vector,
+    DataType::Vector4,
+    DataType::Color,
+    DataType::Mesh,
+    DataType::Texture,
+    DataType::Command,
+    DataType::ScalarCurve,
+    DataType::VectorCurve,
+];
+
+/// Shows the ports declared by a `GroupInput` or `GroupOutput` node. Returns the edited ports
+/// if anything changed.
+pub fn show_group_ports(
+    ui: &mut Ui,
+    id: impl std::hash::Hash + Copy,
+    ports: &[GroupPort],
+) -> Option<Vec<GroupPort>> {
+    let mut edited: Option<Vec<GroupPort>> = None;
+
+    ui.vertical(|ui| {
+        Grid::new(("group ports", id))
+            .num_columns(3)
+            .show(ui, |ui| {
+                for (index, port) in ports.iter().enumerate() {
+                    let mut port = port.clone();
+                    let mut changed = false;
+
+                    changed |= TextEdit::singleline(&mut port.name)
+                        .desired_width(80.0)
+                        .ui(ui)
+                        .changed();
+
+                    ComboBox::from_id_source(("port type", id, index))
+                        .selected_text(format!("{:?}", port.data_type))
+                        .show_ui(ui, |ui| {
+                            for data_type in PORT_TYPES {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut port.data_type,
+                                        data_type,
+                                        format!("{data_type:?}"),
+                                    )
+                                    .changed();
+                            }
+                        });
+
+                    let removed = ui.small_button("✖").clicked();
+                    ui.end_row();
+
+                    if removed {
+                        edited.get_or_insert_with(|| ports.to_vec()).remove(index);
+                    } else if changed {
+                        edited.get_or_insert_with(|| ports.to_vec())[index] = port;
+                    }
+                }
+            });
+
+        if ui.button("Add port").clicked() {
+            edited
+                .get_or_insert_with(|| ports.to_vec())
+                .push(GroupPort {
+                    name: format!("port {}", ports.len() + 1),
+                    data_type: DataType::Scalar,
+                });
+        }
+    });
+
+    edited
+}
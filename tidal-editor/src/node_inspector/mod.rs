@@ -1,23 +1,37 @@
 use std::fmt::format;
 use std::fs::metadata;
 use std::ops::RangeInclusive;
+use std::rc::Rc;
 use std::vec;
 
 use derive_more::Constructor;
 use eframe::egui::panel::Side;
 use eframe::egui::{
-    vec2, Align, Align2, Area, CollapsingHeader, Color32, DragValue, Grid, Layout, Margin,
-    RichText, SidePanel, Slider, Ui, Vec2, Widget,
+    vec2, Align, Align2, Area, CollapsingHeader, Color32, ComboBox, DragValue, Grid, Layout,
+    Margin, RichText, SidePanel, Slider, TextEdit, Ui, Vec2, Widget,
 };
 
 use tidal_core::cgmath::Vector3;
+use tidal_core::color::Color;
+use tidal_core::expression::Expression;
 use tidal_core::graph::node::{Constant, InputState};
-use tidal_core::graph::{Graph, Metadata, Node, NodeId, NodePortId, PortId};
+use tidal_core::graph::{Graph, InputHint, Metadata, Node, NodeId, NodePortId, PortId, Subgraph};
+use tidal_core::operator::Operator;
 
+use crate::node_inspector::curve::show_curve;
+use crate::node_inspector::group::show_group_ports;
+use crate::node_inspector::timeline::show_timeline;
 use crate::state::graph::GraphCommand;
+use crate::state::group::GroupCommand;
 use crate::state::store::Store;
 
+mod curve;
+mod group;
+mod timeline;
+
 pub struct NodeInspectorWidget {
+    /// Group nodes entered from the demo's graph to reach the node's graph
+    pub path: Vec<NodeId>,
     pub node_id: NodeId,
 }
 
@@ -28,19 +42,26 @@ pub enum InspectorWidgetResponse {
 
 impl NodeInspectorWidget {
     pub fn show(&mut self, ui: &mut Ui, store: &Store) -> Vec<InspectorWidgetResponse> {
-        let graph = &store.state().graph;
+        let Some(graph) = store.state().graph.group_graph(&self.path) else {
+            return vec![InspectorWidgetResponse::Close];
+        };
 
         let Some(node) = graph.get_node(self.node_id) else {
             return vec![InspectorWidgetResponse::Close];
         };
 
-        let metadata = node.operator.describe();
+        let metadata = node.describe();
 
         ui.set_width(ui.available_width());
 
         let mut responses = vec![];
 
         self.show_title(ui, &metadata, &mut responses);
+
+        if self.node_id.is_root() && self.path.is_empty() {
+            Self::show_root_operator(ui, node, store);
+        }
+
         self.show_inputs(ui, self.node_id, node, &metadata, &mut responses, store);
 
         responses
@@ -54,10 +75,28 @@ impl NodeInspectorWidget {
     ) {
         ui.vertical(|ui| {
             ui.heading(&*metadata.name);
+            if let Some(description) = metadata.description {
+                ui.label(description);
+            }
             ui.add_space(10.0);
         });
     }
 
+    /// Lets the root node switch between rendering a single scene and a sequence of them.
+    fn show_root_operator(ui: &mut Ui, node: &Node, store: &Store) {
+        ui.horizontal(|ui| {
+            for operator in [Operator::scene(), Operator::sequencer()] {
+                let selected = node.operator == operator;
+
+                if ui.selectable_label(selected, &*operator.0).clicked() && !selected {
+                    store.dispatch(GraphCommand::SetRootOperator { operator });
+                }
+            }
+        });
+
+        ui.add_space(10.0);
+    }
+
     fn show_inputs(
         &mut self,
         ui: &mut Ui,
@@ -70,7 +109,10 @@ impl NodeInspectorWidget {
         Self::wrap_ports(ui, "Inputs", |ui| {
             Grid::new("ports").num_columns(2).show(ui, |ui| {
                 for (port_id, state, input_metadata) in node.iter_described_inputs() {
-                    ui.label(&*input_metadata.name);
+                    let label = ui.label(&*input_metadata.name);
+                    if let Some(description) = input_metadata.description {
+                        label.on_hover_text(description);
+                    }
 
                     match state {
                         InputState::Constant(c) => {
@@ -81,7 +123,9 @@ impl NodeInspectorWidget {
 
                                     ui.horizontal(|ui| {
                                         changed |=
-                                            DragValue::new(&mut value).speed(0.1).ui(ui).changed()
+                                            hinted_drag_value(&mut value, &input_metadata.hint)
+                                                .ui(ui)
+                                                .changed()
                                     });
 
                                     changed.then_some(Constant::Scalar(value))
@@ -104,17 +148,142 @@ impl NodeInspectorWidget {
 
                                     changed.then_some(Constant::Vector((Vector3::new(x, y, z))))
                                 }
-                                Constant::I32(_) => None,
+                                Constant::Vector2(v) => {
+                                    let mut v = *v;
+                                    let mut changed = false;
+
+                                    ui.horizontal(|ui| {
+                                        changed |=
+                                            DragValue::new(&mut v.x).speed(0.1).ui(ui).changed();
+                                        changed |=
+                                            DragValue::new(&mut v.y).speed(0.1).ui(ui).changed();
+                                    });
+
+                                    changed.then_some(Constant::Vector2(v))
+                                }
+                                Constant::Vector4(v) => {
+                                    let mut v = *v;
+                                    let mut changed = false;
+
+                                    ui.horizontal(|ui| {
+                                        changed |=
+                                            DragValue::new(&mut v.x).speed(0.1).ui(ui).changed();
+                                        changed |=
+                                            DragValue::new(&mut v.y).speed(0.1).ui(ui).changed();
+                                        changed |=
+                                            DragValue::new(&mut v.z).speed(0.1).ui(ui).changed();
+                                        changed |=
+                                            DragValue::new(&mut v.w).speed(0.1).ui(ui).changed();
+                                    });
+
+                                    changed.then_some(Constant::Vector4(v))
+                                }
+                                Constant::Color(color) => {
+                                    let mut rgba: [f32; 4] = (*color).into();
+
+                                    ui.color_edit_button_rgba_unmultiplied(&mut rgba)
+                                        .changed()
+                                        .then(|| {
+                                            let [r, g, b, a] = rgba;
+                                            Constant::Color(Color::new(r, g, b, a))
+                                        })
+                                }
+                                Constant::Int(value) => {
+                                    let mut value = *value;
+
+                                    hinted_drag_value(&mut value, &input_metadata.hint)
+                                        .ui(ui)
+                                        .changed()
+                                        .then_some(Constant::Int(value))
+                                }
+                                Constant::Bool(value) => {
+                                    let mut value = *value;
+
+                                    ui.checkbox(&mut value, "")
+                                        .changed()
+                                        .then_some(Constant::Bool(value))
+                                }
+                                Constant::String(value) => {
+                                    let mut value = value.clone();
+
+                                    TextEdit::singleline(&mut value)
+                                        .ui(ui)
+                                        .changed()
+                                        .then_some(Constant::String(value))
+                                }
+                                Constant::ScalarCurve(curve) => show_curve(ui, port_id, curve)
+                                    .map(|curve| Constant::ScalarCurve(Rc::new(curve))),
+                                Constant::VectorCurve(curve) => show_curve(ui, port_id, curve)
+                                    .map(|curve| Constant::VectorCurve(Rc::new(curve))),
+                                Constant::Timeline(timeline) => {
+                                    show_timeline(ui, port_id, timeline)
+                                        .map(|timeline| Constant::Timeline(Rc::new(timeline)))
+                                }
+                                Constant::Group(subgraph) => {
+                                    self.show_group(ui, subgraph, store);
+                                    None
+                                }
+                                Constant::GroupPorts(ports) => show_group_ports(ui, port_id, ports)
+                                    .map(|ports| Constant::GroupPorts(ports.into())),
+                                Constant::Enum(name) => {
+                                    let mut selected = &**name;
+
+                                    ComboBox::from_id_source(("enum", port_id))
+                                        .selected_text(selected)
+                                        .show_ui(ui, |ui| {
+                                            for variant in input_metadata.variants {
+                                                ui.selectable_value(
+                                                    &mut selected,
+                                                    *variant,
+                                                    *variant,
+                                                );
+                                            }
+                                        });
+
+                                    (selected != &**name).then(|| Constant::Enum(selected.into()))
+                                }
+                                Constant::Expression(expression) => {
+                                    let mut source = expression.source().to_owned();
+                                    let mut changed = false;
+
+                                    ui.vertical(|ui| {
+                                        changed |= TextEdit::singleline(&mut source)
+                                            .id_source(("expression", port_id))
+                                            .code_editor()
+                                            .ui(ui)
+                                            .changed();
+
+                                        if let Some(error) = expression.error() {
+                                            ui.colored_label(
+                                                ui.visuals().error_fg_color,
+                                                error.to_string(),
+                                            );
+                                        }
+                                    });
+
+                                    changed.then(|| {
+                                        Constant::Expression(Rc::new(Expression::new(source)))
+                                    })
+                                }
                             };
 
                             if let Some(constant) = updated_constant {
-                                store.dispatch(GraphCommand::ChangeConstant {
-                                    node_id,
-                                    port_id,
-                                    constant,
-                                });
+                                store.dispatch(
+                                    GraphCommand::ChangeConstant {
+                                        node_id,
+                                        port_id,
+                                        constant,
+                                    }
+                                    .at(&self.path),
+                                );
                             }
                         }
+                        InputState::Connection(cs) if cs.is_empty() && input_metadata.required => {
+                            ui.colored_label(ui.visuals().error_fg_color, "required");
+                        }
+                        InputState::Connection(cs) if cs.is_empty() && input_metadata.optional => {
+                            ui.weak("none");
+                        }
                         InputState::Connection(_) => {
                             ui.label("connected :)");
                         }
@@ -126,6 +295,34 @@ impl NodeInspectorWidget {
         });
     }
 
+    /// Shows the name of a group's subgraph and whether it is a library asset.
+    fn show_group(&self, ui: &mut Ui, subgraph: &Subgraph, store: &Store) {
+        let path: Vec<NodeId> = self.path.iter().copied().chain([self.node_id]).collect();
+
+        ui.vertical(|ui| {
+            let mut name = subgraph.name.clone();
+
+            if TextEdit::singleline(&mut name).ui(ui).changed() {
+                store.dispatch(GroupCommand::Rename {
+                    path: path.clone(),
+                    name,
+                });
+            }
+
+            let in_library = store
+                .state()
+                .library
+                .iter()
+                .any(|asset| asset.id == subgraph.id);
+
+            if in_library {
+                ui.label("library asset");
+            } else if ui.button("Add to library").clicked() {
+                store.dispatch(GroupCommand::AddToLibrary { path });
+            }
+        });
+    }
+
     // fn show_outputs(&self, metadata: &Metadata, ui: &mut Ui) {
     //     Self::wrap_ports(ui, "Outputs", |ui| {});
     // }
@@ -138,3 +335,16 @@ impl NodeInspectorWidget {
             .show(ui, add_contents);
     }
 }
+
+/// Drag value bounded to the range of an input's hint, moving by its step.
+fn hinted_drag_value<'a, Num: eframe::emath::Numeric>(
+    value: &'a mut Num,
+    hint: &InputHint,
+) -> DragValue<'a> {
+    let min = hint.min.map_or(f64::NEG_INFINITY, f64::from);
+    let max = hint.max.map_or(f64::INFINITY, f64::from);
+
+    DragValue::new(value)
+        .speed(hint.step.unwrap_or(0.1))
+        .clamp_range(min..=max)
+}
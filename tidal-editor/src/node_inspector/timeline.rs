@@ -0,0 +1,101 @@
+use eframe::egui::{ComboBox, DragValue, Grid, TextEdit, Ui, Widget};
+
+use tidal_core::sequencer::{Clip, Timeline, Transition};
+
+/// Shows the clips of a timeline. Returns the edited timeline if anything changed.
+pub fn show_timeline(
+    ui: &mut Ui,
+    id: impl std::hash::Hash + Copy,
+    timeline: &Timeline,
+) -> Option<Timeline> {
+    let mut edited: Option<Timeline> = None;
+
+    ui.vertical(|ui| {
+        Grid::new(("timeline", id)).num_columns(5).show(ui, |ui| {
+            for (index, clip) in timeline.clips().iter().enumerate() {
+                let mut clip = clip.clone();
+                let mut changed = false;
+
+                changed |= TextEdit::singleline(&mut clip.name)
+                    .desired_width(80.0)
+                    .ui(ui)
+                    .changed();
+
+                ui.horizontal(|ui| {
+                    changed |= DragValue::new(&mut clip.start)
+                        .speed(0.1)
+                        .suffix("s")
+                        .ui(ui)
+                        .changed();
+                    changed |= DragValue::new(&mut clip.end)
+                        .speed(0.1)
+                        .suffix("s")
+                        .ui(ui)
+                        .changed();
+                });
+
+                changed |= DragValue::new(&mut clip.scene)
+                    .prefix("scene ")
+                    .ui(ui)
+                    .changed();
+
+                ui.horizontal(|ui| {
+                    let is_crossfade = matches!(clip.transition, Transition::Crossfade { .. });
+
+                    ComboBox::from_id_source(("transition", id, index))
+                        .selected_text(if is_crossfade { "Crossfade" } else { "Cut" })
+                        .show_ui(ui, |ui| {
+                            if ui.selectable_label(!is_crossfade, "Cut").clicked() {
+                                clip.transition = Transition::Cut;
+                                changed = true;
+                            }
+
+                            if ui.selectable_label(is_crossfade, "Crossfade").clicked()
+                                && !is_crossfade
+                            {
+                                clip.transition = Transition::Crossfade { duration: 1.0 };
+                                changed = true;
+                            }
+                        });
+
+                    if let Transition::Crossfade { duration } = &mut clip.transition {
+                        changed |= DragValue::new(duration)
+                            .speed(0.1)
+                            .clamp_range(0.0..=f32::MAX)
+                            .suffix("s")
+                            .ui(ui)
+                            .changed();
+                    }
+                });
+
+                let removed = ui.small_button("✖").clicked();
+                ui.end_row();
+
+                if removed {
+                    edited.get_or_insert_with(|| timeline.clone()).remove(index);
+                } else if changed {
+                    clip.end = clip.end.max(clip.start);
+
+                    edited
+                        .get_or_insert_with(|| timeline.clone())
+                        .update(index, clip);
+                }
+            }
+        });
+
+        if ui.button("Add clip").clicked() {
+            let start = timeline.duration();
+            let scene = timeline.clips().len();
+
+            edited.get_or_insert_with(|| timeline.clone()).insert(Clip {
+                name: format!("Clip {}", scene + 1),
+                start,
+                end: start + 10.0,
+                scene,
+                transition: Transition::Cut,
+            });
+        }
+    });
+
+    edited
+}
@@ -4,7 +4,8 @@ use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
-use tidal_core::graph::Graph;
+use tidal_core::demo::Soundtrack;
+use tidal_core::graph::{Graph, Subgraph};
 
 #[derive(Debug, Clone)]
 pub struct ProjectStorageDetails {
@@ -15,6 +16,10 @@ pub struct ProjectStorageDetails {
 #[derive(Serialize, Deserialize)]
 pub struct ProjectState {
     pub graph: Graph,
+    #[serde(default)]
+    pub library: Vec<Subgraph>,
+    #[serde(default)]
+    pub soundtrack: Option<Soundtrack>,
 }
 
 #[derive(Serialize, Deserialize)]
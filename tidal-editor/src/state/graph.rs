@@ -5,9 +5,10 @@ use derive_more::Constructor;
 use eframe::egui::Vec2;
 
 use tidal_core::cgmath::Vector2;
-use tidal_core::graph::{Constant, Graph, InputState, Node, NodeId, NodePortId, Placement, PortId};
+use tidal_core::graph::{Constant, Graph, Node, NodeId, NodePortId, Placement, PortId, Subgraph};
 use tidal_core::operator::Operator;
 
+use crate::state::group::GroupCommand;
 use crate::state::store::Command;
 use crate::state::State;
 
@@ -39,6 +40,14 @@ pub enum GraphCommand {
         port_id: PortId,
         constant: Constant,
     },
+    SetRootOperator {
+        operator: Operator,
+    },
+    /// Creates an instance of a library asset.
+    CreateGroup {
+        subgraph: Subgraph,
+        position: Vector2<f32>,
+    },
 }
 
 impl GraphCommand {
@@ -75,11 +84,29 @@ impl GraphCommand {
                 constant,
             } => {
                 if let Some(node) = state.get_node_mut(*node_id) {
-                    if let Some(port) = node.get_input_state_mut(*port_id) {
-                        *port = InputState::Constant(*constant)
-                    }
+                    node.set_constant(*port_id, constant.clone());
                 }
             }
+            GraphCommand::SetRootOperator { operator } => state.set_root_operator(operator.clone()),
+            GraphCommand::CreateGroup { subgraph, position } => {
+                let mut node = Node::new(Operator::group(), *position);
+                node.set_subgraph(subgraph.clone());
+
+                state.nodes.push(node);
+            }
+        }
+    }
+
+    /// Command applying this one to the graph of the group at `path`, or to the demo's graph
+    /// when `path` is empty.
+    pub fn at(self, path: &[NodeId]) -> Command {
+        if path.is_empty() {
+            Command::Graph(self)
+        } else {
+            Command::Group(GroupCommand::Edit {
+                path: path.to_vec(),
+                command: self,
+            })
         }
     }
 
@@ -0,0 +1,89 @@
+use tidal_core::graph::{NodeId, Subgraph};
+
+use crate::state::graph::GraphCommand;
+use crate::state::State;
+
+#[derive(Debug, Clone)]
+pub enum GroupCommand {
+    /// Applies a command to the subgraph of the group at `path`.
+    Edit {
+        path: Vec<NodeId>,
+        command: GraphCommand,
+    },
+    Rename {
+        path: Vec<NodeId>,
+        name: String,
+    },
+    /// Saves the subgraph of the group at `path` as a library asset.
+    AddToLibrary {
+        path: Vec<NodeId>,
+    },
+}
+
+impl GroupCommand {
+    pub fn apply(&self, state: &mut State) {
+        match self {
+            GroupCommand::Edit { path, command } => {
+                state.update_group(path, |subgraph| command.apply(&mut subgraph.graph))
+            }
+            GroupCommand::Rename { path, name } => {
+                state.update_group(path, |subgraph| subgraph.name = name.clone())
+            }
+            GroupCommand::AddToLibrary { path } => {
+                if let Some(subgraph) = state.graph.group(path) {
+                    if !state.library.iter().any(|asset| asset.id == subgraph.id) {
+                        state.library.push(subgraph.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn can_merge_with(&self, other: &GroupCommand) -> bool {
+        type C = GroupCommand;
+
+        match (self, other) {
+            (
+                C::Edit { path, command },
+                C::Edit {
+                    path: other_path,
+                    command: other_command,
+                },
+            ) => *path == *other_path && command.can_merge_with(other_command),
+            (
+                C::Rename { path, .. },
+                C::Rename {
+                    path: other_path, ..
+                },
+            ) => *path == *other_path,
+            _ => false,
+        }
+    }
+}
+
+impl State {
+    /// Edits the subgraph of the group at `path`, then replaces the library asset and the
+    /// other instances of it with the result.
+    fn update_group(&mut self, path: &[NodeId], f: impl FnOnce(&mut Subgraph)) {
+        let mut updated = None;
+
+        self.graph.update_group(path, |subgraph| {
+            f(subgraph);
+            updated = Some(subgraph.clone());
+        });
+
+        let Some(subgraph) = updated else {
+            return;
+        };
+
+        for asset in self.library.iter_mut() {
+            if asset.id == subgraph.id {
+                *asset = subgraph.clone();
+            } else {
+                asset.graph.update_groups(&subgraph);
+            }
+        }
+
+        self.graph.update_groups(&subgraph);
+    }
+}
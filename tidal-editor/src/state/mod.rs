@@ -1,6 +1,8 @@
-use tidal_core::graph::Graph;
+use tidal_core::demo::Soundtrack;
+use tidal_core::graph::{Graph, Subgraph};
 
 pub mod graph;
+pub mod group;
 pub mod store;
 
 /// State represents the entire state of the application. Doesn't include
@@ -8,4 +10,8 @@ pub mod store;
 #[derive(Debug, Clone)]
 pub struct State {
     pub graph: Graph,
+    /// Subgraphs saved as reusable assets
+    pub library: Vec<Subgraph>,
+    /// Music the demo is synced to
+    pub soundtrack: Option<Soundtrack>,
 }
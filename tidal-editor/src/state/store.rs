@@ -6,6 +6,7 @@ use delegate::delegate;
 use derive_more::From;
 
 use crate::state::graph::GraphCommand;
+use crate::state::group::GroupCommand;
 use crate::state::State;
 
 const MAXIMUM_DURATION: Duration = Duration::from_millis(100);
@@ -13,19 +14,22 @@ const MAXIMUM_DURATION: Duration = Duration::from_millis(100);
 #[derive(Debug, Clone, From)]
 pub enum Command {
     Graph(GraphCommand),
+    Group(GroupCommand),
 }
 
 impl Command {
     pub fn apply(&self, state: &mut State) {
         match self {
             Command::Graph(c) => c.apply(&mut state.graph),
+            Command::Group(c) => c.apply(state),
         }
     }
 
     pub fn can_merge_with(&self, other: &Command) -> bool {
         match (self, other) {
             (Command::Graph(a), Command::Graph(b)) => a.can_merge_with(b),
-            // _ -> false,
+            (Command::Group(a), Command::Group(b)) => a.can_merge_with(b),
+            _ => false,
         }
     }
 }
@@ -45,6 +49,8 @@ pub struct Store {
     undo: Vec<State>,
     redo: Vec<State>,
     last_command: Option<LastCommand>,
+    /// Incremented every time the state changes
+    revision: u64,
 }
 
 impl Store {
@@ -55,6 +61,7 @@ impl Store {
             undo: Default::default(),
             redo: Default::default(),
             last_command: None,
+            revision: 0,
         }
     }
 
@@ -62,6 +69,7 @@ impl Store {
         if let Some(state) = self.undo.pop() {
             self.redo.push(self.state.clone());
             self.state = state;
+            self.revision += 1;
         }
     }
 
@@ -69,6 +77,7 @@ impl Store {
         if let Some(state) = self.redo.pop() {
             self.undo.push(self.state.clone());
             self.state = state;
+            self.revision += 1;
         }
     }
 
@@ -88,8 +97,14 @@ impl Store {
         &self.state
     }
 
+    #[inline]
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
     fn apply(&mut self, command: Command) {
         let now = Instant::now();
+        self.revision += 1;
 
         if let Some(last) = &self.last_command {
             if last.command.can_merge_with(&command) {
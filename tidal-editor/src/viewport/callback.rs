@@ -4,6 +4,7 @@ use eframe::egui::{PaintCallback, PaintCallbackInfo, Rect};
 use eframe::egui_wgpu::{Callback, CallbackResources, CallbackTrait};
 use eframe::wgpu::RenderPass;
 
+use tidal_core::cgmath::Vector2;
 use tidal_core::interpreter::{Interpreter, InterpreterContext};
 
 use crate::interpreter_holder::InterpreterHolder;
@@ -11,12 +12,16 @@ use crate::viewport::resource::ViewportCallbackResource;
 
 pub(crate) struct ViewportCallback {
     interpreter_holder: InterpreterHolder,
+    time: f32,
 }
 
 impl ViewportCallback {
     #[inline]
-    pub fn new(interpreter_holder: InterpreterHolder) -> Self {
-        Self { interpreter_holder }
+    pub fn new(interpreter_holder: InterpreterHolder, time: f32) -> Self {
+        Self {
+            interpreter_holder,
+            time,
+        }
     }
 }
 
@@ -32,11 +37,14 @@ impl CallbackTrait for ViewportCallback {
             .unwrap();
 
         let mut renderer = viewport_callback_resource.renderer.lock().unwrap();
+        let size = viewport_callback_resource.texture.size();
 
         let _ = self.interpreter_holder.lock().run(&mut InterpreterContext {
             renderer: &mut renderer,
             render_target: &viewport_callback_resource.texture_view,
+            render_target_size: Vector2::new(size.width, size.height),
             frame: 0.0,
+            time: self.time,
         });
 
         // Draw
@@ -1,4 +1,5 @@
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use delegate::delegate;
 use eframe::egui::{vec2, CollapsingHeader, Frame, InnerResponse, Response, Ui, Vec2, Widget};
@@ -18,6 +19,8 @@ mod resource;
 
 pub struct ViewportWidget {
     interpreter_holder: InterpreterHolder,
+    /// When the demo clock started
+    started: Instant,
 }
 
 impl ViewportWidget {
@@ -33,7 +36,10 @@ impl ViewportWidget {
             .callback_resources
             .insert(resource);
 
-        Self { interpreter_holder }
+        Self {
+            interpreter_holder,
+            started: Instant::now(),
+        }
     }
 
     pub(crate) fn show(&self, ui: &mut Ui) {
@@ -57,7 +63,8 @@ impl ViewportWidget {
         let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
 
         Frame::canvas(ui.style()).show(ui, |ui| {
-            let callback = ViewportCallback::new(self.interpreter_holder.clone());
+            let time = self.started.elapsed().as_secs_f32();
+            let callback = ViewportCallback::new(self.interpreter_holder.clone(), time);
 
             ui.painter()
                 .add(Callback::new_paint_callback(rect, callback));
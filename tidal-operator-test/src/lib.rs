@@ -0,0 +1,130 @@
+//! Operators defined outside tidal-core, the way a studio library would, checking
+//! `#[evaluator]` and the registration API work from other crates.
+
+use std::cell::RefCell;
+
+use tidal_core::evaluator;
+use tidal_core::graph::{Constant, DataType, InputState, Metadata, OperatorEnum, OutputMetadata};
+use tidal_core::interpreter::{EvaluateContext, EvaluateError};
+use tidal_core::renderer::CommandList;
+
+thread_local! {
+    static RECORDED: RefCell<Vec<f32>> = RefCell::new(vec![]);
+}
+
+/// Takes the values received by `Record` nodes on this thread, in evaluation order.
+pub fn take_recorded() -> Vec<f32> {
+    RECORDED.with(|recorded| recorded.take())
+}
+
+/// Multiplies its input by two.
+#[evaluator(DoubleEvaluator for Double)]
+#[category("Test/Math")]
+#[output(name = "value")]
+fn evaluate_double(
+    /// Number to double
+    #[input(min = -10.0, max = 10.0, step = 0.5)]
+    #[default(1.0)]
+    value: f32,
+) -> f32 {
+    value * 2.0
+}
+
+/// Counts the frames it was evaluated in, keeping the count as evaluator state.
+#[derive(Debug, Default)]
+pub struct CounterEvaluator {
+    count: i32,
+}
+
+#[evaluator(impl CounterEvaluator for Counter)]
+#[output(name = "count")]
+fn evaluate_counter(#[state] state: &mut CounterEvaluator) -> i32 {
+    state.count += 1;
+    state.count
+}
+
+#[evaluator(RecordEvaluator for Record)]
+fn evaluate_record(#[required] value: f32) {
+    RECORDED.with(|recorded| recorded.borrow_mut().push(value));
+}
+
+/// Records its value like `Record`, but outputs commands so it can be repeated.
+#[evaluator(RecordCommandsEvaluator for RecordCommands)]
+#[output(name = "commands")]
+fn evaluate_record_commands(#[required] value: f32) -> CommandList {
+    RECORDED.with(|recorded| recorded.borrow_mut().push(value));
+
+    CommandList::new()
+}
+
+/// Outputs `even` in even frames and `odd` in odd ones, at 60 frames per second.
+#[evaluator(AlternateEvaluator for Alternate)]
+#[output(name = "value")]
+fn evaluate_alternate(
+    #[context] ctx: &mut EvaluateContext,
+    #[default(0)] even: i32,
+    #[default(0)] odd: i32,
+) -> i32 {
+    if (ctx.time() * 60.0).round() as u32 % 2 == 0 {
+        even
+    } else {
+        odd
+    }
+}
+
+/// Operation applied by `Combine`.
+#[derive(OperatorEnum, Debug, Copy, Clone)]
+pub enum Combination {
+    Add,
+    Multiply,
+}
+
+/// Combines `a` with `b`, or outputs `a` when `b` isn't connected.
+#[evaluator(CombineEvaluator for Combine)]
+#[output(name = "value")]
+fn evaluate_combine(
+    #[default(Multiply)] operation: Combination,
+    #[default(1.0)] a: f32,
+    b: Option<f32>,
+) -> f32 {
+    match (operation, b) {
+        (Combination::Add, Some(b)) => a + b,
+        (Combination::Multiply, Some(b)) => a * b,
+        (_, None) => a,
+    }
+}
+
+/// Outputs of a `Spread` node whose count is connected, only known while evaluating.
+pub const MAXIMUM_SPREAD: i32 = 64;
+
+/// Outputs `count` values, the output `n` being `n * step`. Its outputs come from a constant
+/// `count`, so it can have more of them than any fixed limit.
+#[evaluator(SpreadEvaluator for Spread)]
+#[describe(describe_spread)]
+#[fallible]
+fn evaluate_spread(
+    #[context] ctx: &mut EvaluateContext,
+    #[default(24)] count: i32,
+    #[default(1.0)] step: f32,
+) -> Result<(), EvaluateError> {
+    for n in 0..count.max(0) as usize {
+        ctx.write_output(n, (n as f32 * step).into())?;
+    }
+
+    Ok(())
+}
+
+/// Adds an output per value. A connected count exposes [`MAXIMUM_SPREAD`] outputs, evaluating
+/// more of them fails.
+fn describe_spread(metadata: &mut Metadata, inputs: &[InputState]) {
+    let count = match inputs.first() {
+        Some(InputState::Constant(Constant::Int(count))) => (*count).max(0),
+        Some(InputState::Connection(connections)) if !connections.is_empty() => MAXIMUM_SPREAD,
+        _ => return,
+    };
+
+    metadata.outputs.extend((0..count).map(|n| OutputMetadata {
+        name: format!("value {n}").into(),
+        data_type: DataType::Scalar,
+    }));
+}
@@ -0,0 +1,541 @@
+use std::f32::consts::PI;
+
+use tidal_core::audio::AudioBuffer;
+use tidal_core::cgmath::{Vector2, Zero};
+use tidal_core::demo::Demo;
+use tidal_core::graph::{
+    Constant, DataType, Graph, InputHint, InputState, Node, NodeId, NodePortId, Placement, PortId,
+    ValidationError, ValidationErrorKind,
+};
+use tidal_core::interpreter::{EvaluateError, Interpreter, InterpreterContext};
+use tidal_core::operator::Operator;
+use tidal_core::renderer::{Context, Renderer};
+
+use tidal_operator_test::{take_recorded, MAXIMUM_SPREAD};
+
+const TARGET_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+struct HeadlessContext {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+}
+
+impl Context for HeadlessContext {
+    fn device(&self) -> &wgpu::Device {
+        &self.device
+    }
+
+    fn queue(&self) -> &wgpu::Queue {
+        &self.queue
+    }
+
+    fn surface_format(&self) -> wgpu::TextureFormat {
+        TARGET_FORMAT
+    }
+}
+
+/// Creates a renderer without a window and a texture to render to, or `None` on machines
+/// without any adapter.
+fn headless_renderer() -> Option<(Renderer, wgpu::TextureView)> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+
+    let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+        power_preference: wgpu::PowerPreference::default(),
+        force_fallback_adapter: false,
+        compatible_surface: None,
+    }))?;
+
+    let (device, queue) = pollster::block_on(adapter.request_device(
+        &wgpu::DeviceDescriptor {
+            label: None,
+            features: wgpu::Features::empty(),
+            limits: wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits()),
+        },
+        None,
+    ))
+    .ok()?;
+
+    let target = device
+        .create_texture(&wgpu::TextureDescriptor {
+            label: Some("Target"),
+            size: wgpu::Extent3d {
+                width: 4,
+                height: 4,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TARGET_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        })
+        .create_view(&Default::default());
+
+    let renderer = Renderer::new(Box::new(HeadlessContext { device, queue }));
+
+    Some((renderer, target))
+}
+
+fn operator(name: &str) -> Operator {
+    Operator(name.into())
+}
+
+/// Graph whose root records the value output by `node`, connected to it.
+fn record_graph(node: Node) -> Graph {
+    let mut graph = Graph {
+        nodes: vec![Node::new(operator("Record"), Vector2::zero()), node],
+    };
+
+    graph.connect(
+        &NodePortId(NodeId::from(1), PortId::from(0)),
+        &NodePortId(NodeId::root(), PortId::from(0)),
+        Placement::Replace(0),
+    );
+
+    graph
+}
+
+/// Runs `frames` frames of the demo, returning the values recorded in them.
+fn run(graph: Graph, frames: usize) -> Option<Vec<f32>> {
+    run_interpreter(
+        Interpreter::new(Demo {
+            graph,
+            ..Default::default()
+        }),
+        frames,
+    )
+}
+
+/// Runs `frames` frames at 60 frames per second, returning the values recorded in them.
+fn run_interpreter(interpreter: Interpreter, frames: usize) -> Option<Vec<f32>> {
+    let Some((mut renderer, target)) = headless_renderer() else {
+        eprintln!("no graphics adapter available, skipping");
+        return None;
+    };
+
+    take_recorded();
+
+    for frame in 0..frames {
+        interpreter
+            .run(&mut InterpreterContext {
+                renderer: &mut renderer,
+                render_target: &target,
+                render_target_size: Vector2::new(4, 4),
+                frame: frame as f32,
+                time: frame as f32 / 60.0,
+            })
+            .unwrap();
+    }
+
+    Some(take_recorded())
+}
+
+#[test]
+fn renders_scenes_on_devices_without_particles() {
+    // The headless device has WebGL2 limits, without compute shaders or storage buffers.
+    let mut graph = Graph {
+        nodes: vec![
+            Node::new(operator("Scene"), Vector2::zero()),
+            Node::new(operator("ParticleSystem"), Vector2::zero()),
+        ],
+    };
+
+    graph.connect(
+        &NodePortId(NodeId::from(1), PortId::from(0)),
+        &NodePortId(NodeId::root(), PortId::from(0)),
+        Placement::Replace(0),
+    );
+
+    if let Some(recorded) = run(graph, 3) {
+        assert_eq!(recorded, []);
+    }
+}
+
+#[test]
+fn renders_scenes_with_any_sample_count() {
+    let metadata = operator("Scene").describe();
+    let samples = &metadata.inputs[3];
+
+    assert_eq!(samples.data_type, DataType::Enum);
+    assert_eq!(samples.variants, ["X1", "X2", "X4", "X8"]);
+
+    // Counts the device lacks fall back to 4 samples.
+    for variant in ["X1", "X2", "X4", "X8"] {
+        let mut scene = Node::new(operator("Scene"), Vector2::zero());
+        scene.set_constant(PortId::from(3), Constant::Enum(variant.into()));
+
+        let mut graph = Graph {
+            nodes: vec![
+                scene,
+                Node::new(operator("ParticleSystem"), Vector2::zero()),
+            ],
+        };
+        graph.connect(
+            &NodePortId(NodeId::from(1), PortId::from(0)),
+            &NodePortId(NodeId::root(), PortId::from(0)),
+            Placement::Replace(0),
+        );
+        assert_eq!(graph.validate(), [], "{variant}");
+
+        if let Some(recorded) = run(graph, 1) {
+            assert_eq!(recorded, []);
+        }
+    }
+}
+
+#[test]
+fn registers_operators_of_other_crates() {
+    let double = operator("Double");
+
+    assert!(double.is_registered());
+    assert!(Operator::all().contains(&double));
+
+    let metadata = double.describe();
+
+    assert_eq!(metadata.name, "Double");
+    assert_eq!(metadata.description, Some("Multiplies its input by two."));
+    assert_eq!(metadata.category, Some("Test/Math"));
+    assert_eq!(metadata.inputs.len(), 1);
+    assert_eq!(metadata.inputs[0].name, "Value");
+    assert_eq!(metadata.inputs[0].data_type, DataType::Scalar);
+    assert!(matches!(
+        metadata.inputs[0].default,
+        Some(Constant::Scalar(value)) if value == 1.0
+    ));
+    assert_eq!(metadata.inputs[0].description, Some("Number to double"));
+    assert_eq!(
+        metadata.inputs[0].hint,
+        InputHint {
+            min: Some(-10.0),
+            max: Some(10.0),
+            step: Some(0.5),
+        }
+    );
+    assert_eq!(metadata.outputs.len(), 1);
+    assert_eq!(metadata.outputs[0].name, "value");
+}
+
+#[test]
+fn evaluates_operators_of_other_crates() {
+    let mut double = Node::new(operator("Double"), Vector2::zero());
+    double.set_constant(PortId::from(0), Constant::Scalar(21.0));
+
+    if let Some(recorded) = run(record_graph(double), 1) {
+        assert_eq!(recorded, [42.0]);
+    }
+}
+
+#[test]
+fn keeps_evaluator_state_between_frames() {
+    let counter = Node::new(operator("Counter"), Vector2::zero());
+
+    if let Some(recorded) = run(record_graph(counter), 3) {
+        assert_eq!(recorded, [1.0, 2.0, 3.0]);
+    }
+}
+
+#[test]
+fn validates_required_inputs() {
+    let graph = Graph {
+        nodes: vec![Node::new(operator("Record"), Vector2::zero())],
+    };
+    let input = NodePortId(NodeId::root(), PortId::from(0));
+
+    assert!(graph.get_node(NodeId::root()).unwrap().describe().inputs[0].required);
+    assert_eq!(
+        graph.validate(),
+        [ValidationError {
+            path: vec![],
+            input,
+            kind: ValidationErrorKind::MissingConnection,
+        }]
+    );
+
+    let graph = record_graph(Node::new(operator("Double"), Vector2::zero()));
+
+    assert_eq!(graph.validate(), []);
+}
+
+#[test]
+fn replaces_the_connection_of_single_inputs() {
+    let mut graph = record_graph(Node::new(operator("Double"), Vector2::zero()));
+    graph.create_node(operator("Double"), Vector2::zero());
+
+    let input = NodePortId(NodeId::root(), PortId::from(0));
+    let output = NodePortId(NodeId::from(2), PortId::from(0));
+
+    graph.connect(&output, &input, Placement::Insert(0));
+
+    assert!(matches!(
+        graph.get_node(NodeId::root()).unwrap().get_input_state(PortId::from(0)),
+        Some(InputState::Connection(connections)) if connections == &[output]
+    ));
+}
+
+#[test]
+fn describes_enum_and_optional_inputs() {
+    let metadata = operator("Combine").describe();
+
+    assert_eq!(metadata.inputs[0].data_type, DataType::Enum);
+    assert_eq!(metadata.inputs[0].variants, ["Add", "Multiply"]);
+    assert!(matches!(
+        &metadata.inputs[0].default,
+        Some(Constant::Enum(name)) if &**name == "Multiply"
+    ));
+
+    assert!(metadata.inputs[2].optional);
+    assert!(matches!(
+        metadata.inputs[2].default_state(),
+        InputState::Connection(connections) if connections.is_empty()
+    ));
+}
+
+#[test]
+fn evaluates_enum_and_optional_inputs() {
+    let mut combine = Node::new(operator("Combine"), Vector2::zero());
+    combine.set_constant(PortId::from(1), Constant::Scalar(3.0));
+
+    // Without `b`, the operator receives `None` and outputs `a`.
+    if let Some(recorded) = run(record_graph(combine.clone()), 1) {
+        assert_eq!(recorded, [3.0]);
+    }
+
+    let mut graph = record_graph(combine);
+    let mut double = Node::new(operator("Double"), Vector2::zero());
+    double.set_constant(PortId::from(0), Constant::Scalar(2.0));
+    graph.nodes.push(double);
+    graph.connect(
+        &NodePortId(NodeId::from(2), PortId::from(0)),
+        &NodePortId(NodeId::from(1), PortId::from(2)),
+        Placement::Replace(0),
+    );
+
+    assert_eq!(graph.validate(), []);
+
+    if let Some(recorded) = run(graph.clone(), 1) {
+        assert_eq!(recorded, [12.0]);
+    }
+
+    // Variants are stored by name, and numbers from older graphs pick one by index.
+    for operation in [Constant::Enum("Add".into()), Constant::Int(0)] {
+        graph.nodes[1].set_constant(PortId::from(0), operation);
+
+        if let Some(recorded) = run(graph.clone(), 1) {
+            assert_eq!(recorded, [7.0]);
+        }
+    }
+
+    graph.nodes[1].set_constant(PortId::from(0), Constant::Enum("Divide".into()));
+
+    assert_eq!(
+        graph.validate(),
+        [ValidationError {
+            path: vec![],
+            input: NodePortId(NodeId::from(1), PortId::from(0)),
+            kind: ValidationErrorKind::UnknownVariant("Divide".into()),
+        }]
+    );
+}
+
+#[test]
+fn evaluates_any_number_of_outputs() {
+    let mut spread = Node::new(operator("Spread"), Vector2::zero());
+    spread.set_constant(PortId::from(1), Constant::Scalar(0.5));
+
+    assert_eq!(spread.describe().outputs.len(), 24);
+
+    let mut graph = record_graph(spread);
+    graph.connect(
+        &NodePortId(NodeId::from(1), PortId::from(23)),
+        &NodePortId(NodeId::root(), PortId::from(0)),
+        Placement::Replace(0),
+    );
+
+    if let Some(recorded) = run(graph.clone(), 1) {
+        assert_eq!(recorded, [11.5]);
+    }
+
+    // A connected count is only known while evaluating, so the node exposes the most outputs
+    // it can write.
+    let mut count = Node::new(operator("Alternate"), Vector2::zero());
+    count.set_constant(PortId::from(0), Constant::Int(30));
+    count.set_constant(PortId::from(1), Constant::Int(30));
+
+    graph.nodes.push(count);
+    graph.connect(
+        &NodePortId(NodeId::from(2), PortId::from(0)),
+        &NodePortId(NodeId::from(1), PortId::from(0)),
+        Placement::Replace(0),
+    );
+
+    assert_eq!(
+        graph.nodes[1].describe().outputs.len(),
+        MAXIMUM_SPREAD as usize
+    );
+
+    graph.connect(
+        &NodePortId(NodeId::from(1), PortId::from(29)),
+        &NodePortId(NodeId::root(), PortId::from(0)),
+        Placement::Replace(0),
+    );
+
+    if let Some(recorded) = run(graph, 1) {
+        assert_eq!(recorded, [14.5]);
+    }
+}
+
+#[test]
+fn fails_on_outputs_no_longer_written() {
+    // `Spread` writes 30 values in even frames, 10 in odd ones.
+    let mut count = Node::new(operator("Alternate"), Vector2::zero());
+    count.set_constant(PortId::from(0), Constant::Int(30));
+    count.set_constant(PortId::from(1), Constant::Int(10));
+
+    let mut graph = record_graph(Node::new(operator("Spread"), Vector2::zero()));
+    graph.nodes.push(count);
+    graph.connect(
+        &NodePortId(NodeId::from(2), PortId::from(0)),
+        &NodePortId(NodeId::from(1), PortId::from(0)),
+        Placement::Replace(0),
+    );
+    graph.connect(
+        &NodePortId(NodeId::from(1), PortId::from(20)),
+        &NodePortId(NodeId::root(), PortId::from(0)),
+        Placement::Replace(0),
+    );
+
+    let Some((mut renderer, target)) = headless_renderer() else {
+        eprintln!("no graphics adapter available, skipping");
+        return;
+    };
+
+    let interpreter = Interpreter::new(Demo {
+        graph,
+        ..Default::default()
+    });
+    take_recorded();
+
+    let mut run_frame = |frame: usize| {
+        interpreter.run(&mut InterpreterContext {
+            renderer: &mut renderer,
+            render_target: &target,
+            render_target_size: Vector2::new(4, 4),
+            frame: frame as f32,
+            time: frame as f32 / 60.0,
+        })
+    };
+
+    assert!(run_frame(0).is_ok());
+    assert_eq!(take_recorded(), [20.0]);
+
+    // The value of the previous frame isn't output again.
+    assert!(matches!(
+        run_frame(1),
+        Err(EvaluateError::MissingOutput(output))
+            if output == NodePortId(NodeId::from(1), PortId::from(20))
+    ));
+    assert_eq!(take_recorded(), []);
+}
+
+/// Demo recording an output of an `AudioSpectrum` node around 1kHz, whose soundtrack is a
+/// 1kHz sine starting after half a second.
+fn audio_spectrum_interpreter(output: usize) -> Interpreter {
+    let mut spectrum = Node::new(operator("AudioSpectrum"), Vector2::zero());
+    spectrum.set_constant(PortId::from(0), Constant::Scalar(900.0));
+    spectrum.set_constant(PortId::from(1), Constant::Scalar(1100.0));
+
+    let mut graph = record_graph(spectrum);
+    graph.connect(
+        &NodePortId(NodeId::from(1), PortId::from(output)),
+        &NodePortId(NodeId::root(), PortId::from(0)),
+        Placement::Replace(0),
+    );
+
+    let samples = (0..48000)
+        .map(|frame| {
+            let time = frame as f32 / 48000.0;
+
+            if time < 0.5 {
+                0.0
+            } else {
+                (2.0 * PI * 1000.0 * time).sin()
+            }
+        })
+        .collect();
+
+    let mut interpreter = Interpreter::new(Demo {
+        graph,
+        ..Default::default()
+    });
+    interpreter.set_soundtrack(Some(AudioBuffer::new(48000, 1, samples)));
+
+    interpreter
+}
+
+#[test]
+fn analyzes_the_soundtrack() {
+    // Frame 30 is at half a second, when the sine starts.
+    if let Some(energy) = run_interpreter(audio_spectrum_interpreter(0), 40) {
+        assert!(energy[..=30].iter().all(|energy| *energy == 0.0));
+        assert!(
+            energy[35..].iter().all(|energy| *energy > 0.2),
+            "{energy:?}"
+        );
+    }
+
+    if let Some(onsets) = run_interpreter(audio_spectrum_interpreter(2), 40) {
+        let frames: Vec<_> = (0..onsets.len()).filter(|i| onsets[*i] == 1.0).collect();
+
+        assert!(
+            !frames.is_empty() && frames.iter().all(|frame| (31..=33).contains(frame)),
+            "onsets at frames {frames:?}"
+        );
+    }
+}
+
+/// Demo repeating a `Counter` recorded by `RecordCommands` as many times as `count` outputs.
+fn repeated_counter_graph(count: Node) -> Graph {
+    let mut graph = Graph {
+        nodes: vec![
+            Node::new(operator("Repeat"), Vector2::zero()),
+            Node::new(operator("RecordCommands"), Vector2::zero()),
+            Node::new(operator("Counter"), Vector2::zero()),
+            count,
+        ],
+    };
+
+    for (output, input) in [((1, 0), (0, 1)), ((2, 0), (1, 0)), ((3, 0), (0, 0))] {
+        graph.connect(
+            &NodePortId(NodeId::from(output.0), PortId::from(output.1)),
+            &NodePortId(NodeId::from(input.0), PortId::from(input.1)),
+            Placement::Replace(0),
+        );
+    }
+
+    graph
+}
+
+#[test]
+fn keeps_a_state_per_iteration() {
+    let mut count = Node::new(operator("Alternate"), Vector2::zero());
+    count.set_constant(PortId::from(0), Constant::Int(2));
+    count.set_constant(PortId::from(1), Constant::Int(2));
+
+    if let Some(recorded) = run(repeated_counter_graph(count), 3) {
+        assert_eq!(recorded, [1.0, 1.0, 2.0, 2.0, 3.0, 3.0]);
+    }
+}
+
+#[test]
+fn drops_the_state_of_iterations_no_longer_evaluated() {
+    // Iterations are told apart by their index and count, so every frame evaluates other ones.
+    let mut count = Node::new(operator("Alternate"), Vector2::zero());
+    count.set_constant(PortId::from(0), Constant::Int(3));
+    count.set_constant(PortId::from(1), Constant::Int(1));
+
+    if let Some(recorded) = run(repeated_counter_graph(count), 4) {
+        // Without evicting them, the iterations of the first frame would count 2 in the third.
+        assert_eq!(recorded, [1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
+    }
+}
@@ -1,84 +1,57 @@
-use std::ffi::c_void;
-use std::{cell::RefCell, collections::HashMap, f32::consts::PI, mem};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
 
-use uuid::uuid;
-use wgpu::{Device, Queue, TextureFormat, TextureView, TextureViewDescriptor};
+use winit::dpi::PhysicalSize;
 use winit::event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
 use winit::window::Window;
 use winit::window::WindowBuilder;
 
-use tidal_core::default_program;
-use tidal_core::renderer::wgpu::context_window::WindowContextHolder;
-use tidal_core::renderer::wgpu::renderer::WgpuRenderer;
-use tidal_core::renderer::wgpu::{Context, Surface};
-use tidal_core::renderer::{RendererError, Vertex};
-use tidal_core::vm::instruction::Instruction;
-use tidal_core::vm::program::{Program, Resources};
-use tidal_core::vm::Interpreter;
+use tidal_core::cgmath::Vector2;
+use tidal_core::demo::Demo;
+use tidal_core::interpreter::{Interpreter, InterpreterContext};
+use tidal_core::renderer::{Context, Renderer};
 
-use crate::renderer::wgpu::{Context, ContextHolder, Screen};
+mod soundtrack;
 
+/// Device and queue of the window, shared with the renderer.
 #[derive(Debug)]
-pub struct WindowContext {
-    surface: wgpu::Surface,
-    surface_config: wgpu::SurfaceConfiguration,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-}
-
-#[derive(Debug)]
-pub struct WindowContextSurfaceTexture {
-    surface: wgpu::SurfaceTexture,
-    view: wgpu::TextureView,
+pub struct RendererContext {
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    surface_format: wgpu::TextureFormat,
 }
 
-impl Surface for WindowContextSurfaceTexture {
+impl Context for RendererContext {
     #[inline(always)]
-    fn view(&self) -> &wgpu::TextureView {
-        &self.view
-    }
-
-    #[inline(always)]
-    fn present(self) {
-        self.surface.present()
-    }
-}
-
-impl Context for WindowContext {
-    type Surface = WindowContextSurfaceTexture;
-
-    #[inline(always)]
-    fn device(&self) -> &Device {
+    fn device(&self) -> &wgpu::Device {
         &self.device
     }
 
     #[inline(always)]
-    fn queue(&self) -> &Queue {
+    fn queue(&self) -> &wgpu::Queue {
         &self.queue
     }
 
     #[inline(always)]
-    fn surface_format(&self) -> TextureFormat {
-        self.surface_config.format
+    fn surface_format(&self) -> wgpu::TextureFormat {
+        self.surface_format
     }
+}
 
-    #[inline(always)]
-    fn prepare_surface(&self) -> Self::Surface {
-        let surface = self
-            .surface
-            .get_current_texture()
-            .expect("unexpected surface error during WindowContext::prepare_screen");
-
-        let view = surface
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-
-        WindowContextSurfaceTexture { surface, view }
-    }
+/// Surface the demo is presented on.
+#[derive(Debug)]
+pub struct WindowSurface {
+    surface: wgpu::Surface,
+    surface_config: wgpu::SurfaceConfiguration,
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
 }
 
-impl WindowContext {
+impl WindowSurface {
     pub async fn new(window: &Window) -> Self {
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
             backends: wgpu::Backends::all(),
@@ -113,8 +86,7 @@ impl WindowContext {
         let surface_format = surface_caps
             .formats
             .iter()
-            .filter(|f| f.is_srgb())
-            .next()
+            .find(|f| f.is_srgb())
             .copied()
             .unwrap_or(surface_caps.formats[0]);
 
@@ -123,8 +95,8 @@ impl WindowContext {
         let surface_config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             format: surface_format,
-            width: size.width,
-            height: size.height,
+            width: size.width.max(1),
+            height: size.height.max(1),
             present_mode: surface_caps.present_modes[0],
             alpha_mode: surface_caps.alpha_modes[0],
             view_formats: vec![],
@@ -135,17 +107,73 @@ impl WindowContext {
         Self {
             surface,
             surface_config,
-            device,
-            queue,
+            device: Arc::new(device),
+            queue: Arc::new(queue),
         }
     }
+
+    pub fn renderer_context(&self) -> RendererContext {
+        RendererContext {
+            device: self.device.clone(),
+            queue: self.queue.clone(),
+            surface_format: self.surface_config.format,
+        }
+    }
+
+    #[inline]
+    pub fn size(&self) -> Vector2<u32> {
+        Vector2::new(self.surface_config.width, self.surface_config.height)
+    }
+
+    /// Reconfigures the surface for a new window size. Minimized windows keep the last size.
+    pub fn resize(&mut self, size: PhysicalSize<u32>) {
+        if size.width == 0 || size.height == 0 {
+            return;
+        }
+
+        self.surface_config.width = size.width;
+        self.surface_config.height = size.height;
+        self.surface.configure(&self.device, &self.surface_config);
+    }
+
+    /// Returns the texture of the next frame, reconfiguring the surface when it was lost.
+    pub fn current_texture(&mut self) -> Option<wgpu::SurfaceTexture> {
+        match self.surface.get_current_texture() {
+            Ok(texture) => Some(texture),
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                self.surface.configure(&self.device, &self.surface_config);
+                None
+            }
+            Err(e) => {
+                log::error!("failed to acquire the next frame: {e}");
+                None
+            }
+        }
+    }
+}
+
+fn load_demo(path: &Path) -> Demo {
+    let demo = File::open(path)
+        .map_err(|e| e.to_string())
+        .and_then(|file| serde_json::from_reader(BufReader::new(file)).map_err(|e| e.to_string()));
+
+    let demo = demo.unwrap_or_else(|e| {
+        log::error!("failed to load demo {path:?}: {e}");
+        Demo::default()
+    });
+
+    for error in demo.graph.validate() {
+        log::warn!("invalid demo graph: {error}");
+    }
+
+    demo
 }
 
 fn main() {
-    pollster::block_on(run()).unwrap();
+    pollster::block_on(run());
 }
 
-async fn run() -> Result<(), RendererError> {
+async fn run() {
     env_logger::init();
 
     let event_loop = EventLoop::new();
@@ -155,19 +183,64 @@ async fn run() -> Result<(), RendererError> {
         .build(&event_loop)
         .unwrap();
 
-    let context = WindowContextHolder::new(&window).await;
-    let mut renderer = WgpuRenderer::new(context);
+    let mut window_surface = WindowSurface::new(&window).await;
+    let mut renderer = Renderer::new(Box::new(window_surface.renderer_context()));
+
+    let demo_path = std::env::args().nth(1).map(PathBuf::from);
+    let demo = demo_path.as_deref().map(load_demo).unwrap_or_default();
+    let root = demo_path
+        .as_deref()
+        .and_then(Path::parent)
+        .unwrap_or(Path::new("."));
+
+    let buffer = soundtrack::load_soundtrack(&demo, root);
+    let soundtrack = buffer.clone().and_then(soundtrack::start_soundtrack);
 
-    let program = default_program();
+    // Audio operators analyze the decoded soundtrack rather than what the device plays.
+    let mut interpreter = Interpreter::new(demo);
+    interpreter.set_soundtrack(buffer);
 
-    let mut interpreter = Interpreter::new(program);
-    interpreter.run_init(&mut renderer);
+    let started = Instant::now();
+    let mut frame = 0;
 
     event_loop.run(move |event, _, control_flow| match event {
         Event::RedrawRequested(_) => {
-            interpreter.run_frame(&mut renderer);
+            let time = started.elapsed().as_secs_f32();
+
+            // The demo clock leads, the soundtrack follows it.
+            if let Some(soundtrack) = &soundtrack {
+                soundtrack.sync(time);
+            }
+
+            let Some(surface_texture) = window_surface.current_texture() else {
+                return;
+            };
+
+            let view = surface_texture
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default());
+
+            let result = interpreter.run(&mut InterpreterContext {
+                renderer: &mut renderer,
+                render_target: &view,
+                render_target_size: window_surface.size(),
+                frame: frame as f32,
+                time,
+            });
+
+            if let Err(e) = result {
+                log::error!("failed to evaluate the demo: {e:?}");
+            }
+
+            surface_texture.present();
+            frame += 1;
         }
+        Event::MainEventsCleared => window.request_redraw(),
         Event::WindowEvent { ref event, .. } => match event {
+            WindowEvent::Resized(size) => window_surface.resize(*size),
+            WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                window_surface.resize(**new_inner_size)
+            }
             WindowEvent::CloseRequested
             | WindowEvent::KeyboardInput {
                 input:
@@ -180,7 +253,6 @@ async fn run() -> Result<(), RendererError> {
             } => *control_flow = ControlFlow::ExitWithCode(0),
             _ => {}
         },
-        // Event::MainEventsCleared => renderer.context.window.request_redraw(),
         _ => {}
     });
 }
@@ -0,0 +1,32 @@
+use std::path::Path;
+
+use tidal_core::audio::{AudioBuffer, AudioPlayer, DeviceOutput};
+use tidal_core::demo::Demo;
+
+/// Decodes the demo's soundtrack, resolving its path from the project directory. Demos whose
+/// soundtrack fails to decode run silently.
+pub fn load_soundtrack(demo: &Demo, root: &Path) -> Option<AudioBuffer> {
+    let soundtrack = demo.soundtrack.as_ref()?;
+
+    soundtrack
+        .load(root)
+        .map_err(|e| log::error!("failed to decode soundtrack {:?}: {e}", soundtrack.path))
+        .ok()
+}
+
+/// Starts playing a soundtrack on the default audio device, or returns `None` when there is no
+/// device to play it on.
+pub fn start_soundtrack(buffer: AudioBuffer) -> Option<AudioPlayer<DeviceOutput>> {
+    let player = DeviceOutput::new().and_then(|output| AudioPlayer::new(output, buffer));
+
+    match player {
+        Ok(player) => {
+            player.play();
+            Some(player)
+        }
+        Err(e) => {
+            log::error!("failed to play soundtrack: {e}");
+            None
+        }
+    }
+}